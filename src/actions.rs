@@ -0,0 +1,437 @@
+use crate::difficulty::{Condition, Difficulty, RECOVERY_LEVEL, RUN_AWAY_SECONDS};
+use crate::pet::Pet;
+use crate::rules::Rules;
+use crate::utils::cap_stat;
+
+/// Hours in one decay interval
+pub const DECAY_INTERVAL_HOURS: i64 = 8;
+
+/// Seconds in one decay interval
+pub const DECAY_INTERVAL_SECONDS: u64 = DECAY_INTERVAL_HOURS as u64 * 3600;
+
+/// Seconds of sleep per point of energy regained: +5 over a full 9-hour night
+pub const REST_SECONDS_PER_POINT: u64 = 9 * 3600 / 5;
+
+/// XP needed to gain a level
+pub const XP_PER_LEVEL: u8 = 100;
+
+// The stat rules for every action, kept free of I/O so the commands and
+// journal replay apply exactly the same changes. The numbers come from `Rules`,
+// adjusted for the pet's difficulty
+
+fn change(stat: u8, by: i32) -> u8 {
+    cap_stat(stat as i32 + by, 0, 100)
+}
+
+/// Returns true if the pet can't hold it any longer
+pub fn accident_due(pet: &Pet, rules: &Rules) -> bool {
+    pet.potty_level > rules.accident.threshold
+}
+
+/// An accident: cleanliness -30, happiness -15 by default
+pub fn accident(pet: &mut Pet, rules: &Rules) {
+    let rules = &pet.difficulty.rules(rules);
+    pet.cleanliness = change(pet.cleanliness, rules.accident.cleanliness);
+    pet.happiness = change(pet.happiness, rules.accident.happiness);
+}
+
+/// Feeding: hunger +20, happiness +10 by default
+pub fn feed(pet: &mut Pet, rules: &Rules) {
+    let rules = &pet.difficulty.rules(rules);
+    pet.hunger = change(pet.hunger, rules.feed.hunger);
+    pet.happiness = change(pet.happiness, rules.feed.happiness);
+    recover(pet);
+}
+
+/// Playing: happiness +15, energy -10 by default
+pub fn play(pet: &mut Pet, rules: &Rules) {
+    let rules = &pet.difficulty.rules(rules);
+    pet.happiness = change(pet.happiness, rules.play.happiness);
+    pet.energy = change(pet.energy, rules.play.energy);
+}
+
+/// Walking: energy +15, and potty -50 if the pet relieved itself, by default
+pub fn walk(pet: &mut Pet, relieved: bool, rules: &Rules) {
+    let rules = &pet.difficulty.rules(rules);
+    pet.energy = change(pet.energy, rules.walk.energy);
+    if relieved {
+        pet.potty_level = change(pet.potty_level, rules.walk.potty);
+    }
+}
+
+/// Training: energy -15, XP +20, with a level (and happiness +5) per 100 XP, by default
+/// Returns the number of levels gained
+pub fn train(pet: &mut Pet, rules: &Rules) -> u32 {
+    let rules = &pet.difficulty.rules(rules);
+    pet.energy = change(pet.energy, rules.train.energy);
+    pet.xp += rules.train.xp;
+
+    let mut levels = 0;
+    while pet.xp >= XP_PER_LEVEL {
+        pet.xp -= XP_PER_LEVEL;
+        pet.level += 1;
+        pet.happiness = change(pet.happiness, rules.train.level_up_happiness);
+        levels += 1;
+    }
+    levels
+}
+
+/// A potty break: potty level resets, and happiness +5 (by default) unless it came too late
+pub fn potty(pet: &mut Pet, had_accident: bool, rules: &Rules) {
+    let rules = &pet.difficulty.rules(rules);
+    pet.potty_level = 0;
+    if !had_accident {
+        pet.happiness = change(pet.happiness, rules.potty.happiness);
+    }
+}
+
+/// Cleaning: cleanliness +40 by default
+pub fn clean(pet: &mut Pet, rules: &Rules) {
+    let rules = &pet.difficulty.rules(rules);
+    pet.cleanliness = change(pet.cleanliness, rules.clean.cleanliness);
+    recover(pet);
+}
+
+/// An ill pet gets better once it is fed and clean enough again
+fn recover(pet: &mut Pet) {
+    if pet.condition == Condition::Ill
+        && pet.hunger >= RECOVERY_LEVEL
+        && pet.cleanliness >= RECOVERY_LEVEL
+    {
+        pet.condition = Condition::Healthy;
+    }
+}
+
+/// Decay over `seconds` of elapsed time, by default hunger -3, happiness -2,
+/// cleanliness -2 and potty +2 per 8-hour interval
+/// Each stat changes by a point as soon as it is due (with the defaults, hunger
+/// every 2h40m and the others every 4h), and `decay_progress` carries the time
+/// towards the next ones. Casual pets stop at the difficulty's floor, and
+/// hardcore pets suffer for being left at zero
+/// A pet on vacation doesn't decay at all, unless it has a sitter, who feeds and
+/// walks it whenever a visit falls due
+pub fn decay(pet: &mut Pet, mut seconds: u64, rules: &Rules) {
+    while let Some(vacation) = &mut pet.vacation {
+        let Some(until_visit) = vacation.until_visit() else {
+            return;
+        };
+        if seconds < until_visit {
+            vacation.visit_progress += seconds as u32;
+            break;
+        }
+        vacation.visit_progress = 0;
+        decay_alone(pet, until_visit, rules);
+        feed(pet, rules);
+        walk(pet, true, rules);
+        seconds -= until_visit;
+    }
+    decay_alone(pet, seconds, rules);
+}
+
+/// Decay with nobody looking after the pet
+fn decay_alone(pet: &mut Pet, seconds: u64, rules: &Rules) {
+    let floor = pet.difficulty.decay_floor();
+    let interval = pet.difficulty.decay_interval();
+    let progress = pet.decay_progress;
+    let due = |per_interval: i32| {
+        let points = points_due(
+            per_interval.unsigned_abs() as u64,
+            interval,
+            progress,
+            seconds,
+        )
+        .min(100);
+        points as i32 * per_interval.signum()
+    };
+    let unhappy_for = seconds.saturating_sub(seconds_until_due(
+        rules.decay.happiness.unsigned_abs() as u64,
+        interval,
+        progress,
+        pet.happiness as u64,
+    ));
+
+    pet.hunger = decayed(pet.hunger, due(rules.decay.hunger), floor);
+    pet.happiness = decayed(pet.happiness, due(rules.decay.happiness), floor);
+    pet.cleanliness = decayed(pet.cleanliness, due(rules.decay.cleanliness), floor);
+    pet.potty_level = decayed(pet.potty_level, due(rules.decay.potty), floor);
+    pet.decay_progress = ((pet.decay_progress as u64 + seconds) % interval) as u32;
+
+    if pet.difficulty == Difficulty::Hardcore {
+        suffer(pet, unhappy_for);
+    }
+}
+
+/// A stat after decay, which takes it no further than `floor` from the edge it
+/// is heading for, unless it was already past it
+fn decayed(stat: u8, by: i32, floor: u8) -> u8 {
+    let changed = change(stat, by);
+    if by < 0 {
+        changed.max(floor.min(stat))
+    } else {
+        changed.min((100 - floor).max(stat))
+    }
+}
+
+/// Neglect on hardcore: a pet left starving or filthy falls ill, and one left
+/// at zero happiness for `RUN_AWAY_SECONDS` runs away
+/// `unhappy_for` is how much of the decay happiness spent at zero
+fn suffer(pet: &mut Pet, unhappy_for: u64) {
+    if pet.condition == Condition::Healthy && (pet.hunger == 0 || pet.cleanliness == 0) {
+        pet.condition = Condition::Ill;
+    }
+    pet.neglect_seconds = match pet.happiness {
+        0 => pet
+            .neglect_seconds
+            .saturating_add(unhappy_for.min(u32::MAX as u64) as u32),
+        _ => 0,
+    };
+    if pet.neglect_seconds >= RUN_AWAY_SECONDS {
+        pet.condition = Condition::RanAway;
+    }
+}
+
+/// Returns true if decay over `seconds` would change any stat by a point, however
+/// full or empty the stats already are, or bring a pet-sitter's visit
+pub fn decay_due(pet: &Pet, seconds: u64, rules: &Rules) -> bool {
+    if let Some(vacation) = &pet.vacation {
+        match vacation.until_visit() {
+            None => return false,
+            Some(until_visit) if seconds >= until_visit => return true,
+            Some(_) => {}
+        }
+    }
+    let decay = &rules.decay;
+    let interval = pet.difficulty.decay_interval();
+    [
+        decay.hunger,
+        decay.happiness,
+        decay.cleanliness,
+        decay.potty,
+    ]
+    .into_iter()
+    .any(|per_interval| {
+        points_due(
+            per_interval.unsigned_abs() as u64,
+            interval,
+            pet.decay_progress,
+            seconds,
+        ) > 0
+    })
+}
+
+/// Rest over `seconds` asleep: energy +1 per `REST_SECONDS_PER_POINT`, with
+/// `rest_progress` carrying the time towards the next point
+/// A pet on vacation without a sitter is frozen, so it doesn't rest either
+pub fn rest(pet: &mut Pet, seconds: u64) {
+    if is_frozen(pet) {
+        return;
+    }
+    let slept = pet.rest_progress as u64 + seconds;
+    pet.energy = change(pet.energy, (slept / REST_SECONDS_PER_POINT).min(100) as i32);
+    pet.rest_progress = (slept % REST_SECONDS_PER_POINT) as u32;
+}
+
+/// Returns true if resting for `seconds` would regain a point of energy
+pub fn rest_due(pet: &Pet, seconds: u64) -> bool {
+    !is_frozen(pet) && pet.rest_progress as u64 + seconds >= REST_SECONDS_PER_POINT
+}
+
+/// Returns true if the pet is on vacation with nobody looking after it
+fn is_frozen(pet: &Pet) -> bool {
+    pet.vacation
+        .as_ref()
+        .is_some_and(|vacation| !vacation.sitter)
+}
+
+/// Whole points of a stat that changes by `per_interval` each `interval` seconds
+/// falling due between `progress` seconds into an interval and `seconds` later
+fn points_due(per_interval: u64, interval: u64, progress: u32, seconds: u64) -> u64 {
+    let start = progress as u64;
+    per_interval * (start + seconds) / interval - per_interval * start / interval
+}
+
+/// Seconds from `progress` seconds into an interval until `points` of a stat that
+/// changes by `per_interval` each `interval` seconds have fallen due, or
+/// `u64::MAX` if never
+fn seconds_until_due(per_interval: u64, interval: u64, progress: u32, points: u64) -> u64 {
+    if points == 0 {
+        return 0;
+    }
+    if per_interval == 0 {
+        return u64::MAX;
+    }
+    let start = progress as u64;
+    let already = per_interval * start / interval;
+    ((points + already) * interval).div_ceil(per_interval) - start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::CASUAL_FLOOR;
+    use crate::vacation::Vacation;
+    use chrono::Utc;
+
+    #[test]
+    fn test_train_levels_up_and_returns_levels_gained() {
+        // Given: a pet 10 XP short of a level
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.xp = 90;
+
+        // When: training
+        let levels = train(&mut pet, &Rules::default());
+
+        // Then: one level is gained and the spare XP carries over
+        assert_eq!(levels, 1);
+        assert_eq!(pet.level, 2);
+        assert_eq!(pet.xp, 10);
+        assert_eq!(pet.happiness, 85);
+    }
+
+    #[test]
+    fn test_accident_is_due_only_above_threshold() {
+        // Given: pets at and just above the threshold
+        let rules = Rules::default();
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.potty_level = rules.accident.threshold;
+        assert!(!accident_due(&pet, &rules));
+
+        // When: the potty level goes one higher
+        pet.potty_level += 1;
+
+        // Then: an accident is due
+        assert!(accident_due(&pet, &rules));
+    }
+
+    #[test]
+    fn test_decay_is_the_same_however_often_it_is_applied() {
+        // Given: two identical pets
+        let rules = Rules::default();
+        let mut checked_hourly = Pet::new("Kylo".to_string(), "dog".to_string());
+        let mut left_alone = checked_hourly.clone();
+
+        // When: one decays an hour at a time for a day and the other all at once
+        for _ in 0..24 {
+            decay(&mut checked_hourly, 3600, &rules);
+        }
+        decay(&mut left_alone, 24 * 3600, &rules);
+
+        // Then: they end up the same, three intervals down
+        assert_eq!(checked_hourly, left_alone);
+        assert_eq!(left_alone.hunger, 71);
+        assert_eq!(left_alone.potty_level, 6);
+        assert_eq!(left_alone.decay_progress, 0);
+    }
+
+    #[test]
+    fn test_actions_follow_tuned_rules() {
+        // Given: rules with more filling food and faster hunger
+        let mut rules = Rules::default();
+        rules.feed.hunger = 35;
+        rules.decay.hunger = -6;
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.hunger = 50;
+
+        // When: the pet is fed, then left for one interval
+        feed(&mut pet, &rules);
+        decay(&mut pet, DECAY_INTERVAL_SECONDS, &rules);
+
+        // Then: both use the tuned numbers
+        assert_eq!(pet.hunger, 50 + 35 - 6);
+    }
+
+    #[test]
+    fn test_care_is_scaled_by_difficulty() {
+        // Given: a hungry, dirty pet on each difficulty
+        let rules = Rules::default();
+        let cared_for = |difficulty| {
+            let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+            pet.difficulty = difficulty;
+            pet.hunger = 10;
+            pet.cleanliness = 10;
+
+            // When: it is fed and cleaned
+            feed(&mut pet, &rules);
+            clean(&mut pet, &rules);
+            (pet.hunger, pet.cleanliness)
+        };
+
+        // Then: casual care does twice as much and hardcore care two thirds as much
+        assert_eq!(cared_for(Difficulty::Casual), (50, 90));
+        assert_eq!(cared_for(Difficulty::Normal), (30, 50));
+        assert_eq!(cared_for(Difficulty::Hardcore), (23, 36));
+    }
+
+    #[test]
+    fn test_hardcore_neglect_makes_a_pet_ill_then_run_away() {
+        // Given: two identical hardcore pets
+        let rules = Rules::default();
+        let mut checked_hourly = Pet::new("Kylo".to_string(), "dog".to_string());
+        checked_hourly.difficulty = Difficulty::Hardcore;
+        let mut left_alone = checked_hourly.clone();
+
+        // When: both are neglected for nine days, one checked on every hour
+        for _ in 0..9 * 24 {
+            decay(&mut checked_hourly, 3600, &rules);
+        }
+        decay(&mut left_alone, 9 * 24 * 3600, &rules);
+
+        // Then: both are ill but still around, as happiness only just hit zero
+        assert_eq!(checked_hourly, left_alone);
+        assert_eq!(left_alone.condition, Condition::Ill);
+
+        // When: a day more passes
+        decay(&mut left_alone, 24 * 3600, &rules);
+
+        // Then: the pet has run away
+        assert_eq!(left_alone.condition, Condition::RanAway);
+    }
+
+    #[test]
+    fn test_casual_decay_stops_at_the_floor() {
+        // Given: a casual pet
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.difficulty = Difficulty::Casual;
+
+        // When: it is left alone for a month
+        decay(&mut pet, 30 * 24 * 3600, &Rules::default());
+
+        // Then: no stat has gone past the floor, and nothing bad happened
+        assert_eq!(pet.hunger, CASUAL_FLOOR);
+        assert_eq!(pet.happiness, CASUAL_FLOOR);
+        assert_eq!(pet.potty_level, 100 - CASUAL_FLOOR);
+        assert_eq!(pet.condition, Condition::Healthy);
+    }
+
+    #[test]
+    fn test_vacation_freezes_decay_or_brings_a_sitter() {
+        // Given: a pet on vacation at home alone, and one with a sitter
+        let rules = Rules::default();
+        let start = Utc::now();
+        let mut frozen = Pet::new("Kylo".to_string(), "dog".to_string());
+        frozen.vacation = Some(Vacation::new(start, false));
+        let mut checked_hourly = Pet::new("Luna".to_string(), "cat".to_string());
+        checked_hourly.vacation = Some(Vacation::new(start, true));
+        let mut left_alone = checked_hourly.clone();
+
+        // When: two weeks pass, half of them asleep, with one sitter's pet
+        // checked on every hour
+        let before = frozen.clone();
+        decay(&mut frozen, 14 * 24 * 3600, &rules);
+        rest(&mut frozen, 7 * 24 * 3600);
+        for _ in 0..14 * 24 {
+            decay(&mut checked_hourly, 3600, &rules);
+        }
+        decay(&mut left_alone, 14 * 24 * 3600, &rules);
+
+        // Then: the first pet hasn't changed or rested, and the sitter kept the other fed
+        // and walked the same however often it was checked
+        assert_eq!(frozen, before);
+        assert!(!decay_due(&frozen, 14 * 24 * 3600, &rules));
+        assert!(!rest_due(&frozen, 7 * 24 * 3600));
+        assert_eq!(checked_hourly, left_alone);
+        assert_eq!(left_alone.hunger, 100);
+        assert_eq!(left_alone.potty_level, 0);
+        assert_eq!(left_alone.vacation.unwrap().visit_progress, 0);
+    }
+}
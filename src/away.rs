@@ -0,0 +1,176 @@
+use crate::actions::DECAY_INTERVAL_SECONDS;
+use crate::difficulty::Condition;
+use crate::history::PetStats;
+use crate::pet::Pet;
+use crate::rules::Rules;
+
+/// Something a pet became while it was left alone
+/// The levels crossed come from the `catch_up` and `accident` rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crossing {
+    /// Hunger fell below `catch_up.hungry`
+    Hungry,
+    /// Happiness fell below `catch_up.bored`
+    Bored,
+    /// Cleanliness fell below `catch_up.dirty`
+    Dirty,
+    /// The potty level rose above the accident threshold
+    Desperate,
+    /// Sleep took energy back up to `catch_up.rested` or more
+    Rested,
+    Ill,
+    RanAway,
+}
+
+impl Crossing {
+    /// The crossings between two states of a pet, most pressing last
+    fn between(before: &Pet, after: &Pet, rules: &Rules) -> Vec<Crossing> {
+        let levels = &rules.catch_up;
+        let crossed = |was: bool, is: bool| !was && is;
+        [
+            (
+                Crossing::Rested,
+                crossed(
+                    before.energy >= levels.rested,
+                    after.energy >= levels.rested,
+                ),
+            ),
+            (
+                Crossing::Hungry,
+                crossed(before.hunger < levels.hungry, after.hunger < levels.hungry),
+            ),
+            (
+                Crossing::Bored,
+                crossed(
+                    before.happiness < levels.bored,
+                    after.happiness < levels.bored,
+                ),
+            ),
+            (
+                Crossing::Dirty,
+                crossed(
+                    before.cleanliness < levels.dirty,
+                    after.cleanliness < levels.dirty,
+                ),
+            ),
+            (
+                Crossing::Desperate,
+                crossed(
+                    before.potty_level > rules.accident.threshold,
+                    after.potty_level > rules.accident.threshold,
+                ),
+            ),
+            (
+                Crossing::Ill,
+                crossed(
+                    before.condition == Condition::Ill,
+                    after.condition == Condition::Ill,
+                ),
+            ),
+            (
+                Crossing::RanAway,
+                crossed(
+                    before.condition == Condition::RanAway,
+                    after.condition == Condition::RanAway,
+                ),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, crossed)| *crossed)
+        .map(|(crossing, _)| crossing)
+        .collect()
+    }
+}
+
+/// What decay and rest did to a pet over a stretch of time left alone
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecaySummary {
+    /// Seconds of decay applied
+    pub seconds: u64,
+    /// How many of those seconds the pet spent asleep
+    pub asleep: u64,
+    pub before: PetStats,
+    pub after: PetStats,
+    pub crossings: Vec<Crossing>,
+}
+
+impl DecaySummary {
+    pub fn new(before: &Pet, after: &Pet, seconds: u64, asleep: u64, rules: &Rules) -> Self {
+        Self {
+            seconds,
+            asleep,
+            before: PetStats::from(before),
+            after: PetStats::from(after),
+            crossings: Crossing::between(before, after, rules),
+        }
+    }
+
+    /// Whole decay intervals the stretch covered
+    pub fn intervals(&self) -> u64 {
+        self.seconds / DECAY_INTERVAL_SECONDS
+    }
+
+    /// Each stat that changed, with how much by
+    pub fn changes(&self) -> Vec<(&'static str, i64)> {
+        self.before
+            .fields()
+            .into_iter()
+            .zip(self.after.fields())
+            .filter(|((_, before), (_, after))| before != after)
+            .map(|((name, before), (_, after))| (name, after - before))
+            .collect()
+    }
+
+    /// Returns true if the pet was away for at least a whole interval and it
+    /// changed something
+    pub fn is_worth_telling(&self) -> bool {
+        self.intervals() > 0 && (!self.changes().is_empty() || !self.crossings.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_lists_changes_and_crossings() {
+        // Given: a pet before and after a couple of days alone
+        let before = Pet::new("Kylo".to_string(), "dog".to_string());
+        let mut after = before.clone();
+        after.hunger = 35;
+        after.happiness = 60;
+
+        // When: summarising the time away
+        let summary = DecaySummary::new(&before, &after, 2 * 24 * 3600, 0, &Rules::default());
+
+        // Then: it counts the intervals, the losses and the new hunger
+        assert_eq!(summary.intervals(), 6);
+        assert_eq!(
+            summary.changes(),
+            vec![("hunger", 35 - before.hunger as i64), ("happiness", -20)]
+        );
+        assert_eq!(summary.crossings, vec![Crossing::Hungry]);
+        assert!(summary.is_worth_telling());
+    }
+
+    #[test]
+    fn test_crossings_follow_the_rules() {
+        // Given: rules with a lower accident threshold and a stricter idea of hungry
+        let mut rules = Rules::default();
+        rules.accident.threshold = 60;
+        rules.catch_up.hungry = 70;
+        let before = Pet::new("Kylo".to_string(), "dog".to_string());
+        let mut after = before.clone();
+        after.hunger = 65;
+        after.potty_level = 70;
+
+        // When: summarising the time away
+        let summary = DecaySummary::new(&before, &after, 24 * 3600, 0, &rules);
+
+        // Then: the crossings use those levels
+        assert_eq!(
+            summary.crossings,
+            vec![Crossing::Hungry, Crossing::Desperate]
+        );
+    }
+}
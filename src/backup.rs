@@ -0,0 +1,313 @@
+use crate::clock::Clock;
+use crate::persistence::crypto::{read_sealed, write_sealed};
+use crate::persistence::{PetFileError, parse_pet, pet_slug, serialize_pet};
+use crate::pet::Pet;
+use crate::store::PetStore;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory (inside the data dir) holding the daily backups
+pub const BACKUP_DIR: &str = "backups";
+
+/// How many daily backups are kept before the oldest is removed
+pub const BACKUPS_KEPT: usize = 7;
+
+/// Backup ids are the local date the backup was taken on
+const ID_FORMAT: &str = "%Y-%m-%d";
+
+/// A backup file: every pet as a versioned save document, so restoring an old
+/// backup goes through the same schema migrations as an old save file
+#[derive(Serialize, Deserialize)]
+struct BackupFile {
+    taken_at: DateTime<Utc>,
+    pets: Vec<serde_json::Value>,
+}
+
+/// A daily backup of the roster
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    /// The local date the backup belongs to, e.g. "2025-10-17"
+    pub id: String,
+    /// When the backup was last written
+    pub taken_at: DateTime<Utc>,
+    /// The pets in the backup, sorted by name
+    pub pets: Vec<Pet>,
+}
+
+/// The id of the backup taken today by the clock's reckoning
+pub fn todays_id(clock: &dyn Clock) -> String {
+    clock.today().format(ID_FORMAT).to_string()
+}
+
+fn backup_path(dir: &Path, id: &str) -> io::Result<PathBuf> {
+    // Ids are dates, which also keeps them from naming paths outside the backup dir
+    NaiveDate::parse_from_str(id, ID_FORMAT).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "'{}' is not a backup id (expected a date like 2025-10-17)",
+                id
+            ),
+        )
+    })?;
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+/// The data directory a backup directory is in, whose key encrypts the backups
+fn data_dir_of(dir: &Path) -> &Path {
+    dir.parent().unwrap_or(dir)
+}
+
+fn read_backup_file(dir: &Path, id: &str) -> io::Result<Backup> {
+    let path = backup_path(dir, id)?;
+    let contents = read_sealed(data_dir_of(dir), &path).map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "No backup '{}'. Use 'termipet backup list' to see your backups.",
+                    id
+                ),
+            )
+        } else {
+            e
+        }
+    })?;
+    let invalid = |e: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Backup {} is damaged ({})", path.display(), e),
+        )
+    };
+
+    let file: BackupFile = serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+    let mut pets = Vec::new();
+    for document in file.pets {
+        // A pet old enough to need a timestamp gets the one the backup was taken at
+        match parse_pet(&document.to_string(), file.taken_at) {
+            Ok((pet, _)) => pets.push(pet),
+            Err(PetFileError::TooNew(version)) => {
+                return Err(invalid(format!(
+                    "written by a newer termipet, schema v{}",
+                    version
+                )));
+            }
+            Err(PetFileError::Corrupt(e)) => return Err(invalid(e)),
+        }
+    }
+    pets.sort_by_key(|pet| pet_slug(&pet.name));
+
+    Ok(Backup {
+        id: id.to_string(),
+        taken_at: file.taken_at,
+        pets,
+    })
+}
+
+/// Lists the backups in a directory, newest first
+/// A damaged backup is skipped with a warning so the others stay usable
+fn read_backups(dir: &Path) -> io::Result<Vec<Backup>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut ids: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let id = name.strip_suffix(".json")?;
+            NaiveDate::parse_from_str(id, ID_FORMAT).ok()?;
+            Some(id.to_string())
+        })
+        .collect();
+    // Dates sort as text, so reversing puts the newest first
+    ids.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut backups = Vec::new();
+    for id in ids {
+        match read_backup_file(dir, &id) {
+            Ok(backup) => backups.push(backup),
+            Err(e) => eprintln!("⚠️  Skipping backup {}: {}", id, e),
+        }
+    }
+    Ok(backups)
+}
+
+/// Writes the pets into the backup for `today`, then removes backups beyond `BACKUPS_KEPT`
+/// Pets already in today's backup that are no longer in `pets` are kept, so a pet
+/// released today can still be restored
+fn write_backup(
+    dir: &Path,
+    pets: &[Pet],
+    today: NaiveDate,
+    taken_at: DateTime<Utc>,
+) -> io::Result<String> {
+    let id = today.format(ID_FORMAT).to_string();
+    let mut merged: Vec<Pet> = match read_backup_file(dir, &id) {
+        Ok(backup) => backup.pets,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    for pet in pets {
+        merged.retain(|kept| pet_slug(&kept.name) != pet_slug(&pet.name));
+        merged.push(pet.clone());
+    }
+    merged.sort_by_key(|pet| pet_slug(&pet.name));
+
+    let documents = merged
+        .iter()
+        .map(|pet| {
+            serialize_pet(pet).and_then(|json| {
+                serde_json::from_str(&json)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            })
+        })
+        .collect::<io::Result<Vec<serde_json::Value>>>()?;
+    let file = BackupFile {
+        taken_at,
+        pets: documents,
+    };
+    let json = serde_json::to_string_pretty(&file).map_err(io::Error::other)?;
+
+    fs::create_dir_all(dir)?;
+    write_sealed(data_dir_of(dir), &backup_path(dir, &id)?, &json)?;
+
+    for old in read_backups(dir)?.iter().skip(BACKUPS_KEPT) {
+        fs::remove_file(backup_path(dir, &old.id)?)?;
+    }
+    Ok(id)
+}
+
+/// Backs up every pet into today's backup
+/// Call it while holding the store lock, before changing something that can't be undone.
+/// Returns the backup id, or None if the store keeps no backups or has no pets
+pub fn take_backup(store: &dyn PetStore) -> io::Result<Option<String>> {
+    let Some(dir) = store.backup_dir() else {
+        return Ok(None);
+    };
+    let pets = store.list_pets()?;
+    if pets.is_empty() {
+        return Ok(None);
+    }
+    let clock = store.clock();
+    write_backup(&dir, &pets, clock.today(), clock.now()).map(Some)
+}
+
+/// Takes today's backup unless it has already been taken
+pub fn take_daily_backup(store: &dyn PetStore) -> io::Result<Option<String>> {
+    let Some(dir) = store.backup_dir() else {
+        return Ok(None);
+    };
+    let today = todays_id(store.clock());
+    if backup_path(&dir, &today)?.exists() {
+        return Ok(None);
+    }
+
+    let _lock = store.lock()?;
+    if backup_path(&dir, &today)?.exists() {
+        // Another process got there first
+        return Ok(None);
+    }
+    take_backup(store)
+}
+
+/// Lists the store's backups, newest first
+pub fn list_backups(store: &dyn PetStore) -> io::Result<Vec<Backup>> {
+    match store.backup_dir() {
+        Some(dir) => read_backups(&dir),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Reads one of the store's backups
+pub fn read_backup(store: &dyn PetStore, id: &str) -> io::Result<Backup> {
+    match store.backup_dir() {
+        Some(dir) => read_backup_file(&dir, id),
+        None => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} doesn't keep backups", store.location()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Days;
+    use tempfile::TempDir;
+
+    fn pet(name: &str) -> Pet {
+        Pet::new(name.to_string(), "dog".to_string())
+    }
+
+    #[test]
+    fn test_backup_keeps_pets_released_the_same_day() {
+        // Given: a backup of two pets
+        let temp_dir = TempDir::new().unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 10, 17).unwrap();
+        write_backup(
+            temp_dir.path(),
+            &[pet("Kylo"), pet("Luna")],
+            today,
+            Utc::now(),
+        )
+        .unwrap();
+
+        // When: backing up again after Luna was released and Kylo was fed
+        let mut kylo = pet("Kylo");
+        kylo.hunger = 100;
+        let id = write_backup(temp_dir.path(), &[kylo], today, Utc::now()).unwrap();
+
+        // Then: the day's backup has the latest Kylo and still has Luna
+        let backup = read_backup_file(temp_dir.path(), &id).unwrap();
+        assert_eq!(id, "2025-10-17");
+        let names: Vec<&str> = backup.pets.iter().map(|pet| pet.name.as_str()).collect();
+        assert_eq!(names, ["Kylo", "Luna"]);
+        assert_eq!(backup.pets[0].hunger, 100);
+    }
+
+    #[test]
+    fn test_backups_rotate_after_the_kept_number_of_days() {
+        // Given: a backup on each of more days than are kept
+        let temp_dir = TempDir::new().unwrap();
+        let first = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+        for day in 0..=BACKUPS_KEPT as u64 {
+            write_backup(
+                temp_dir.path(),
+                &[pet("Kylo")],
+                first + Days::new(day),
+                Utc::now(),
+            )
+            .unwrap();
+        }
+
+        // When: listing the backups
+        let ids: Vec<String> = read_backups(temp_dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.id)
+            .collect();
+
+        // Then: only the most recent days are left, newest first
+        assert_eq!(ids.len(), BACKUPS_KEPT);
+        assert_eq!(ids.first().unwrap(), "2025-10-08");
+        assert_eq!(ids.last().unwrap(), "2025-10-02");
+    }
+
+    #[test]
+    fn test_backup_ids_must_be_dates() {
+        // Given: an empty backup directory
+        let temp_dir = TempDir::new().unwrap();
+
+        // When/Then: anything but a date is rejected before touching the filesystem
+        let error = read_backup_file(temp_dir.path(), "../pets/kylo").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        let error = read_backup_file(temp_dir.path(), "2025-10-17").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, Utc};
+use std::cell::Cell;
+use std::fmt;
+
+/// Where the game gets the current time from
+/// Stores hand their clock to everything that stamps or ages a pet, so decay,
+/// timestamps and undo windows can all be driven by a fake clock
+pub trait Clock: fmt::Debug {
+    /// The current time
+    fn now(&self) -> DateTime<Utc>;
+
+    /// A time as the wall clock in the user's time zone shows it
+    fn local(&self, at: DateTime<Utc>) -> NaiveDateTime {
+        at.with_timezone(&Local).naive_local()
+    }
+
+    /// The current date in the local time zone, e.g. for naming daily backups
+    fn today(&self) -> NaiveDate {
+        self.local(self.now()).date()
+    }
+}
+
+/// The real time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that stands still until it is moved, for `--now` and for tests
+/// that need to play out several days in a moment
+#[derive(Debug, Clone)]
+pub struct FixedClock {
+    now: Cell<DateTime<Utc>>,
+    offset: Option<FixedOffset>,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Cell::new(now),
+            offset: None,
+        }
+    }
+
+    /// Reads local times at a fixed offset from UTC instead of the system time zone
+    pub fn with_offset(mut self, offset: FixedOffset) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Moves the clock on (or back, with a negative duration)
+    pub fn advance(&self, by: chrono::Duration) {
+        self.now.set(self.now.get() + by);
+    }
+
+    /// Sets the clock to a time
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.now.set(now);
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now.get()
+    }
+
+    fn local(&self, at: DateTime<Utc>) -> NaiveDateTime {
+        match self.offset {
+            Some(offset) => at.with_timezone(&offset).naive_local(),
+            None => at.with_timezone(&Local).naive_local(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{adopt_pet, feed_pet};
+    use crate::difficulty::Difficulty;
+    use crate::history::{Action, HistoryFilter, PetStats};
+    use crate::journal::replay;
+    use crate::store::{MemoryStore, PetStore};
+    use chrono::{Duration, TimeZone};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_fixed_clock_plays_out_several_days() {
+        // Given: a pet adopted at a known time
+        let start = Utc.with_ymd_and_hms(2025, 10, 17, 9, 0, 0).unwrap();
+        let clock = Rc::new(FixedClock::new(start));
+        let store = MemoryStore::new().with_clock(clock.clone());
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+
+        // When: two days pass before Kylo is fed
+        clock.advance(Duration::days(2));
+        feed_pet(&store, None).unwrap();
+
+        // Then: exactly six intervals of decay happened, stamped by the clock
+        let pet = store.load_pet(None).unwrap().unwrap();
+        assert_eq!(pet.hunger, 80 - 6 * 3 + 20);
+        assert_eq!(pet.last_updated, start + Duration::days(2));
+        let decay = store
+            .history(&HistoryFilter {
+                action: Some(Action::Decay),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(decay.len(), 1);
+        assert_eq!(decay[0].at, start + Duration::days(2));
+
+        // And: the journal replays to the same pet
+        let rebuilt = replay(&store.journal().unwrap(), "Kylo", None, store.rules()).unwrap();
+        assert_eq!(PetStats::from(&rebuilt), PetStats::from(&pet));
+    }
+}
@@ -1,18 +1,30 @@
-use crate::persistence::{load_pet, save_pet};
-use crate::pet::Pet;
+use crate::backup::take_backup;
+use crate::difficulty::Difficulty;
+use crate::error::TermipetError;
+use crate::history::{Action, HistoryEntry};
+use crate::journal::{Event, JournalEntry};
+use crate::pet::{Pet, validate_name};
+use crate::store::PetStore;
+use crate::undo::UndoStep;
 use std::io::{self, Write};
 
-/// Adopts a new pet with the given name and species
-/// Prompts for confirmation if a pet already exists
-pub fn adopt_pet(name: &str, species: &str) -> Result<String, Box<dyn std::error::Error>> {
-    // Check if a pet already exists
-    let existing_pet = load_pet();
-
-    // If pet exists and is not the default placeholder, prompt for confirmation
-    if let Ok(pet) = &existing_pet
-        && pet.name != "Pet"
-    {
-        print!("⚠️  A pet already exists. Overwrite? (y/n): ");
+/// Adopts a new pet with the given name, species and difficulty and makes it the active pet
+/// Other pets in the roster are kept; prompts for confirmation only if a pet
+/// with the same name already exists
+pub fn adopt_pet(
+    store: &dyn PetStore,
+    name: &str,
+    species: &str,
+    difficulty: Difficulty,
+) -> Result<String, TermipetError> {
+    let name = validate_name(name)?;
+
+    // If a pet with this name exists, prompt for confirmation
+    if let Some(existing_pet) = store.load_pet(Some(name))? {
+        print!(
+            "⚠️  A pet named {} already exists. Overwrite? (y/n): ",
+            existing_pet.name
+        );
         io::stdout().flush()?;
 
         let mut input = String::new();
@@ -23,14 +35,42 @@ pub fn adopt_pet(name: &str, species: &str) -> Result<String, Box<dyn std::error
         }
     }
 
-    // Create and save the new pet
-    let new_pet = Pet::new(name.to_string(), species.to_string());
-    save_pet(&new_pet)?;
+    // Lock only after the prompt so a pending answer doesn't block other processes
+    let _lock = store.lock()?;
+    let now = store.clock().now();
+
+    // Keep a copy of a pet about to be overwritten in today's backup
+    if store.pet_exists(name)? {
+        take_backup(store)?;
+    }
 
-    Ok(format!(
-        "🐾 Welcome, {} the {}! Your adventure begins.",
-        name, species
-    ))
+    // Create and save the new pet
+    let mut new_pet = Pet::new(name.to_string(), species.to_string());
+    new_pet.difficulty = difficulty;
+    store.save_pet(&new_pet)?;
+    store.set_active_pet(name)?;
+    store.record(&HistoryEntry::new(Action::Adopt, &new_pet, &new_pet, now))?;
+    store.append_event(&JournalEntry::new(
+        name,
+        Event::Adopted {
+            species: species.to_string(),
+            difficulty,
+        },
+        now,
+    ))?;
+    // Adopting can't be undone, and nothing from before it can be either
+    store.push_undo(&UndoStep::new(Action::Adopt, &new_pet, now))?;
+
+    Ok(match difficulty {
+        Difficulty::Normal => format!(
+            "🐾 Welcome, {} the {}! Your adventure begins.",
+            name, species
+        ),
+        _ => format!(
+            "🐾 Welcome, {} the {}! Your {} adventure begins.",
+            name, species, difficulty
+        ),
+    })
 }
 
 #[cfg(test)]
@@ -79,7 +119,6 @@ mod tests {
 
         // Then: the existing pet should be detected
         assert_eq!(loaded_pet.name, "Buddy");
-        assert!(loaded_pet.name != "Pet"); // Not the default placeholder
     }
 
     #[test]
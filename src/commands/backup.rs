@@ -0,0 +1,156 @@
+use crate::backup::{list_backups, read_backup, take_backup, todays_id};
+use crate::error::TermipetError;
+use crate::history::{Action, HistoryEntry, PetStats};
+use crate::journal::{Event, JournalEntry, PetState};
+use crate::persistence::pet_slug;
+use crate::store::PetStore;
+use crate::undo::UndoStep;
+use colored::*;
+
+/// Lists the daily backups, newest first, with the pets each one holds
+pub fn show_backups(store: &dyn PetStore) -> Result<(), TermipetError> {
+    let backups = list_backups(store)?;
+    if backups.is_empty() {
+        println!("No backups yet. One is taken each day the first time you use termipet.");
+        return Ok(());
+    }
+
+    println!("\n{}", "=== Backups ===".bold());
+    println!();
+    for backup in &backups {
+        let names: Vec<&str> = backup.pets.iter().map(|pet| pet.name.as_str()).collect();
+        println!(
+            "  {}  {}  {}",
+            backup.id.green(),
+            format!(
+                "taken {}",
+                backup
+                    .taken_at
+                    .with_timezone(&chrono::Local)
+                    .format("%H:%M")
+            )
+            .dimmed(),
+            names.join(", ")
+        );
+    }
+    println!(
+        "\n  {}\n",
+        "Use 'termipet backup restore <id>' to bring pets back.".dimmed()
+    );
+
+    Ok(())
+}
+
+/// Brings pets back from a backup, overwriting pets with the same name
+/// With a pet name, only that pet is restored; other pets in the roster are never removed
+pub fn restore_backup(
+    store: &dyn PetStore,
+    id: &str,
+    pet_name: Option<&str>,
+) -> Result<(), TermipetError> {
+    // Hold the lock until every restored pet is saved
+    let _lock = store.lock()?;
+
+    let backup = read_backup(store, id)?;
+    let pets: Vec<_> = backup
+        .pets
+        .into_iter()
+        .filter(|pet| pet_name.is_none_or(|name| pet_slug(name) == pet_slug(&pet.name)))
+        .collect();
+    if pets.is_empty() {
+        return Err(match pet_name {
+            Some(name) => format!("{} isn't in backup {}.", name, id),
+            None => format!("Backup {} has no pets in it.", id),
+        }
+        .into());
+    }
+
+    // Keep the current state in today's backup, so restoring an older backup can
+    // itself be reversed (restoring today's backup mustn't overwrite it, though)
+    if id != todays_id(store.clock()) {
+        take_backup(store)?;
+    }
+
+    for pet in &pets {
+        let before = store
+            .load_pet(Some(&pet.name))?
+            .unwrap_or_else(|| pet.clone());
+        // Read after loading, so the restore is stamped no earlier than the decay loading applied
+        let now = store.clock().now();
+
+        store.save_pet(pet)?;
+        store.record(&HistoryEntry::new(Action::Restore, &before, pet, now))?;
+        store.append_event(&JournalEntry::new(
+            &pet.name,
+            Event::Restored {
+                species: pet.species.clone(),
+                stats: PetStats::from(pet),
+                state: PetState::from(pet),
+            },
+            now,
+        ))?;
+        store.push_undo(&UndoStep::new(Action::Restore, pet, now))?;
+
+        println!(
+            "♻️  {} the {} is back as of backup {}.",
+            pet.name, pet.species, id
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{adopt_pet, feed_pet};
+    use crate::difficulty::Difficulty;
+    use crate::journal::replay;
+    use crate::store::JsonFileStore;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_restore_brings_back_a_released_pet() {
+        // Given: a fed pet that was backed up and then released
+        let temp_dir = TempDir::new().unwrap();
+        let store = JsonFileStore::new(temp_dir.path());
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        feed_pet(&store, None).unwrap();
+        let id = take_backup(&store).unwrap().unwrap();
+        store.delete_pet("Kylo").unwrap();
+        store
+            .append_event(&JournalEntry::new("Kylo", Event::Released, Utc::now()))
+            .unwrap();
+
+        // When: restoring the backup
+        restore_backup(&store, &id, None).unwrap();
+
+        // Then: the pet is back with its stats, and the journal agrees
+        let pet = store.load_pet(None).unwrap().unwrap();
+        assert_eq!(pet.name, "Kylo");
+        assert_eq!(pet.hunger, 100);
+        let rebuilt = replay(&store.journal().unwrap(), "Kylo", None, store.rules()).unwrap();
+        assert_eq!(PetStats::from(&rebuilt), PetStats::from(&pet));
+    }
+
+    #[test]
+    fn test_restore_only_the_named_pet() {
+        // Given: a backup of two pets, both changed since
+        let temp_dir = TempDir::new().unwrap();
+        let store = JsonFileStore::new(temp_dir.path());
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        adopt_pet(&store, "Luna", "cat", Difficulty::Normal).unwrap();
+        let id = take_backup(&store).unwrap().unwrap();
+        feed_pet(&store, Some("Kylo")).unwrap();
+        feed_pet(&store, Some("Luna")).unwrap();
+
+        // When: restoring only Luna
+        restore_backup(&store, &id, Some("luna")).unwrap();
+
+        // Then: Luna is rolled back and Kylo is left alone
+        assert_eq!(store.load_pet(Some("Luna")).unwrap().unwrap().hunger, 80);
+        assert_eq!(store.load_pet(Some("Kylo")).unwrap().unwrap().hunger, 100);
+        assert!(restore_backup(&store, &id, Some("Rex")).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use crate::away::{Crossing, DecaySummary};
+use crate::commands::history::stat_label;
+use crate::error::TermipetError;
+use crate::store::PetStore;
+
+/// How long the pet was left, in whole days, or whole hours if less than a day
+pub(crate) fn format_time_away(seconds: u64) -> String {
+    let (count, unit) = match seconds / (24 * 3600) {
+        0 => (seconds / 3600, "hour"),
+        days => (days, "day"),
+    };
+    format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
+
+/// Joins phrases into a list, e.g. "hungry, bored and grubby"
+fn join_and<S: AsRef<str>>(phrases: &[S]) -> String {
+    match phrases {
+        [] => String::new(),
+        [only] => only.as_ref().to_string(),
+        [rest @ .., last] => {
+            let rest: Vec<&str> = rest.iter().map(AsRef::as_ref).collect();
+            format!("{} and {}", rest.join(", "), last.as_ref())
+        }
+    }
+}
+
+/// Describes time away, e.g. "While you were away for 2 days, Kylo got hungry
+/// and bored… [Hunger -18, Happiness -12]"
+pub(crate) fn format_digest(name: &str, summary: &DecaySummary) -> String {
+    let mut became = Vec::new();
+    let mut did = Vec::new();
+    for crossing in &summary.crossings {
+        match crossing {
+            Crossing::Hungry => became.push("hungry"),
+            Crossing::Bored => became.push("bored"),
+            Crossing::Dirty => became.push("grubby"),
+            Crossing::Desperate => became.push("desperate for the potty"),
+            Crossing::Rested => did.push("caught up on sleep"),
+            Crossing::Ill => did.push("fell ill"),
+            Crossing::RanAway => did.push("ran away"),
+        }
+    }
+    let mut what = Vec::new();
+    if !became.is_empty() {
+        what.push(format!("got {}", join_and(&became)));
+    }
+    what.extend(did.into_iter().map(str::to_string));
+    let what = if what.is_empty() {
+        "missed you".to_string()
+    } else {
+        join_and(&what)
+    };
+
+    let changes: Vec<String> = summary
+        .changes()
+        .into_iter()
+        .map(|(stat, by)| format!("{} {:+}", stat_label(stat), by))
+        .collect();
+    format!(
+        "📬 While you were away for {}, {} {}… [{}]",
+        format_time_away(summary.seconds),
+        name,
+        what,
+        changes.join(", ")
+    )
+}
+
+/// Saves the decay since the pet was last looked after and returns a digest of
+/// it, if the pet was left for at least one decay interval
+/// Saving means the same time away is only ever told once
+pub fn catch_up(
+    store: &dyn PetStore,
+    pet_name: Option<&str>,
+) -> Result<Option<String>, TermipetError> {
+    // Hold the lock until the decayed pet is saved
+    let _lock = store.lock()?;
+
+    // With no pet adopted there is nothing to tell
+    let Some(pet) = store.load_pet(pet_name)? else {
+        return Ok(None);
+    };
+    let Some(summary) = store.decay_summary(&pet.name) else {
+        return Ok(None);
+    };
+    if !summary.is_worth_telling() {
+        return Ok(None);
+    }
+
+    store.save_pet(&pet)?;
+    Ok(Some(format_digest(&pet.name, &summary)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::commands::adopt_pet;
+    use crate::difficulty::Difficulty;
+    use crate::store::MemoryStore;
+    use chrono::{Duration, TimeZone, Utc};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_catch_up_tells_of_time_away_once() {
+        // Given: a pet left alone for six days
+        let start = Utc.with_ymd_and_hms(2025, 10, 17, 12, 0, 0).unwrap();
+        let clock = Rc::new(FixedClock::new(start));
+        let store = MemoryStore::new().with_clock(clock.clone());
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        clock.advance(Duration::days(6));
+
+        // When: catching up, then catching up again
+        let digest = catch_up(&store, None).unwrap().unwrap();
+        let again = catch_up(&store, None).unwrap();
+
+        // Then: the first time tells what happened and the second has nothing to add
+        assert!(
+            digest.starts_with("📬 While you were away for 6 days, Kylo got hungry and bored…"),
+            "{}",
+            digest
+        );
+        assert!(digest.contains("Hunger -54"), "{}", digest);
+        assert_eq!(again, None);
+    }
+
+    #[test]
+    fn test_catch_up_keeps_quiet_about_short_breaks() {
+        // Given: a pet left alone for an hour
+        let clock = Rc::new(FixedClock::new(Utc::now()));
+        let store = MemoryStore::new().with_clock(clock.clone());
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        clock.advance(Duration::hours(1));
+
+        // When/Then: there is nothing to tell, and nothing is saved
+        assert_eq!(catch_up(&store, None).unwrap(), None);
+        assert_eq!(store.journal().unwrap().len(), 1);
+    }
+}
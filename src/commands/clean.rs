@@ -1,69 +1,38 @@
-use crate::persistence::{load_pet, save_pet};
-use crate::utils::cap_stat;
+use crate::commands::perform;
+use crate::engine::PetAction;
+use crate::error::TermipetError;
+use crate::store::PetStore;
 use colored::*;
 
 /// Cleans the pet, increasing cleanliness
-pub fn clean_pet() -> Result<(), Box<dyn std::error::Error>> {
-    // Load the pet
-    let pet_result = load_pet();
-
-    match pet_result {
-        Ok(mut pet) => {
-            // Check if it's the default placeholder pet (no real pet adopted)
-            if pet.name == "Pet" {
-                println!(
-                    "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
-                );
-                return Ok(());
-            }
-
-            // Store old value for display
-            let old_cleanliness = pet.cleanliness;
-
-            // Check if already at max cleanliness
-            let already_spotless = pet.cleanliness >= 95;
-
-            // Increase cleanliness by 40 (capped at 100)
-            pet.cleanliness = cap_stat(pet.cleanliness as i32 + 40, 0, 100);
-
-            // Calculate actual change
-            let cleanliness_change = pet.cleanliness as i32 - old_cleanliness as i32;
-
-            // Save the updated pet
-            save_pet(&pet)?;
-
-            // Print reaction message
-            if already_spotless {
-                println!(
-                    "✨ {} is already spotless! [{} {}]",
-                    pet.name,
-                    "Cleanliness".green(),
-                    format!("+{}", cleanliness_change).green()
-                );
-            } else {
-                println!(
-                    "🧼 {} feels fresh and happy! [{} {}]",
-                    pet.name,
-                    "Cleanliness".green(),
-                    format!("+{}", cleanliness_change).green()
-                );
-            }
-
-            Ok(())
-        }
-        Err(_) => {
-            println!(
-                "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
-            );
-            Ok(())
-        }
+pub fn clean_pet(store: &dyn PetStore, pet_name: Option<&str>) -> Result<(), TermipetError> {
+    let (pet, effects) = perform(store, pet_name, PetAction::Clean)?;
+
+    // Print reaction message
+    let cleanliness_change = format!("+{}", effects.deltas.cleanliness).green();
+    if effects.before.cleanliness >= 95 {
+        println!(
+            "✨ {} is already spotless! [{} {}]",
+            pet.name,
+            "Cleanliness".green(),
+            cleanliness_change
+        );
+    } else {
+        println!(
+            "🧼 {} feels fresh and happy! [{} {}]",
+            pet.name,
+            "Cleanliness".green(),
+            cleanliness_change
+        );
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::pet::Pet;
+    use crate::utils::cap_stat;
     use std::fs;
     use tempfile::TempDir;
 
@@ -0,0 +1,96 @@
+use crate::error::TermipetError;
+use crate::persistence::Diagnosis;
+use crate::store::PetStore;
+use colored::*;
+
+/// Formats one finding, showing what a repair does or did about it
+fn format_finding(finding: &Diagnosis) -> String {
+    match (&finding.repair, finding.repaired) {
+        (Some(repair), true) => format!(
+            "{} {} → repaired ({})",
+            "✔".green(),
+            finding.problem,
+            repair
+        ),
+        (Some(repair), false) => format!(
+            "{} {} → --repair will {}",
+            "✗".red(),
+            finding.problem,
+            repair
+        ),
+        (None, _) => format!("{} {}", "ℹ".yellow(), finding.problem),
+    }
+}
+
+/// Checks the save directory for damaged or inconsistent files
+/// With `repair`, fixes what it can while holding the data dir lock
+pub fn run_doctor(store: &dyn PetStore, repair: bool) -> Result<(), TermipetError> {
+    let _lock = if repair { Some(store.lock()?) } else { None };
+
+    println!("\n{}", format!("🩺 Checking {}", store.location()).bold());
+    println!();
+
+    let findings = store.diagnose(repair)?;
+    if findings.is_empty() {
+        println!("  {} No problems found.", "✔".green());
+    }
+    for finding in &findings {
+        println!("  {}", format_finding(finding));
+    }
+
+    let unrepaired = findings
+        .iter()
+        .filter(|finding| finding.repair.is_some() && !finding.repaired)
+        .count();
+    if unrepaired > 0 {
+        println!();
+        println!(
+            "Run 'termipet doctor --repair' to fix {} problem(s).",
+            unrepaired
+        );
+    }
+    println!();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_finding(repair: Option<&str>, repaired: bool) -> Diagnosis {
+        Diagnosis {
+            problem: "kylo.json is damaged".to_string(),
+            repair: repair.map(str::to_string),
+            repaired,
+        }
+    }
+
+    #[test]
+    fn test_doctor_formats_fixable_problem() {
+        // Given: a fixable problem that hasn't been repaired
+        let line = format_finding(&sample_finding(Some("quarantine it"), false));
+
+        // Then: the line tells the user how to fix it
+        assert!(line.contains("kylo.json is damaged"));
+        assert!(line.contains("--repair will quarantine it"));
+    }
+
+    #[test]
+    fn test_doctor_formats_repaired_problem() {
+        // Given: a problem that was repaired
+        let line = format_finding(&sample_finding(Some("quarantine it"), true));
+
+        // Then: the line confirms the repair
+        assert!(line.contains("repaired (quarantine it)"));
+    }
+
+    #[test]
+    fn test_doctor_formats_report_only_finding() {
+        // Given: an informational finding
+        let line = format_finding(&sample_finding(None, false));
+
+        // Then: no repair is offered
+        assert!(!line.contains("repair"));
+    }
+}
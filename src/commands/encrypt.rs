@@ -0,0 +1,71 @@
+use crate::error::TermipetError;
+use crate::persistence::crypto::{PASSPHRASE_ENV, is_encrypted};
+use crate::persistence::{decrypt_data_dir, encrypt_data_dir, lock_data_dir};
+use crate::store::StoreKind;
+use crate::store::sqlite::DATABASE_FILE;
+use std::path::Path;
+
+/// Reads the passphrase from the environment, so it never lands in shell history as an argument
+fn passphrase() -> Result<String, TermipetError> {
+    match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) if !passphrase.is_empty() => Ok(passphrase),
+        _ => Err(format!(
+            "Set {0} to the passphrase first, e.g. 'read -rs {0} && export {0}'.",
+            PASSPHRASE_ENV
+        )
+        .into()),
+    }
+}
+
+/// Encrypts the pet files, undo stacks, backups, active pet, journal and history
+/// in the data directory
+/// Every later command needs the same passphrase in `TERMIPET_PASSPHRASE`
+pub fn encrypt_saves(data_dir: &Path, kind: StoreKind) -> Result<(), TermipetError> {
+    if kind != StoreKind::Json {
+        return Err("Encryption is only available for the JSON store.".into());
+    }
+    // The SQLite store's pets would stay readable, so don't pretend they're safe
+    if data_dir.join(DATABASE_FILE).exists() {
+        return Err(format!(
+            "{} also holds a SQLite store ({}), which can't be encrypted. Move it out of the data directory first.",
+            data_dir.display(),
+            DATABASE_FILE
+        )
+        .into());
+    }
+    let passphrase = passphrase()?;
+
+    let _lock = lock_data_dir(data_dir)?;
+    let was_encrypted = is_encrypted(data_dir);
+    let encrypted = encrypt_data_dir(data_dir, &passphrase)?;
+
+    if was_encrypted && encrypted == 0 {
+        println!("{} is already encrypted.", data_dir.display());
+    } else {
+        println!(
+            "🔒 Encrypted {} file(s) in {}. Keep {} set to use your pets, and don't lose the passphrase: it can't be recovered.",
+            encrypted,
+            data_dir.display(),
+            PASSPHRASE_ENV
+        );
+    }
+    Ok(())
+}
+
+/// Decrypts the data directory back to plain JSON files and turns encryption off
+pub fn decrypt_saves(data_dir: &Path) -> Result<(), TermipetError> {
+    if !is_encrypted(data_dir) {
+        println!("{} isn't encrypted.", data_dir.display());
+        return Ok(());
+    }
+    let passphrase = passphrase()?;
+
+    let _lock = lock_data_dir(data_dir)?;
+    let decrypted = decrypt_data_dir(data_dir, &passphrase)?;
+    println!(
+        "🔓 Decrypted {} file(s) in {}. Your pets are saved as plain JSON again.",
+        decrypted,
+        data_dir.display()
+    );
+    Ok(())
+}
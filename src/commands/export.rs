@@ -0,0 +1,33 @@
+use crate::commands::require_pet;
+use crate::error::TermipetError;
+use crate::store::PetStore;
+use crate::transfer::{Format, encode_pet};
+use std::fs;
+use std::path::Path;
+
+/// Exports the pet as a JSON, TOML or YAML document, to a file or to stdout
+/// The document keeps `last_updated`, so decay carries on after an import elsewhere
+pub fn export_pet(
+    store: &dyn PetStore,
+    pet_name: Option<&str>,
+    format: Format,
+    output: Option<&Path>,
+) -> Result<(), TermipetError> {
+    let pet = require_pet(store, pet_name)?;
+
+    let document = encode_pet(&pet, format)?;
+    match output {
+        Some(path) => {
+            fs::write(path, document)?;
+            println!(
+                "📦 Exported {} the {} to {}",
+                pet.name,
+                pet.species,
+                path.display()
+            );
+        }
+        None => print!("{}", document),
+    }
+
+    Ok(())
+}
@@ -1,68 +1,30 @@
-use crate::persistence::{load_pet, save_pet};
-use crate::utils::cap_stat;
+use crate::commands::perform;
+use crate::engine::PetAction;
+use crate::error::TermipetError;
+use crate::store::PetStore;
 use colored::*;
 
 /// Feeds the pet, increasing hunger and happiness
-pub fn feed_pet() -> Result<(), Box<dyn std::error::Error>> {
-    // Load the pet
-    let pet_result = load_pet();
-
-    match pet_result {
-        Ok(mut pet) => {
-            // Check if it's the default placeholder pet (no real pet adopted)
-            if pet.name == "Pet" {
-                println!(
-                    "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
-                );
-                return Ok(());
-            }
-
-            // Check if pet is already full
-            if pet.hunger >= 95 {
-                println!("{} is already full! Maybe later.", pet.name);
-                return Ok(());
-            }
-
-            // Store old values for display
-            let old_hunger = pet.hunger;
-            let old_happiness = pet.happiness;
-
-            // Apply stat changes with capping
-            pet.hunger = cap_stat(pet.hunger as i32 + 20, 0, 100);
-            pet.happiness = cap_stat(pet.happiness as i32 + 10, 0, 100);
-
-            // Calculate actual changes
-            let hunger_change = pet.hunger as i32 - old_hunger as i32;
-            let happiness_change = pet.happiness as i32 - old_happiness as i32;
-
-            // Save the updated pet
-            save_pet(&pet)?;
-
-            // Print reaction message
-            println!(
-                "🍖 {} munches happily! [{} {}, {} {}]",
-                pet.name,
-                "Hunger".green(),
-                format!("+{}", hunger_change).green(),
-                "Happiness".green(),
-                format!("+{}", happiness_change).green()
-            );
-
-            Ok(())
-        }
-        Err(_) => {
-            println!(
-                "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
-            );
-            Ok(())
-        }
-    }
+pub fn feed_pet(store: &dyn PetStore, pet_name: Option<&str>) -> Result<(), TermipetError> {
+    let (pet, effects) = perform(store, pet_name, PetAction::Feed)?;
+
+    // Print reaction message
+    println!(
+        "🍖 {} munches happily! [{} {}, {} {}]",
+        pet.name,
+        "Hunger".green(),
+        format!("+{}", effects.deltas.hunger).green(),
+        "Happiness".green(),
+        format!("+{}", effects.deltas.happiness).green()
+    );
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::pet::Pet;
+    use crate::utils::cap_stat;
     use std::fs;
     use tempfile::TempDir;
 
@@ -140,8 +102,8 @@ mod tests {
     #[test]
     fn test_feed_stat_changes_calculation() {
         // Given: initial stats
-        let old_hunger = 60;
-        let old_happiness = 70;
+        let old_hunger: u8 = 60;
+        let old_happiness: u8 = 70;
 
         // When: applying changes
         let new_hunger = cap_stat(old_hunger as i32 + 20, 0, 100);
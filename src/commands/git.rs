@@ -0,0 +1,21 @@
+use crate::error::TermipetError;
+use crate::git::init_repo;
+use std::path::Path;
+
+/// Turns the data directory into a git repository, after which every change is committed
+pub fn init_git(data_dir: &Path) -> Result<(), TermipetError> {
+    if !init_repo(data_dir)? {
+        println!("{} is already tracked with git.", data_dir.display());
+        return Ok(());
+    }
+
+    println!(
+        "📚 Now tracking {} with git. Every change will be committed.",
+        data_dir.display()
+    );
+    println!(
+        "Browse it with 'git -C {0} log -p', or add a remote and push to share it.",
+        data_dir.display()
+    );
+    Ok(())
+}
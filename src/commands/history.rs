@@ -0,0 +1,144 @@
+use crate::error::TermipetError;
+use crate::history::{Action, HistoryEntry, HistoryFilter};
+use crate::store::PetStore;
+use crate::utils::parse_time;
+use colored::*;
+use std::collections::BTreeMap;
+
+/// Display name for a stat, matching the labels the other commands print
+pub(crate) fn stat_label(name: &str) -> &str {
+    match name {
+        "hunger" => "Hunger",
+        "happiness" => "Happiness",
+        "energy" => "Energy",
+        "cleanliness" => "Cleanliness",
+        "potty_level" => "Potty",
+        "xp" => "XP",
+        "level" => "Level",
+        other => other,
+    }
+}
+
+/// Describes the stats an entry changed, e.g. "Hunger 60 → 80, Happiness 70 → 80"
+pub(crate) fn format_changes(entry: &HistoryEntry) -> String {
+    let changes: Vec<String> = entry
+        .before
+        .fields()
+        .into_iter()
+        .zip(entry.after.fields())
+        .filter(|((_, before), (_, after))| before != after)
+        .map(|((name, before), (_, after))| format!("{} {} → {}", stat_label(name), before, after))
+        .collect();
+
+    if changes.is_empty() {
+        "no change".to_string()
+    } else {
+        changes.join(", ")
+    }
+}
+
+/// Formats one history line with its local time, action and stat changes
+fn format_history_line(entry: &HistoryEntry) -> String {
+    format!(
+        "{}  {:<8}  {}",
+        entry
+            .at
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M"),
+        entry.action,
+        format_changes(entry)
+    )
+}
+
+/// Shows the recorded actions for a pet, optionally narrowed to one action
+/// and/or a time window, followed by a count per action
+pub fn show_history(
+    store: &dyn PetStore,
+    pet_name: Option<&str>,
+    action: Option<Action>,
+    since: Option<&str>,
+) -> Result<(), TermipetError> {
+    let name = match pet_name {
+        Some(name) => name.to_string(),
+        None => match store.active_pet_name()? {
+            Some(name) => name,
+            None => return Err(TermipetError::NoPet),
+        },
+    };
+
+    let filter = HistoryFilter {
+        pet: Some(name.clone()),
+        action,
+        since: since
+            .map(|since| parse_time(since, store.clock().now()))
+            .transpose()?,
+    };
+    let entries = store.history(&filter)?;
+
+    if entries.is_empty() {
+        println!("No matching history for {} yet.", name);
+        return Ok(());
+    }
+
+    println!("\n{}", format!("=== {}'s history ===", name).bold());
+    println!();
+    for entry in &entries {
+        println!("  {}", format_history_line(entry));
+    }
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for entry in &entries {
+        *counts.entry(entry.action.as_str()).or_default() += 1;
+    }
+    let summary: Vec<String> = counts
+        .iter()
+        .map(|(action, count)| format!("{} × {}", count, action))
+        .collect();
+    println!("\n  {}\n", summary.join(", ").dimmed());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pet::Pet;
+    use chrono::Utc;
+
+    #[test]
+    fn test_history_lists_only_changed_stats() {
+        // Given: an accident that lowered cleanliness and happiness
+        let before = Pet::new("Kylo".to_string(), "dog".to_string());
+        let mut after = before.clone();
+        after.cleanliness = 50;
+        after.happiness = 65;
+        let entry = HistoryEntry::new(Action::Accident, &before, &after, Utc::now());
+
+        // When: formatting the changes
+        let changes = format_changes(&entry);
+
+        // Then: only the stats that moved are listed
+        assert_eq!(changes, "Happiness 80 → 65, Cleanliness 80 → 50");
+    }
+
+    #[test]
+    fn test_history_records_accident_and_walk() {
+        // Given: a pet about to have an accident
+        let store = crate::store::MemoryStore::new();
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.potty_level = 90;
+        store.save_pet(&pet).unwrap();
+
+        // When: walking the pet
+        crate::commands::walk_pet(&store, None, true).unwrap();
+
+        // Then: the accident and the walk are recorded in order
+        let actions: Vec<Action> = store
+            .history(&HistoryFilter::default())
+            .unwrap()
+            .iter()
+            .map(|entry| entry.action)
+            .collect();
+        assert_eq!(actions, [Action::Accident, Action::Walk]);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::away::DecaySummary;
+use crate::backup::take_backup;
+use crate::error::TermipetError;
+use crate::history::{Action, HistoryEntry, PetStats};
+use crate::journal::{Event, JournalEntry, PetState};
+use crate::persistence::decay_until;
+use crate::pet::validate_name;
+use crate::store::PetStore;
+use crate::transfer::{Format, decode_pet};
+use crate::undo::UndoStep;
+use std::fs;
+use std::path::Path;
+
+/// Imports a pet from an exported file and makes it the active pet
+/// The format comes from `format` or else the file extension. A pet with the same
+/// name is only replaced with `overwrite`; `rename` imports it under another name
+pub fn import_pet(
+    store: &dyn PetStore,
+    path: &Path,
+    format: Option<Format>,
+    rename: Option<&str>,
+    overwrite: bool,
+) -> Result<String, TermipetError> {
+    let Some(format) = format.or_else(|| Format::from_path(path)) else {
+        return Err(format!(
+            "Can't tell the format of {}. Use --format json|toml|yaml.",
+            path.display()
+        )
+        .into());
+    };
+    let now = store.clock().now();
+    let contents = fs::read_to_string(path)?;
+    let mut pet = decode_pet(&contents, format, now)
+        .map_err(|e| format!("Can't import {}: {}", path.display(), e))?;
+
+    if let Some(name) = rename {
+        pet.name = validate_name(name)?.to_string();
+    }
+    // A clock running ahead on the other machine mustn't hold back decay here
+    if pet.last_updated > now {
+        println!(
+            "⚠️  {} was last updated in the future; treating it as updated now.",
+            pet.name
+        );
+        pet.last_updated = now;
+    }
+
+    // Hold the lock until the imported pet is saved
+    let _lock = store.lock()?;
+
+    if store.pet_exists(&pet.name)? {
+        if !overwrite {
+            return Err(format!(
+                "A pet named {} already exists. Use --rename <NAME> to import it alongside, or --overwrite to replace it.",
+                pet.name
+            )
+            .into());
+        }
+        // Keep a copy of the pet about to be replaced in today's backup
+        take_backup(store)?;
+    }
+
+    store.record(&HistoryEntry::new(Action::Import, &pet, &pet, now))?;
+    store.append_event(&JournalEntry::new(
+        &pet.name,
+        Event::Imported {
+            species: pet.species.clone(),
+            stats: PetStats::from(&pet),
+            state: PetState::from(&pet),
+        },
+        now,
+    ))?;
+    // Importing can't be undone, and nothing from before it can be either
+    store.push_undo(&UndoStep::new(Action::Import, &pet, now))?;
+
+    // Catch up on the decay since the pet was exported
+    let before = pet.clone();
+    let DecaySummary {
+        seconds, asleep, ..
+    } = decay_until(&mut pet, store.clock(), store.rules(), now);
+    if seconds > 0 {
+        let decay = HistoryEntry::new(Action::Decay, &before, &pet, now);
+        if decay.changed_stats() {
+            store.record(&decay)?;
+        }
+        store.append_event(&JournalEntry::new(
+            &pet.name,
+            Event::decayed(seconds, asleep),
+            now,
+        ))?;
+    }
+
+    store.save_pet(&pet)?;
+    store.set_active_pet(&pet.name)?;
+
+    Ok(format!(
+        "📥 Imported {} the {} from {}.",
+        pet.name,
+        pet.species,
+        path.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{adopt_pet, export_pet, feed_pet};
+    use crate::difficulty::Difficulty;
+    use crate::journal::replay;
+    use crate::store::{JsonFileStore, MemoryStore};
+    use chrono::{Duration, Utc};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_import_carries_on_decay_from_the_export() {
+        // Given: a pet exported from another machine a day after it was last looked after
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("kylo.toml");
+        let mut pet = crate::pet::Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.hunger = 90;
+        pet.last_updated = Utc::now() - Duration::hours(17);
+        fs::write(
+            &path,
+            crate::transfer::encode_pet(&pet, Format::Toml).unwrap(),
+        )
+        .unwrap();
+
+        // When: importing it
+        let store = MemoryStore::new();
+        import_pet(&store, &path, None, None, false).unwrap();
+
+        // Then: it is the active pet, two intervals of decay have been applied,
+        // and the journal rebuilds the same pet
+        let imported = store.load_pet(None).unwrap().unwrap();
+        assert_eq!(imported.name, "Kylo");
+        assert_eq!(imported.hunger, 84);
+        let rebuilt = replay(&store.journal().unwrap(), "Kylo", None, store.rules()).unwrap();
+        assert_eq!(PetStats::from(&rebuilt), PetStats::from(&imported));
+    }
+
+    #[test]
+    fn test_import_handles_name_collisions() {
+        // Given: a pet exported and then fed on this machine
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let store = JsonFileStore::new(&data_dir);
+        let path = temp_dir.path().join("kylo.yaml");
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        export_pet(&store, None, Format::Yaml, Some(&path)).unwrap();
+        feed_pet(&store, None).unwrap();
+
+        // When/Then: importing it again is refused unless renamed or overwriting
+        assert!(import_pet(&store, &path, None, None, false).is_err());
+
+        import_pet(&store, &path, None, Some("Ren"), false).unwrap();
+        assert_eq!(store.load_pet(Some("Ren")).unwrap().unwrap().hunger, 80);
+        assert_eq!(store.load_pet(Some("Kylo")).unwrap().unwrap().hunger, 100);
+
+        import_pet(&store, &path, None, None, true).unwrap();
+        assert_eq!(store.load_pet(Some("Kylo")).unwrap().unwrap().hunger, 80);
+        assert_eq!(store.list_pets().unwrap().len(), 2);
+    }
+}
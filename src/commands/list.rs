@@ -0,0 +1,74 @@
+use crate::error::TermipetError;
+use crate::persistence::pet_slug;
+use crate::pet::Pet;
+use crate::store::PetStore;
+use colored::*;
+
+/// Formats a single roster line, marking the active pet with an arrow
+fn format_roster_line(pet: &Pet, is_active: bool) -> String {
+    let marker = if is_active { "➜" } else { " " };
+    format!(
+        "{} {} the {} (Level {})",
+        marker, pet.name, pet.species, pet.level
+    )
+}
+
+/// Lists every adopted pet, highlighting the active one
+pub fn list_roster(store: &dyn PetStore) -> Result<(), TermipetError> {
+    let pets = store.list_pets()?;
+
+    if pets.is_empty() {
+        println!(
+            "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
+        );
+        return Ok(());
+    }
+
+    let active = store.active_pet_name()?.map(|name| pet_slug(&name));
+
+    println!("\n{}", "=== Your pets ===".bold());
+    for pet in &pets {
+        let is_active = active.as_deref() == Some(pet_slug(&pet.name).as_str());
+        let line = format_roster_line(pet, is_active);
+        if is_active {
+            println!("{}", line.green());
+        } else {
+            println!("{}", line);
+        }
+    }
+    println!();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_marks_active_pet() {
+        // Given: an active pet
+        let pet = Pet::new("Kylo".to_string(), "dog".to_string());
+
+        // When: formatting its roster line
+        let line = format_roster_line(&pet, true);
+
+        // Then: the line carries the active marker and pet details
+        assert!(line.starts_with("➜"));
+        assert!(line.contains("Kylo the dog"));
+        assert!(line.contains("Level 1"));
+    }
+
+    #[test]
+    fn test_list_leaves_inactive_pet_unmarked() {
+        // Given: a pet that is not active
+        let pet = Pet::new("Luna".to_string(), "cat".to_string());
+
+        // When: formatting its roster line
+        let line = format_roster_line(&pet, false);
+
+        // Then: no active marker is shown
+        assert!(!line.contains("➜"));
+        assert!(line.contains("Luna the cat"));
+    }
+}
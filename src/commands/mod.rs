@@ -1,21 +1,113 @@
 pub mod adopt;
+pub mod backup;
+pub mod catch_up;
 pub mod clean;
+pub mod doctor;
+pub mod encrypt;
+pub mod export;
 pub mod feed;
+pub mod git;
+pub mod history;
+pub mod import;
+pub mod list;
 pub mod play;
 pub mod potty;
+pub mod replay;
 pub mod reset;
 pub mod shell;
 pub mod status;
+pub mod switch;
+pub mod sync;
 pub mod train;
+pub mod undo;
+pub mod vacation;
 pub mod walk;
 
 pub use adopt::adopt_pet;
+pub use backup::{restore_backup, show_backups};
+pub use catch_up::catch_up;
 pub use clean::clean_pet;
+pub use doctor::run_doctor;
+pub use encrypt::{decrypt_saves, encrypt_saves};
+pub use export::export_pet;
 pub use feed::feed_pet;
+pub use git::init_git;
+pub use history::show_history;
+pub use import::import_pet;
+pub use list::list_roster;
 pub use play::play_pet;
 pub use potty::potty_pet;
+pub use replay::replay_pet;
 pub use reset::reset_pet;
 pub use shell::run_shell;
 pub use status::show_status;
+pub use switch::switch_pet;
+pub use sync::sync_pets;
 pub use train::train_pet;
+pub use undo::undo_pet;
+pub use vacation::{end_vacation, start_vacation};
 pub use walk::walk_pet;
+
+use crate::engine::{ActionOutcome, Effects, Engine, PetAction};
+use crate::error::TermipetError;
+use crate::history::{Action, HistoryEntry};
+use crate::journal::JournalEntry;
+use crate::pet::Pet;
+use crate::sleep;
+use crate::store::PetStore;
+use crate::undo::UndoStep;
+
+/// Loads the pet a command acts on, failing if there is no such pet
+pub(crate) fn require_pet(
+    store: &dyn PetStore,
+    pet_name: Option<&str>,
+) -> Result<Pet, TermipetError> {
+    store.load_pet(pet_name)?.ok_or_else(|| match pet_name {
+        Some(name) => TermipetError::UnknownPet(name.to_string()),
+        None => TermipetError::NoPet,
+    })
+}
+
+/// Runs an action through the engine: loads the pet, applies the action, then
+/// saves the pet and records what happened in the history, journal and undo stack
+/// Returns the updated pet and the action's effects for the command to present,
+/// or an error saying why not if there is no pet or it refuses
+pub(crate) fn perform(
+    store: &dyn PetStore,
+    pet_name: Option<&str>,
+    action: PetAction,
+) -> Result<(Pet, Effects), TermipetError> {
+    // Hold the lock until the updated pet is saved
+    let _lock = store.lock()?;
+
+    let mut pet = require_pet(store, pet_name)?;
+    // Read after loading, so the action is stamped no earlier than the decay loading applied
+    let now = store.clock().now();
+
+    let engine = Engine::new(store.rules(), sleep::is_asleep(store.clock()));
+    let effects = match engine.apply(action, &mut pet) {
+        ActionOutcome::Refused(refusal) => {
+            return Err(TermipetError::refused(&pet, action, refusal));
+        }
+        ActionOutcome::Done(effects) => *effects,
+    };
+
+    store.save_pet(&pet)?;
+    if let Some(after_accident) = &effects.after_accident {
+        store.record(&HistoryEntry::new(
+            Action::Accident,
+            &effects.before,
+            after_accident,
+            now,
+        ))?;
+    }
+    let action_start = effects.after_accident.as_ref().unwrap_or(&effects.before);
+    store.record(&HistoryEntry::new(action.kind(), action_start, &pet, now))?;
+    for event in &effects.events {
+        store.append_event(&JournalEntry::new(&pet.name, event.clone(), now))?;
+    }
+    // Undoing the action also undoes an accident that happened on it
+    store.push_undo(&UndoStep::new(action.kind(), &effects.before, now))?;
+
+    Ok((pet, effects))
+}
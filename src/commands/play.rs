@@ -1,68 +1,39 @@
-use crate::persistence::{load_pet, save_pet};
-use crate::utils::cap_stat;
+use crate::commands::perform;
+use crate::engine::PetAction;
+use crate::error::TermipetError;
+use crate::store::PetStore;
 use colored::*;
 
 /// Plays with the pet, increasing happiness but decreasing energy
-pub fn play_pet() -> Result<(), Box<dyn std::error::Error>> {
-    // Load the pet
-    let pet_result = load_pet();
-
-    match pet_result {
-        Ok(mut pet) => {
-            // Check if it's the default placeholder pet (no real pet adopted)
-            if pet.name == "Pet" {
-                println!(
-                    "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
-                );
-                return Ok(());
-            }
-
-            // Check if pet is too tired
-            if pet.energy < 10 {
-                println!("{} is too tired to play right now.", pet.name);
-                return Ok(());
-            }
-
-            // Store old values for display
-            let old_happiness = pet.happiness;
-            let old_energy = pet.energy;
-
-            // Apply stat changes with capping
-            pet.happiness = cap_stat(pet.happiness as i32 + 15, 0, 100);
-            pet.energy = cap_stat(pet.energy as i32 - 10, 0, 100);
-
-            // Calculate actual changes
-            let happiness_change = pet.happiness as i32 - old_happiness as i32;
-            let energy_change = pet.energy as i32 - old_energy as i32;
-
-            // Save the updated pet
-            save_pet(&pet)?;
-
-            // Print reaction message
-            println!(
-                "🎾 {} plays fetch and wags their tail! [{} {}, {} {}]",
-                pet.name,
-                "Happiness".green(),
-                format!("+{}", happiness_change).green(),
-                "Energy".red(),
-                format!("{}", energy_change).red()
-            );
-
-            Ok(())
-        }
-        Err(_) => {
-            println!(
-                "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
-            );
-            Ok(())
-        }
+/// A sleeping pet only plays if `wake` is set
+pub fn play_pet(
+    store: &dyn PetStore,
+    pet_name: Option<&str>,
+    wake: bool,
+) -> Result<(), TermipetError> {
+    let (pet, effects) = perform(store, pet_name, PetAction::Play { wake })?;
+
+    if effects.woke {
+        println!("⏰ You woke {} up.", pet.name);
     }
+
+    // Print reaction message
+    println!(
+        "🎾 {} plays fetch and wags their tail! [{} {}, {} {}]",
+        pet.name,
+        "Happiness".green(),
+        format!("+{}", effects.deltas.happiness).green(),
+        "Energy".red(),
+        format!("{}", effects.deltas.energy).red()
+    );
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::pet::Pet;
+    use crate::utils::cap_stat;
     use std::fs;
     use tempfile::TempDir;
 
@@ -143,8 +114,8 @@ mod tests {
     #[test]
     fn test_play_stat_changes_calculation() {
         // Given: initial stats
-        let old_happiness = 70;
-        let old_energy = 60;
+        let old_happiness: u8 = 70;
+        let old_energy: u8 = 60;
 
         // When: applying changes
         let new_happiness = cap_stat(old_happiness as i32 + 15, 0, 100);
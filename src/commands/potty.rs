@@ -1,105 +1,58 @@
-use crate::persistence::{load_pet, save_pet};
-use crate::utils::cap_stat;
+use crate::commands::perform;
+use crate::engine::PetAction;
+use crate::error::TermipetError;
+use crate::store::PetStore;
 use colored::*;
 
 /// Helps the pet go potty, resetting potty level and handling accidents
-pub fn potty_pet() -> Result<(), Box<dyn std::error::Error>> {
-    // Load the pet
-    let pet_result = load_pet();
-
-    match pet_result {
-        Ok(mut pet) => {
-            // Check if it's the default placeholder pet (no real pet adopted)
-            if pet.name == "Pet" {
-                println!(
-                    "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
-                );
-                return Ok(());
-            }
-
-            // Track what happened
-            let mut had_accident = false;
-
-            // Store old values for display
-            let old_potty = pet.potty_level;
-            let old_cleanliness = pet.cleanliness;
-            let old_happiness = pet.happiness;
-
-            // Check for accident FIRST (before potty relief)
-            if pet.potty_level > 80 {
-                had_accident = true;
-                pet.cleanliness = cap_stat(pet.cleanliness as i32 - 30, 0, 100);
-                pet.happiness = cap_stat(pet.happiness as i32 - 15, 0, 100);
-            }
-
-            // Reset potty level
-            pet.potty_level = 0;
-
-            // Increase happiness (even if accident happened)
-            if !had_accident {
-                pet.happiness = cap_stat(pet.happiness as i32 + 5, 0, 100);
-            }
-
-            // Calculate actual changes
-            let potty_change = pet.potty_level as i32 - old_potty as i32;
-            let cleanliness_change = pet.cleanliness as i32 - old_cleanliness as i32;
-            let happiness_change = pet.happiness as i32 - old_happiness as i32;
-
-            // Save the updated pet
-            save_pet(&pet)?;
-
-            // Print reaction message based on what happened
-            if had_accident {
-                // Build stat changes string
-                let mut changes = vec![format!(
-                    "{} {}",
-                    "Potty".green(),
-                    format!("{}", potty_change).green()
-                )];
-
-                if cleanliness_change != 0 {
-                    changes.push(format!(
-                        "{} {}",
-                        "Cleanliness".red(),
-                        format!("{}", cleanliness_change).red()
-                    ));
-                }
-
-                if happiness_change != 0 {
-                    changes.push(format!(
-                        "{} {}",
-                        "Happiness".red(),
-                        format!("{}", happiness_change).red()
-                    ));
-                }
-
-                println!("💩 {} had an accident! [{}]", pet.name, changes.join(", "));
-            } else {
-                println!(
-                    "🚽 {} feels relieved! [{} {}, {} {}]",
-                    pet.name,
-                    "Potty".green(),
-                    format!("{}", potty_change).green(),
-                    "Happiness".green(),
-                    format!("+{}", happiness_change).green()
-                );
-            }
-
-            Ok(())
+pub fn potty_pet(store: &dyn PetStore, pet_name: Option<&str>) -> Result<(), TermipetError> {
+    let (pet, effects) = perform(store, pet_name, PetAction::Potty)?;
+    let deltas = effects.deltas;
+
+    // Print reaction message based on what happened
+    if effects.had_accident() {
+        // Build stat changes string
+        let mut changes = vec![format!(
+            "{} {}",
+            "Potty".green(),
+            format!("{}", deltas.potty_level).green()
+        )];
+
+        if deltas.cleanliness != 0 {
+            changes.push(format!(
+                "{} {}",
+                "Cleanliness".red(),
+                format!("{}", deltas.cleanliness).red()
+            ));
         }
-        Err(_) => {
-            println!(
-                "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
-            );
-            Ok(())
+
+        if deltas.happiness != 0 {
+            changes.push(format!(
+                "{} {}",
+                "Happiness".red(),
+                format!("{}", deltas.happiness).red()
+            ));
         }
+
+        println!("💩 {} had an accident! [{}]", pet.name, changes.join(", "));
+    } else {
+        println!(
+            "🚽 {} feels relieved! [{} {}, {} {}]",
+            pet.name,
+            "Potty".green(),
+            format!("{}", deltas.potty_level).green(),
+            "Happiness".green(),
+            format!("+{}", deltas.happiness).green()
+        );
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::pet::Pet;
+    use crate::utils::cap_stat;
     use std::fs;
     use tempfile::TempDir;
 
@@ -0,0 +1,127 @@
+use crate::commands::status::print_status;
+use crate::error::TermipetError;
+use crate::journal::replay;
+use crate::persistence::pet_slug;
+use crate::store::PetStore;
+use crate::utils::parse_time;
+
+/// Rebuilds a pet by replaying its event journal with the current rules,
+/// optionally stopping at a point in time, and shows the result
+pub fn replay_pet(
+    store: &dyn PetStore,
+    pet_name: Option<&str>,
+    until: Option<&str>,
+) -> Result<(), TermipetError> {
+    let name = match pet_name {
+        Some(name) => name.to_string(),
+        None => match store.active_pet_name()? {
+            Some(name) => name,
+            None => return Err(TermipetError::NoPet),
+        },
+    };
+    let until = until
+        .map(|until| parse_time(until, store.clock().now()))
+        .transpose()?;
+
+    let journal = store.journal()?;
+    let events: Vec<_> = journal
+        .iter()
+        .filter(|entry| pet_slug(&entry.pet) == pet_slug(&name))
+        .collect();
+    let replayed = events
+        .iter()
+        .filter(|entry| until.is_none_or(|until| entry.at <= until))
+        .count();
+
+    match replay(&journal, &name, until, store.rules()) {
+        Some(pet) => {
+            println!(
+                "⏪ Replayed {} events for {} (as of {})",
+                replayed,
+                pet.name,
+                pet.last_updated
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+            );
+            print_status(&pet);
+        }
+        None if events.is_empty() => println!(
+            "No journal for {} yet. Pets adopted before the journal existed can't be replayed.",
+            name
+        ),
+        None => println!("{} wasn't in your care at that time.", name),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, FixedClock};
+    use crate::commands::{
+        adopt_pet, clean_pet, feed_pet, play_pet, potty_pet, train_pet, walk_pet,
+    };
+    use crate::difficulty::Difficulty;
+    use crate::history::PetStats;
+    use crate::store::MemoryStore;
+    use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, TimeZone, Utc};
+    use std::rc::Rc;
+
+    /// A clock that moves on a millisecond every time it is read, like a real one
+    #[derive(Debug)]
+    struct TickingClock(FixedClock);
+
+    impl Clock for TickingClock {
+        fn now(&self) -> DateTime<Utc> {
+            let now = self.0.now();
+            self.0.advance(Duration::milliseconds(1));
+            now
+        }
+
+        fn local(&self, at: DateTime<Utc>) -> NaiveDateTime {
+            self.0.local(at)
+        }
+    }
+
+    #[test]
+    fn test_replay_matches_the_saved_pet() {
+        // Given: a pet that has been through every kind of action
+        let store = MemoryStore::new();
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        play_pet(&store, None, true).unwrap();
+        walk_pet(&store, None, true).unwrap();
+        feed_pet(&store, None).unwrap();
+        train_pet(&store, None, true).unwrap();
+        potty_pet(&store, None).unwrap();
+        clean_pet(&store, None).unwrap();
+
+        // When: rebuilding it from the journal
+        let rebuilt = replay(&store.journal().unwrap(), "Kylo", None, store.rules()).unwrap();
+
+        // Then: it has exactly the stats that were saved
+        let saved = store.load_pet(None).unwrap().unwrap();
+        assert_eq!(PetStats::from(&rebuilt), PetStats::from(&saved));
+    }
+
+    #[test]
+    fn test_replay_matches_the_saved_pet_after_decay_and_an_action() {
+        // Given: a pet played with twice, eight hours apart, by a clock that ticks between reads
+        let morning = Utc.with_ymd_and_hms(2026, 6, 1, 9, 0, 0).unwrap();
+        let clock = Rc::new(TickingClock(
+            FixedClock::new(morning).with_offset(FixedOffset::east_opt(0).unwrap()),
+        ));
+        let store = MemoryStore::new().with_clock(clock.clone());
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        play_pet(&store, None, true).unwrap();
+        clock.0.advance(Duration::hours(8));
+        play_pet(&store, None, true).unwrap();
+
+        // When: rebuilding it from the journal
+        let rebuilt = replay(&store.journal().unwrap(), "Kylo", None, store.rules()).unwrap();
+
+        // Then: the decay is replayed before the play, as it happened
+        let saved = store.load_pet(None).unwrap().unwrap();
+        assert_eq!(PetStats::from(&rebuilt), PetStats::from(&saved));
+    }
+}
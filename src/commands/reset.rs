@@ -1,10 +1,13 @@
-use crate::persistence::{get_pet_file_path, load_pet};
-use std::fs;
+use crate::backup::take_backup;
+use crate::commands::require_pet;
+use crate::error::TermipetError;
+use crate::journal::{Event, JournalEntry};
+use crate::store::PetStore;
 use std::io::{self, Write};
 
 /// Prompts the user for confirmation before resetting
 /// Returns true if user confirms (y), false if declined (n)
-fn confirm_reset() -> Result<bool, Box<dyn std::error::Error>> {
+fn confirm_reset() -> Result<bool, TermipetError> {
     loop {
         print!("Are you sure you want to reset your pet? (y/n): ");
         io::stdout().flush()?;
@@ -21,30 +24,33 @@ fn confirm_reset() -> Result<bool, Box<dyn std::error::Error>> {
     }
 }
 
-/// Resets the pet by deleting the pet data file after confirmation
-pub fn reset_pet() -> Result<(), Box<dyn std::error::Error>> {
-    let pet_path = get_pet_file_path()?;
-
-    // Check if pet file exists
-    if !pet_path.exists() {
-        println!("No pet found to reset.");
-        return Ok(());
-    }
-
+/// Resets the pet by removing it from the roster after confirmation
+pub fn reset_pet(store: &dyn PetStore, pet_name: Option<&str>) -> Result<(), TermipetError> {
     // Load pet to get the name for personalized message
-    let pet = load_pet()?;
-    let pet_name = pet.name.clone();
+    let pet = require_pet(store, pet_name)?;
 
     // Ask for confirmation
     let confirmed = confirm_reset()?;
 
     if confirmed {
-        // Delete the pet file
-        fs::remove_file(&pet_path)?;
+        // Lock only after the prompt so a pending answer doesn't block other processes
+        let _lock = store.lock()?;
+        let now = store.clock().now();
+
+        // Keep a copy in today's backup before deleting the pet
+        let backup = take_backup(store)?;
+        store.delete_pet(&pet.name)?;
+        store.append_event(&JournalEntry::new(&pet.name, Event::Released, now))?;
         println!(
             "🐾 {} has been released. You can adopt a new pet anytime.",
-            pet_name
+            pet.name
         );
+        if let Some(id) = backup {
+            println!(
+                "Changed your mind? 'termipet --pet {} backup restore {}' brings them back.",
+                pet.name, id
+            );
+        }
     } else {
         println!("Reset cancelled.");
     }
@@ -54,7 +60,9 @@ pub fn reset_pet() -> Result<(), Box<dyn std::error::Error>> {
 
 #[cfg(test)]
 mod tests {
+    use crate::error::TermipetError;
     use crate::pet::Pet;
+    use crate::store::MemoryStore;
     use std::fs;
     use tempfile::TempDir;
 
@@ -128,10 +136,9 @@ mod tests {
         // Then: should detect missing file
         assert!(!pet_path.exists());
 
-        // And: should handle gracefully (simulated - would print message and exit)
-        if !pet_path.exists() {
-            // In real implementation: print "No pet found to reset."
-            assert!(true); // Successfully detected missing file
-        }
+        // And: should handle gracefully, without asking for confirmation
+        let store = MemoryStore::new();
+        let result = super::reset_pet(&store, None);
+        assert!(matches!(result, Err(TermipetError::NoPet)));
     }
 }
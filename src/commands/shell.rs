@@ -1,11 +1,15 @@
 use crate::commands::{
-    clean_pet, feed_pet, play_pet, potty_pet, reset_pet, show_status, train_pet, walk_pet,
+    catch_up, clean_pet, feed_pet, list_roster, play_pet, potty_pet, reset_pet, show_history,
+    show_status, switch_pet, train_pet, undo_pet, walk_pet,
 };
+use crate::error::TermipetError;
+use crate::store::PetStore;
 use colored::*;
 use std::io::{self, Write};
 
 /// Runs the interactive shell where users can issue commands continuously
-pub fn run_shell() -> Result<(), Box<dyn std::error::Error>> {
+/// `pet_name` targets a specific pet until `/switch` is used
+pub fn run_shell(store: &dyn PetStore, pet_name: Option<&str>) -> Result<(), TermipetError> {
     println!(
         "\n{}",
         "🐾 Welcome to termiPet Interactive Shell!".bold().cyan()
@@ -17,6 +21,7 @@ pub fn run_shell() -> Result<(), Box<dyn std::error::Error>> {
 
     let stdin = io::stdin();
     let mut input = String::new();
+    let mut selected_pet = pet_name.map(str::to_string);
 
     loop {
         // Display prompt
@@ -33,12 +38,14 @@ pub fn run_shell() -> Result<(), Box<dyn std::error::Error>> {
             }
             Ok(_) => {
                 // Execute command and check if we should exit
-                match execute_command(&input) {
+                match execute_command(store, &input, &mut selected_pet) {
                     Ok(should_exit) => {
                         if should_exit {
                             break;
                         }
                     }
+                    // The pet saying no isn't an error in the shell; it just moves on
+                    Err(e) if !e.is_failure() => println!("{}", e),
                     Err(e) => {
                         eprintln!("Error: {}", e);
                     }
@@ -73,7 +80,18 @@ fn display_help() {
         "  {} - Train your pet to gain XP and level up",
         "/train".green()
     );
+    println!(
+        "  {} - Add --wake to /play, /walk or /train to wake a sleeping pet",
+        "--wake".green()
+    );
     println!("  {} - Check your pet's current status", "/status".green());
+    println!("  {} - List all your pets", "/list".green());
+    println!(
+        "  {} - Show what has happened to your pet",
+        "/history".green()
+    );
+    println!("  {} - Undo your last action", "/undo".green());
+    println!("  {} - Switch the active pet", "/switch <name>".green());
     println!(
         "  {} - Clean your pet to increase cleanliness",
         "/clean".green()
@@ -88,40 +106,75 @@ fn display_help() {
 }
 
 /// Parses and executes a command from the shell
-fn execute_command(input: &str) -> Result<bool, Box<dyn std::error::Error>> {
+/// `selected_pet` is the pet commands act on (`None` means the active pet)
+fn execute_command(
+    store: &dyn PetStore,
+    input: &str,
+    selected_pet: &mut Option<String>,
+) -> Result<bool, TermipetError> {
     let command = input.trim().to_lowercase();
+    let pet_name = selected_pet.as_deref();
+
+    // /switch takes the pet name as an argument
+    if let Some(name) = command.strip_prefix("/switch ") {
+        println!("{}", switch_pet(store, name.trim())?);
+        // Follow the newly active pet from now on
+        *selected_pet = None;
+        return Ok(false);
+    }
+
+    // Tell what happened while the pet was left alone before the command's own output
+    if matches!(
+        command.split_whitespace().next(),
+        Some("/feed" | "/play" | "/walk" | "/train" | "/status" | "/clean" | "/potty" | "/history")
+    ) && let Some(digest) = catch_up(store, pet_name)?
+    {
+        println!("{}", digest);
+    }
 
     match command.as_str() {
         "/feed" => {
-            feed_pet()?;
+            feed_pet(store, pet_name)?;
             Ok(false)
         }
-        "/play" => {
-            play_pet()?;
+        "/play" | "/play --wake" => {
+            play_pet(store, pet_name, command.ends_with("--wake"))?;
             Ok(false)
         }
-        "/walk" => {
-            walk_pet()?;
+        "/walk" | "/walk --wake" => {
+            walk_pet(store, pet_name, command.ends_with("--wake"))?;
             Ok(false)
         }
-        "/train" => {
-            train_pet()?;
+        "/train" | "/train --wake" => {
+            train_pet(store, pet_name, command.ends_with("--wake"))?;
             Ok(false)
         }
         "/status" => {
-            show_status()?;
+            show_status(store, pet_name)?;
             Ok(false)
         }
         "/clean" => {
-            clean_pet()?;
+            clean_pet(store, pet_name)?;
             Ok(false)
         }
         "/potty" => {
-            potty_pet()?;
+            potty_pet(store, pet_name)?;
             Ok(false)
         }
         "/reset" => {
-            reset_pet()?;
+            reset_pet(store, pet_name)?;
+            Ok(false)
+        }
+        "/list" => {
+            list_roster(store)?;
+            Ok(false)
+        }
+        "/history" => {
+            show_history(store, pet_name, None, None)?;
+            Ok(false)
+        }
+        "/undo" => {
+            undo_pet(store, pet_name)?;
             Ok(false)
         }
         "/help" => {
@@ -149,6 +202,7 @@ fn execute_command(input: &str) -> Result<bool, Box<dyn std::error::Error>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::store::MemoryStore;
 
     #[test]
     fn test_shell_command_parsing_feed() {
@@ -164,6 +218,7 @@ mod tests {
 
     #[test]
     fn test_shell_command_parsing_exit() {
+        let store = MemoryStore::new();
         // Given: a command string "/exit"
         let command = "/exit";
 
@@ -174,13 +229,14 @@ mod tests {
         assert_eq!(normalised, "/exit");
 
         // And: executing it should return true to signal exit
-        let result = execute_command(command);
+        let result = execute_command(&store, command, &mut None);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), true);
+        assert!(result.unwrap());
     }
 
     #[test]
     fn test_shell_command_parsing_invalid() {
+        let store = MemoryStore::new();
         // Given: an invalid command string "/fly"
         let command = "/fly";
 
@@ -193,15 +249,15 @@ mod tests {
         assert_ne!(normalised, "/exit");
 
         // And: executing it should return Ok(false) without crashing
-        let result = execute_command(command);
+        let result = execute_command(&store, command, &mut None);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), false);
+        assert!(!result.unwrap());
     }
 
     #[test]
     fn test_shell_normalises_input() {
         // Given: commands with extra whitespace and mixed case
-        let commands = vec!["  /FEED  ", "/Play", "  /exit", "/WALK  "];
+        let commands = ["  /FEED  ", "/Play", "  /exit", "/WALK  "];
 
         // When: normalising each command
         let normalised: Vec<String> = commands
@@ -218,6 +274,7 @@ mod tests {
 
     #[test]
     fn test_shell_help_command() {
+        let store = MemoryStore::new();
         // Given: the help command
         let command = "/help";
 
@@ -228,28 +285,30 @@ mod tests {
         assert_eq!(normalised, "/help");
 
         // And: executing it should not signal exit
-        let result = execute_command(command);
+        let result = execute_command(&store, command, &mut None);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), false);
+        assert!(!result.unwrap());
     }
 
     #[test]
     fn test_shell_empty_input() {
+        let store = MemoryStore::new();
         // Given: empty or whitespace-only input
         let commands = vec!["", "   ", "\n", "  \t  "];
 
         // When: processing each empty command
         for cmd in commands {
-            let result = execute_command(cmd);
+            let result = execute_command(&store, cmd, &mut None);
 
             // Then: it should not crash and should not signal exit
             assert!(result.is_ok());
-            assert_eq!(result.unwrap(), false);
+            assert!(!result.unwrap());
         }
     }
 
     #[test]
     fn test_execute_command_returns_bool_for_exit_status() {
+        let store = MemoryStore::new();
         // Given: various commands
         let test_cases = vec![
             ("/exit", true),  // Should signal exit
@@ -260,10 +319,10 @@ mod tests {
 
         // When/Then: each command returns appropriate exit signal
         for (cmd, expected_exit) in test_cases {
-            let result = execute_command(cmd);
+            let result = execute_command(&store, cmd, &mut None);
             // We only check if result matches expected exit status
             // Some commands like /feed may fail without a pet, but that's OK for this test
-            if cmd == "/exit" || cmd == "/help" || cmd == "" {
+            if cmd == "/exit" || cmd == "/help" || cmd.is_empty() {
                 assert!(result.is_ok());
                 assert_eq!(result.unwrap(), expected_exit);
             }
@@ -1,6 +1,10 @@
+use crate::commands::require_pet;
+use crate::difficulty::{Condition, Difficulty};
+use crate::error::TermipetError;
 use crate::mood::{calculate_mood, get_mood_message};
-use crate::persistence::load_pet;
 use crate::pet::Pet;
+use crate::sleep;
+use crate::store::PetStore;
 use colored::*;
 
 /// Determines the color for a stat based on its value
@@ -43,6 +47,9 @@ pub fn print_status(pet: &Pet) {
     println!("  XP:          {:3}", pet.xp);
     println!("  Level:       {:3}", pet.level);
     println!("  Potty:       {:3}", pet.potty_level);
+    if pet.difficulty != Difficulty::Normal {
+        println!("  Difficulty:  {}", pet.difficulty);
+    }
 
     // Print mood
     println!();
@@ -52,27 +59,45 @@ pub fn print_status(pet: &Pet) {
     println!();
 }
 
-/// Displays the pet status or a message if no pet exists
-pub fn show_status() -> Result<(), Box<dyn std::error::Error>> {
-    match load_pet() {
-        Ok(pet) => {
-            // Check if it's the default placeholder pet (no real pet adopted)
-            if pet.name == "Pet" {
-                println!(
-                    "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
-                );
+/// Displays the pet status
+pub fn show_status(store: &dyn PetStore, pet_name: Option<&str>) -> Result<(), TermipetError> {
+    let pet = require_pet(store, pet_name)?;
+
+    print_status(&pet);
+    match pet.condition {
+        Condition::Healthy => {}
+        Condition::Ill => println!(
+            "🤒 {} is ill until they are fed and clean again.\n",
+            pet.name
+        ),
+        Condition::RanAway => {
+            println!("💔 {} has run away.\n", pet.name);
+            return Ok(());
+        }
+    }
+    if let Some(vacation) = &pet.vacation {
+        println!(
+            "🏖️  {} has been on vacation since {}{}.\n",
+            pet.name,
+            vacation
+                .since
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d"),
+            if vacation.sitter {
+                ", with a pet-sitter"
             } else {
-                print_status(&pet);
+                ""
             }
-            Ok(())
-        }
-        Err(_) => {
-            println!(
-                "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
-            );
-            Ok(())
-        }
+        );
+    }
+    if sleep::is_asleep(store.clock()) {
+        println!(
+            "💤 {} is asleep until {:02}:00.\n",
+            pet.name,
+            sleep::WAKE_HOUR
+        );
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -120,6 +145,7 @@ mod tests {
             cleanliness: 80,
             potty_level: 0,
             last_updated: chrono::Utc::now(),
+            ..Default::default()
         };
 
         // When: printing status (we can't easily capture stdout in test,
@@ -144,6 +170,7 @@ mod tests {
             cleanliness: 80,
             potty_level: 0,
             last_updated: chrono::Utc::now(),
+            ..Default::default()
         };
 
         // When: printing status
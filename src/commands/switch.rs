@@ -0,0 +1,16 @@
+use crate::commands::require_pet;
+use crate::error::TermipetError;
+use crate::store::PetStore;
+
+/// Makes the named pet the default target for all commands
+pub fn switch_pet(store: &dyn PetStore, name: &str) -> Result<String, TermipetError> {
+    let _lock = store.lock()?;
+
+    let pet = require_pet(store, Some(name))?;
+    store.set_active_pet(name)?;
+
+    Ok(format!(
+        "🐾 {} the {} is now your active pet.",
+        pet.name, pet.species
+    ))
+}
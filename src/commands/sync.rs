@@ -0,0 +1,256 @@
+use crate::away::DecaySummary;
+use crate::backup::take_backup;
+use crate::commands::history::format_changes;
+use crate::error::TermipetError;
+use crate::history::{Action, HistoryEntry};
+use crate::journal::{Event, JournalEntry, replay};
+use crate::persistence::{decay_until, pet_slug};
+use crate::store::PetStore;
+use crate::sync::{merge_journals, read_sync_journal, write_sync_journal};
+use crate::undo::UndoStep;
+use std::path::Path;
+
+/// Merges the pets with the journal in a synced directory (e.g. a dotfiles checkout)
+/// Both journals are combined and each changed pet is rebuilt by replaying its
+/// events in time order, so actions taken on different machines are all kept
+/// An encrypted store is refused, as the shared journal would be plain text
+pub fn sync_pets(store: &dyn PetStore, dir: &Path) -> Result<(), TermipetError> {
+    if store.is_encrypted() {
+        return Err(format!(
+            "{} is encrypted, and syncing would copy its journal into {} unencrypted. Run 'termipet decrypt' first.",
+            store.location(),
+            dir.display()
+        )
+        .into());
+    }
+    if !dir.is_dir() {
+        return Err(format!("Sync directory {} doesn't exist.", dir.display()).into());
+    }
+
+    // Hold the lock until the merged pets are saved
+    let _lock = store.lock()?;
+    let now = store.clock().now();
+
+    let local = store.journal()?;
+    let other = read_sync_journal(dir)?;
+    let merged = merge_journals(&local, &other)?;
+
+    for entry in &merged.received {
+        store.append_event(entry)?;
+    }
+
+    // The pets the other machines changed, each once, by their latest name
+    let mut names: Vec<String> = Vec::new();
+    for entry in merged.received.iter().rev() {
+        if !names
+            .iter()
+            .any(|name| pet_slug(name) == pet_slug(&entry.pet))
+        {
+            names.push(entry.pet.clone());
+        }
+    }
+    names.reverse();
+
+    let mut report = Vec::new();
+    let mut backed_up = false;
+    for name in &names {
+        let before = replay(&local, name, None, store.rules());
+        match replay(&merged.journal, name, None, store.rules()) {
+            Some(synced) => {
+                let mut pet = synced.clone();
+                let DecaySummary {
+                    seconds, asleep, ..
+                } = decay_until(&mut pet, store.clock(), store.rules(), now);
+                store.save_pet(&pet)?;
+
+                let entry = HistoryEntry::new(
+                    Action::Sync,
+                    before.as_ref().unwrap_or(&synced),
+                    &synced,
+                    now,
+                );
+                store.record(&entry)?;
+                if seconds > 0 {
+                    let decay = HistoryEntry::new(Action::Decay, &synced, &pet, now);
+                    if decay.changed_stats() {
+                        store.record(&decay)?;
+                    }
+                    store.append_event(&JournalEntry::new(
+                        &pet.name,
+                        Event::decayed(seconds, asleep),
+                        now,
+                    ))?;
+                }
+                // Steps from before the merge no longer apply to the merged pet
+                store.push_undo(&UndoStep::new(Action::Sync, &pet, now))?;
+
+                match before {
+                    Some(_) => {
+                        report.push(format!("  🔀 {}: {}", pet.name, format_changes(&entry)))
+                    }
+                    None => report.push(format!("  ➕ {} the {} arrived", pet.name, pet.species)),
+                }
+            }
+            // Replay also finds nothing for pets adopted before the journal
+            // existed, so only a release recorded last means the pet is gone
+            None if store.pet_exists(name)?
+                && merged
+                    .journal
+                    .iter()
+                    .rfind(|entry| pet_slug(&entry.pet) == pet_slug(name))
+                    .is_some_and(|entry| entry.event == Event::Released) =>
+            {
+                // Keep a copy in today's backup before deleting the pet
+                if !backed_up {
+                    take_backup(store)?;
+                    backed_up = true;
+                }
+                store.delete_pet(name)?;
+                report.push(format!("  👋 {} was released on another machine", name));
+            }
+            None => {}
+        }
+    }
+
+    if store.active_pet_name()?.is_none()
+        && let Some(name) = names
+            .iter()
+            .find(|name| store.pet_exists(name).unwrap_or(false))
+    {
+        store.set_active_pet(name)?;
+    }
+
+    // Send everything back, including any decay just recorded here
+    let outgoing = merge_journals(&store.journal()?, &other)?;
+    write_sync_journal(dir, &outgoing.journal)?;
+
+    println!(
+        "🔄 Synced with {}: {} events received, {} sent.",
+        dir.display(),
+        merged.received.len(),
+        outgoing.sent
+    );
+    for line in report {
+        println!("{}", line);
+    }
+
+    // Pets adopted before the journal existed have nothing to merge
+    let journal = store.journal()?;
+    for pet in store.list_pets()? {
+        if replay(&journal, &pet.name, None, store.rules()).is_none() {
+            println!(
+                "⚠️  {} has no journal to sync. Use 'termipet export' and 'termipet import' to move them.",
+                pet.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{adopt_pet, feed_pet, play_pet, train_pet};
+    use crate::difficulty::Difficulty;
+    use crate::pet::Pet;
+    use crate::store::{JsonFileStore, MemoryStore};
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sync_merges_actions_from_both_machines() {
+        // Given: a pet adopted on one machine and synced to another
+        let sync_dir = TempDir::new().unwrap();
+        let laptop = MemoryStore::new();
+        let desktop = MemoryStore::new();
+        adopt_pet(&laptop, "Kylo", "dog", Difficulty::Normal).unwrap();
+        sync_pets(&laptop, sync_dir.path()).unwrap();
+        sync_pets(&desktop, sync_dir.path()).unwrap();
+
+        // When: each machine does something different before syncing again
+        feed_pet(&laptop, None).unwrap();
+        play_pet(&desktop, None, true).unwrap();
+        train_pet(&desktop, None, true).unwrap();
+        sync_pets(&laptop, sync_dir.path()).unwrap();
+        sync_pets(&desktop, sync_dir.path()).unwrap();
+        sync_pets(&laptop, sync_dir.path()).unwrap();
+
+        // Then: both machines have the pet with every action applied
+        let on_laptop = laptop.load_pet(None).unwrap().unwrap();
+        let on_desktop = desktop.load_pet(None).unwrap().unwrap();
+        assert_eq!(on_laptop.hunger, 100);
+        assert_eq!(on_laptop.xp, 20);
+        assert_eq!(on_laptop.happiness, on_desktop.happiness);
+        assert_eq!(on_laptop.xp, on_desktop.xp);
+        assert_eq!(on_laptop.hunger, on_desktop.hunger);
+    }
+
+    #[test]
+    fn test_sync_brings_releases_across() {
+        // Given: two machines sharing two pets
+        let sync_dir = TempDir::new().unwrap();
+        let laptop = MemoryStore::new();
+        let desktop = MemoryStore::new();
+        adopt_pet(&laptop, "Kylo", "dog", Difficulty::Normal).unwrap();
+        adopt_pet(&laptop, "Luna", "cat", Difficulty::Normal).unwrap();
+        sync_pets(&laptop, sync_dir.path()).unwrap();
+        sync_pets(&desktop, sync_dir.path()).unwrap();
+
+        // When: one of them releases Luna and both sync
+        laptop.delete_pet("Luna").unwrap();
+        laptop
+            .append_event(&JournalEntry::new("Luna", Event::Released, Utc::now()))
+            .unwrap();
+        sync_pets(&laptop, sync_dir.path()).unwrap();
+        sync_pets(&desktop, sync_dir.path()).unwrap();
+
+        // Then: Luna is gone from the other machine too
+        assert!(!desktop.pet_exists("Luna").unwrap());
+        assert!(desktop.pet_exists("Kylo").unwrap());
+    }
+
+    #[test]
+    fn test_sync_keeps_pets_adopted_before_the_journal() {
+        // Given: the same pet on two machines from before the journal existed
+        let sync_dir = TempDir::new().unwrap();
+        let laptop = MemoryStore::new();
+        let desktop = MemoryStore::new();
+        for store in [&laptop, &desktop] {
+            store
+                .save_pet(&Pet::new("Kylo".to_string(), "dog".to_string()))
+                .unwrap();
+            store.set_active_pet("Kylo").unwrap();
+        }
+
+        // When: one machine journals an action for it and both sync
+        feed_pet(&laptop, None).unwrap();
+        sync_pets(&laptop, sync_dir.path()).unwrap();
+        sync_pets(&desktop, sync_dir.path()).unwrap();
+
+        // Then: the other machine keeps its pet rather than taking it as released
+        assert!(desktop.pet_exists("Kylo").unwrap());
+    }
+
+    #[test]
+    fn test_sync_refuses_an_encrypted_store() {
+        // Given: a pet in an encrypted data directory, and a sync folder
+        let data_dir = TempDir::new().unwrap();
+        let sync_dir = TempDir::new().unwrap();
+        crate::persistence::crypto::create_key_file(
+            data_dir.path(),
+            "hunter2",
+            crate::persistence::crypto::test_params(),
+        )
+        .unwrap();
+        let store = JsonFileStore::new(data_dir.path());
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+
+        // When: syncing
+        let result = sync_pets(&store, sync_dir.path());
+
+        // Then: it is refused before anything reaches the sync folder
+        assert!(matches!(result, Err(TermipetError::InvalidInput(_))));
+        assert!(!sync_dir.path().join(crate::sync::SYNC_JOURNAL).exists());
+    }
+}
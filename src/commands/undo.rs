@@ -0,0 +1,188 @@
+use crate::actions;
+use crate::commands::history::format_changes;
+use crate::commands::require_pet;
+use crate::error::TermipetError;
+use crate::history::{Action, HistoryEntry};
+use crate::journal::{Event, JournalEntry};
+use crate::pet::Pet;
+use crate::sleep;
+use crate::store::PetStore;
+
+/// Reverts the pet's most recent action by restoring the state from before it
+/// Refuses to go back past an adoption, restore, import, sync, vacation or stat decay
+pub fn undo_pet(store: &dyn PetStore, pet_name: Option<&str>) -> Result<(), TermipetError> {
+    // Hold the lock until the restored pet is saved
+    let _lock = store.lock()?;
+
+    let pet = require_pet(store, pet_name)?;
+    let now = store.clock().now();
+
+    let Some(step) = store.last_undo(&pet.name)? else {
+        return Err(TermipetError::NothingToUndo(format!(
+            "Nothing to undo for {}.",
+            pet.name
+        )));
+    };
+
+    if step.is_barrier() {
+        let reason = match step.action {
+            Action::Adopt => "since they were adopted",
+            Action::Restore => "since they were restored from a backup",
+            Action::Import => "since they were imported",
+            Action::Sync => "since they were synced",
+            Action::Vacation => "since their vacation started or ended",
+            _ => "since their stats decayed",
+        };
+        let mut message = format!("Nothing to undo for {} {}.", pet.name, reason);
+        if step.action == Action::Adopt {
+            message.push_str("\nUse 'termipet reset' to release a pet.");
+        }
+        return Err(TermipetError::NothingToUndo(message));
+    }
+    let elapsed = (now - step.at).num_seconds().max(0) as u64;
+    if actions::decay_due(&step.pet, elapsed, store.rules()) {
+        return Err(TermipetError::NothingToUndo(format!(
+            "{}'s stats have decayed since the last {}, so it can't be undone.",
+            pet.name, step.action
+        )));
+    }
+    let asleep = sleep::seconds_asleep(store.clock(), step.at, now);
+    if actions::rest_due(&step.pet, asleep) {
+        return Err(TermipetError::NothingToUndo(format!(
+            "{} has slept since the last {}, so it can't be undone.",
+            pet.name, step.action
+        )));
+    }
+
+    store.pop_undo(&pet.name)?;
+    // Undoing an action never gives back time towards decay, rest, neglect or
+    // the sitter's next visit
+    let restored = Pet {
+        decay_progress: pet.decay_progress,
+        rest_progress: pet.rest_progress,
+        neglect_seconds: pet.neglect_seconds,
+        vacation: pet.vacation.clone(),
+        ..step.pet
+    };
+    store.save_pet(&restored)?;
+    let entry = HistoryEntry::new(Action::Undo, &pet, &restored, now);
+    store.record(&entry)?;
+    store.append_event(&JournalEntry::new(&pet.name, Event::Undone, now))?;
+
+    println!(
+        "↩️  Undid the last {} for {}. [{}]",
+        step.action,
+        pet.name,
+        format_changes(&entry)
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::commands::{adopt_pet, feed_pet, train_pet, walk_pet};
+    use crate::difficulty::Difficulty;
+    use crate::history::PetStats;
+    use crate::journal::replay;
+    use crate::store::MemoryStore;
+    use crate::undo::UndoStep;
+    use chrono::{Duration, Utc};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_undo_restores_the_state_before_the_last_action() {
+        // Given: a pet that was fed and then trained by mistake
+        let store = MemoryStore::new();
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        walk_pet(&store, None, true).unwrap();
+        let after_walk = store.load_pet(None).unwrap().unwrap();
+        feed_pet(&store, None).unwrap();
+        train_pet(&store, None, true).unwrap();
+
+        // When: undoing twice
+        undo_pet(&store, None).unwrap();
+        undo_pet(&store, None).unwrap();
+
+        // Then: the pet is back to how it was after the walk
+        let pet = store.load_pet(None).unwrap().unwrap();
+        assert_eq!(PetStats::from(&pet), PetStats::from(&after_walk));
+
+        // And: replaying the journal agrees
+        let rebuilt = replay(&store.journal().unwrap(), "Kylo", None, store.rules()).unwrap();
+        assert_eq!(PetStats::from(&rebuilt), PetStats::from(&pet));
+    }
+
+    #[test]
+    fn test_undo_refuses_to_undo_an_adoption() {
+        // Given: a freshly adopted pet
+        let store = MemoryStore::new();
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+
+        // When: trying to undo
+        let result = undo_pet(&store, None);
+
+        // Then: it is refused, the pet is still there and the barrier stays in place
+        let error = result.unwrap_err();
+        assert!(matches!(error, TermipetError::NothingToUndo(_)));
+        assert_eq!(error.exit_code(), 15);
+        assert!(!error.is_failure());
+        assert!(store.pet_exists("Kylo").unwrap());
+        assert_eq!(
+            store.last_undo("Kylo").unwrap().unwrap().action,
+            Action::Adopt
+        );
+    }
+
+    #[test]
+    fn test_undo_refuses_once_a_decay_interval_has_passed() {
+        // Given: a feed that happened nine hours ago
+        let store = MemoryStore::new();
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        let mut pet = store.load_pet(None).unwrap().unwrap();
+        pet.hunger = 40;
+        let mut step = UndoStep::new(Action::Feed, &pet, Utc::now());
+        step.at = Utc::now() - Duration::hours(9);
+        store.push_undo(&step).unwrap();
+        pet.hunger = 60;
+        store.save_pet(&pet).unwrap();
+
+        // When: trying to undo it
+        let result = undo_pet(&store, None);
+
+        // Then: it is refused and nothing changes
+        assert!(matches!(result, Err(TermipetError::NothingToUndo(_))));
+        assert_eq!(store.load_pet(None).unwrap().unwrap().hunger, 60);
+        assert_eq!(store.last_undo("Kylo").unwrap(), Some(step));
+    }
+
+    #[test]
+    fn test_undo_keeps_time_towards_decay() {
+        // Given: a pet fed an hour ago, well before any stat is due to drop
+        let clock = Rc::new(FixedClock::new(Utc::now()));
+        let store = MemoryStore::new().with_clock(clock.clone());
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        feed_pet(&store, None).unwrap();
+        clock.advance(Duration::hours(1));
+
+        // When: undoing the feed
+        undo_pet(&store, None).unwrap();
+
+        // Then: the feed is gone but the hour still counts towards decay
+        let pet = store.load_pet(None).unwrap().unwrap();
+        assert_eq!(pet.hunger, 80);
+        assert_eq!(pet.decay_progress, 3600);
+        let rebuilt = replay(&store.journal().unwrap(), "Kylo", None, store.rules()).unwrap();
+        assert_eq!(rebuilt.decay_progress, 3600);
+        assert_eq!(PetStats::from(&rebuilt), PetStats::from(&pet));
+
+        // And: once a stat point has fallen due, the next action can't be undone
+        feed_pet(&store, None).unwrap();
+        clock.advance(Duration::hours(2));
+        let result = undo_pet(&store, None);
+        assert!(matches!(result, Err(TermipetError::NothingToUndo(_))));
+        assert_eq!(store.load_pet(None).unwrap().unwrap().hunger, 99);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::commands::catch_up::format_time_away;
+use crate::commands::require_pet;
+use crate::difficulty::Condition;
+use crate::error::TermipetError;
+use crate::history::{Action, HistoryEntry};
+use crate::journal::{Event, JournalEntry};
+use crate::store::PetStore;
+use crate::undo::UndoStep;
+use crate::vacation::{SITTER_VISIT_SECONDS, Vacation};
+
+/// Starts a vacation: the pet's stats freeze, or with `sitter` a pet-sitter
+/// feeds and walks it on a schedule, until the vacation ends
+pub fn start_vacation(
+    store: &dyn PetStore,
+    pet_name: Option<&str>,
+    sitter: bool,
+) -> Result<(), TermipetError> {
+    // Hold the lock until the updated pet is saved
+    let _lock = store.lock()?;
+
+    let mut pet = require_pet(store, pet_name)?;
+    let now = store.clock().now();
+
+    if pet.condition == Condition::RanAway {
+        return Err(TermipetError::RanAway(pet.name));
+    }
+
+    if let Some(vacation) = &pet.vacation {
+        println!(
+            "{} has been on vacation since {}.",
+            pet.name,
+            vacation
+                .since
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d")
+        );
+        return Ok(());
+    }
+
+    let before = pet.clone();
+    pet.vacation = Some(Vacation::new(now, sitter));
+
+    store.save_pet(&pet)?;
+    store.record(&HistoryEntry::new(Action::Vacation, &before, &pet, now))?;
+    store.append_event(&JournalEntry::new(
+        &pet.name,
+        Event::VacationStarted { sitter },
+        now,
+    ))?;
+    // Nothing from before the vacation can be undone once it has started
+    store.push_undo(&UndoStep::new(Action::Vacation, &pet, now))?;
+
+    if sitter {
+        println!(
+            "🏖️  Enjoy your trip! A pet-sitter will feed and walk {} every {} hours until you run 'termipet vacation end'.",
+            pet.name,
+            SITTER_VISIT_SECONDS / 3600
+        );
+    } else {
+        println!(
+            "🏖️  Enjoy your trip! {}'s stats are frozen until you run 'termipet vacation end'.",
+            pet.name
+        );
+    }
+
+    Ok(())
+}
+
+/// Ends a vacation, so the pet's stats decay as usual again
+pub fn end_vacation(store: &dyn PetStore, pet_name: Option<&str>) -> Result<(), TermipetError> {
+    // Hold the lock until the updated pet is saved
+    let _lock = store.lock()?;
+
+    let mut pet = require_pet(store, pet_name)?;
+    let now = store.clock().now();
+
+    let Some(vacation) = pet.vacation.take() else {
+        println!("{} isn't on vacation.", pet.name);
+        return Ok(());
+    };
+    let before = pet.clone();
+
+    store.save_pet(&pet)?;
+    store.record(&HistoryEntry::new(Action::Vacation, &before, &pet, now))?;
+    store.append_event(&JournalEntry::new(&pet.name, Event::VacationEnded, now))?;
+    store.push_undo(&UndoStep::new(Action::Vacation, &pet, now))?;
+
+    println!(
+        "🏠 Welcome back! You were away for {}, and {} is glad to see you.",
+        format_time_away((now - vacation.since).num_seconds().max(0) as u64),
+        pet.name
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::commands::{adopt_pet, feed_pet, undo_pet};
+    use crate::difficulty::Difficulty;
+    use crate::history::PetStats;
+    use crate::journal::replay;
+    use crate::store::MemoryStore;
+    use chrono::{Duration, TimeZone, Utc};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_vacation_freezes_the_pet_until_it_ends() {
+        // Given: a pet left on vacation for two weeks
+        let start = Utc.with_ymd_and_hms(2025, 10, 17, 12, 0, 0).unwrap();
+        let clock = Rc::new(FixedClock::new(start));
+        let store = MemoryStore::new().with_clock(clock.clone());
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        start_vacation(&store, None, false).unwrap();
+        clock.advance(Duration::days(14));
+
+        // When: the vacation ends and a day passes
+        end_vacation(&store, None).unwrap();
+        let back = store.load_pet(None).unwrap().unwrap();
+        clock.advance(Duration::days(1));
+
+        // Then: nothing decayed while away, decay resumes afterwards,
+        // and the journal replays to the same pet
+        assert_eq!(back.hunger, 80);
+        assert_eq!(back.vacation, None);
+        let pet = store.load_pet(None).unwrap().unwrap();
+        assert_eq!(pet.hunger, 80 - 9);
+        let rebuilt = replay(&store.journal().unwrap(), "Kylo", None, store.rules()).unwrap();
+        assert_eq!(PetStats::from(&rebuilt), PetStats::from(&back));
+    }
+
+    #[test]
+    fn test_vacation_with_a_sitter_cannot_be_undone() {
+        // Given: a fed pet left with a sitter
+        let clock = Rc::new(FixedClock::new(Utc::now()));
+        let store = MemoryStore::new().with_clock(clock.clone());
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        feed_pet(&store, None).unwrap();
+        start_vacation(&store, None, true).unwrap();
+
+        // When: trying to undo the feeding
+        let result = undo_pet(&store, None);
+
+        // Then: it is refused, and the pet is still fed and still with the sitter
+        assert!(matches!(result, Err(TermipetError::NothingToUndo(_))));
+        let pet = store.load_pet(None).unwrap().unwrap();
+        assert_eq!(pet.hunger, 100);
+        assert!(pet.vacation.unwrap().sitter);
+    }
+}
@@ -1,128 +1,93 @@
-use crate::persistence::{load_pet, save_pet};
-use crate::utils::{cap_stat, random_bool};
+use crate::commands::perform;
+use crate::engine::PetAction;
+use crate::error::TermipetError;
+use crate::store::PetStore;
 use colored::*;
 
 /// Walks the pet, restoring energy and managing potty needs
-pub fn walk_pet() -> Result<(), Box<dyn std::error::Error>> {
-    // Load the pet
-    let pet_result = load_pet();
-
-    match pet_result {
-        Ok(mut pet) => {
-            // Check if it's the default placeholder pet (no real pet adopted)
-            if pet.name == "Pet" {
-                println!(
-                    "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
-                );
-                return Ok(());
-            }
-
-            // Track what happened during the walk
-            let mut had_accident = false;
-            let mut potty_relieved = false;
-
-            // Store old values for display
-            let old_energy = pet.energy;
-            let old_potty = pet.potty_level;
-            let old_cleanliness = pet.cleanliness;
-            let old_happiness = pet.happiness;
-
-            // Check for accident FIRST (before walk benefits)
-            if pet.potty_level > 80 {
-                had_accident = true;
-                pet.cleanliness = cap_stat(pet.cleanliness as i32 - 30, 0, 100);
-                pet.happiness = cap_stat(pet.happiness as i32 - 15, 0, 100);
-            }
-
-            // Apply energy increase (always happens)
-            pet.energy = cap_stat(pet.energy as i32 + 15, 0, 100);
-
-            // Apply potty reduction with 80% probability
-            if random_bool(0.8) {
-                potty_relieved = true;
-                pet.potty_level = cap_stat(pet.potty_level as i32 - 50, 0, 100);
-            }
-
-            // Calculate actual changes
-            let energy_change = pet.energy as i32 - old_energy as i32;
-            let potty_change = pet.potty_level as i32 - old_potty as i32;
-            let cleanliness_change = pet.cleanliness as i32 - old_cleanliness as i32;
-            let happiness_change = pet.happiness as i32 - old_happiness as i32;
-
-            // Save the updated pet
-            save_pet(&pet)?;
-
-            // Print reaction message based on what happened
-            if had_accident {
-                // Build stat changes string
-                let mut changes = vec![format!(
-                    "{} {}",
-                    "Energy".green(),
-                    format!("+{}", energy_change).green()
-                )];
-
-                if potty_relieved {
-                    changes.push(format!(
-                        "{} {}",
-                        "Potty".green(),
-                        format!("{}", potty_change).green()
-                    ));
-                }
-
-                if cleanliness_change != 0 {
-                    changes.push(format!(
-                        "{} {}",
-                        "Cleanliness".red(),
-                        format!("{}", cleanliness_change).red()
-                    ));
-                }
-
-                if happiness_change != 0 {
-                    changes.push(format!(
-                        "{} {}",
-                        "Happiness".red(),
-                        format!("{}", happiness_change).red()
-                    ));
-                }
-
-                println!(
-                    "💩 {} had an accident but feels better now. [{}]",
-                    pet.name,
-                    changes.join(", ")
-                );
-            } else if potty_relieved {
-                println!(
-                    "🚶 {} feels relieved after the walk! [{} {}, {} {}]",
-                    pet.name,
-                    "Energy".green(),
-                    format!("+{}", energy_change).green(),
-                    "Potty".green(),
-                    format!("{}", potty_change).green()
-                );
-            } else {
-                println!(
-                    "🚶 {} enjoyed the walk and looks refreshed! [{} {}]",
-                    pet.name,
-                    "Energy".green(),
-                    format!("+{}", energy_change).green()
-                );
-            }
-
-            Ok(())
+/// A sleeping pet is only walked if `wake` is set
+pub fn walk_pet(
+    store: &dyn PetStore,
+    pet_name: Option<&str>,
+    wake: bool,
+) -> Result<(), TermipetError> {
+    // Energy always goes up; potty only drops if the pet relieves itself (80% chance by default)
+    let relieved = store.rng().random_bool(store.rules().walk.relief_chance);
+    let (pet, effects) = perform(store, pet_name, PetAction::Walk { wake, relieved })?;
+    let deltas = effects.deltas;
+
+    if effects.woke {
+        println!("⏰ You woke {} up.", pet.name);
+    }
+
+    // Print reaction message based on what happened
+    if effects.had_accident() {
+        // Build stat changes string
+        let mut changes = vec![format!(
+            "{} {}",
+            "Energy".green(),
+            format!("+{}", deltas.energy).green()
+        )];
+
+        if relieved {
+            changes.push(format!(
+                "{} {}",
+                "Potty".green(),
+                format!("{}", deltas.potty_level).green()
+            ));
+        }
+
+        if deltas.cleanliness != 0 {
+            changes.push(format!(
+                "{} {}",
+                "Cleanliness".red(),
+                format!("{}", deltas.cleanliness).red()
+            ));
         }
-        Err(_) => {
-            println!(
-                "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
-            );
-            Ok(())
+
+        if deltas.happiness != 0 {
+            changes.push(format!(
+                "{} {}",
+                "Happiness".red(),
+                format!("{}", deltas.happiness).red()
+            ));
         }
+
+        println!(
+            "💩 {} had an accident but feels better now. [{}]",
+            pet.name,
+            changes.join(", ")
+        );
+    } else if relieved {
+        println!(
+            "🚶 {} feels relieved after the walk! [{} {}, {} {}]",
+            pet.name,
+            "Energy".green(),
+            format!("+{}", deltas.energy).green(),
+            "Potty".green(),
+            format!("{}", deltas.potty_level).green()
+        );
+    } else {
+        println!(
+            "🚶 {} enjoyed the walk and looks refreshed! [{} {}]",
+            pet.name,
+            "Energy".green(),
+            format!("+{}", deltas.energy).green()
+        );
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::adopt_pet;
+    use crate::difficulty::Difficulty;
     use crate::pet::Pet;
+    use crate::rng::GameRng;
+    use crate::store::MemoryStore;
+    use crate::utils::cap_stat;
     use std::fs;
     use tempfile::TempDir;
 
@@ -254,4 +219,24 @@ mod tests {
         // Then: accident should occur
         assert!(should_have_accident);
     }
+
+    #[test]
+    fn test_walk_relief_is_reproducible_from_a_seed() {
+        // Given: a pet that needs to go, walked with a seeded generator
+        let store = MemoryStore::new().with_rng(GameRng::seeded(1));
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        let mut pet = store.load_pet(None).unwrap().unwrap();
+        pet.potty_level = 70;
+        store.save_pet(&pet).unwrap();
+
+        // When: walking three times
+        let mut potty = Vec::new();
+        for _ in 0..3 {
+            walk_pet(&store, None, true).unwrap();
+            potty.push(store.load_pet(None).unwrap().unwrap().potty_level);
+        }
+
+        // Then: seed 1 holds on for two walks and relieves on the third
+        assert_eq!(potty, [70, 70, 20]);
+    }
 }
@@ -0,0 +1,152 @@
+use crate::actions::DECAY_INTERVAL_SECONDS;
+use crate::rules::Rules;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Lowest a casual pet's hunger, happiness or cleanliness can decay to
+pub const CASUAL_FLOOR: u8 = 20;
+
+/// How long a hardcore pet's happiness can sit at zero before it runs away
+pub const RUN_AWAY_SECONDS: u32 = 24 * 3600;
+
+/// Hunger and cleanliness an ill pet needs to be back at to recover
+pub const RECOVERY_LEVEL: u8 = 50;
+
+/// How hard a pet is to look after, chosen when it is adopted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    /// Half-speed decay and costs, twice the care, and decay never takes a stat
+    /// past `CASUAL_FLOOR`
+    Casual,
+    #[default]
+    Normal,
+    /// Decay and costs at one and a half times, two thirds of the care, and
+    /// neglect has consequences
+    Hardcore,
+}
+
+impl Difficulty {
+    /// Percentage that the speed of decay and the costs of actions are scaled by,
+    /// and that the good care does is divided by
+    fn scale_percent(self) -> i32 {
+        match self {
+            Difficulty::Casual => 50,
+            Difficulty::Normal => 100,
+            Difficulty::Hardcore => 150,
+        }
+    }
+
+    /// The rules a pet on this difficulty plays by: the energy spent on play and
+    /// training and the cost of accidents are scaled, and the good that feeding,
+    /// walks, potty breaks and cleaning do is scaled the other way
+    /// Decay is scaled through `decay_interval` instead, so no fraction of a
+    /// point per interval is lost
+    pub fn rules(self, base: &Rules) -> Rules {
+        let mut rules = base.clone();
+        let percent = self.scale_percent();
+        for change in [
+            &mut rules.play.energy,
+            &mut rules.train.energy,
+            &mut rules.accident.cleanliness,
+            &mut rules.accident.happiness,
+        ] {
+            *change = *change * percent / 100;
+        }
+        for change in [
+            &mut rules.feed.hunger,
+            &mut rules.feed.happiness,
+            &mut rules.walk.energy,
+            &mut rules.walk.potty,
+            &mut rules.potty.happiness,
+            &mut rules.clean.cleanliness,
+        ] {
+            *change = *change * 100 / percent;
+        }
+        rules
+    }
+
+    /// Seconds in a decay interval on this difficulty: twice as long on casual
+    /// and two thirds as long on hardcore, so decay runs at `scale_percent`
+    pub fn decay_interval(self) -> u64 {
+        DECAY_INTERVAL_SECONDS * 100 / self.scale_percent() as u64
+    }
+
+    /// How far from the edge decay stops: stats don't decay below it, and the
+    /// potty level doesn't decay above 100 minus it
+    pub fn decay_floor(self) -> u8 {
+        match self {
+            Difficulty::Casual => CASUAL_FLOOR,
+            Difficulty::Normal | Difficulty::Hardcore => 0,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Casual => "casual",
+            Difficulty::Normal => "normal",
+            Difficulty::Hardcore => "hardcore",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "casual" => Ok(Difficulty::Casual),
+            "normal" => Ok(Difficulty::Normal),
+            "hardcore" => Ok(Difficulty::Hardcore),
+            other => Err(format!(
+                "unknown difficulty '{}' (expected 'casual', 'normal' or 'hardcore')",
+                other
+            )),
+        }
+    }
+}
+
+/// What has become of a pet through neglect; only hardcore pets leave `Healthy`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    #[default]
+    Healthy,
+    /// Hunger or cleanliness hit zero; recovers once both are back at `RECOVERY_LEVEL`
+    Ill,
+    /// Happiness sat at zero for `RUN_AWAY_SECONDS`; the pet is gone for good
+    RanAway,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difficulty_scales_decay_and_costs() {
+        // Given: the built-in rules
+        let base = Rules::default();
+
+        // When: adjusting them for each difficulty
+        let casual = Difficulty::Casual.rules(&base);
+        let hardcore = Difficulty::Hardcore.rules(&base);
+
+        // Then: decay and costs shrink or grow, and care does the opposite
+        assert_eq!(Difficulty::Normal.rules(&base), base);
+        assert_eq!(casual.decay, base.decay);
+        assert_eq!(Difficulty::Casual.decay_interval(), 16 * 3600);
+        assert_eq!(Difficulty::Hardcore.decay_interval(), 16 * 3600 / 3);
+        assert_eq!(hardcore.train.energy, -22);
+        assert_eq!(hardcore.feed.hunger, 13);
+        assert_eq!(casual.clean.cleanliness, 80);
+        assert_eq!(casual.feed.full, base.feed.full);
+        assert_eq!(
+            "HARDCORE".parse::<Difficulty>().unwrap(),
+            Difficulty::Hardcore
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use crate::actions;
+use crate::difficulty::Condition;
+use crate::history::Action;
+use crate::journal::Event;
+use crate::pet::Pet;
+use crate::rules::Rules;
+
+/// Something the player asks the pet to do
+/// `wake` lets an energetic action go ahead while the pet is asleep. Whether
+/// the pet relieves itself on a walk is chance, so the caller rolls for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PetAction {
+    Feed,
+    Play { wake: bool },
+    Walk { wake: bool, relieved: bool },
+    Train { wake: bool },
+    Potty,
+    Clean,
+}
+
+impl PetAction {
+    /// The action as recorded in the history and undo stack
+    pub fn kind(self) -> Action {
+        match self {
+            PetAction::Feed => Action::Feed,
+            PetAction::Play { .. } => Action::Play,
+            PetAction::Walk { .. } => Action::Walk,
+            PetAction::Train { .. } => Action::Train,
+            PetAction::Potty => Action::Potty,
+            PetAction::Clean => Action::Clean,
+        }
+    }
+
+    /// Returns `wake` for actions that take energy, or None for the rest
+    fn wake(self) -> Option<bool> {
+        match self {
+            PetAction::Play { wake } | PetAction::Walk { wake, .. } | PetAction::Train { wake } => {
+                Some(wake)
+            }
+            PetAction::Feed | PetAction::Potty | PetAction::Clean => None,
+        }
+    }
+}
+
+/// Why the pet didn't go along with an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refusal {
+    /// The pet ran away through neglect and can't be looked after any more
+    RanAway,
+    /// An ill pet won't play, walk or train
+    Ill,
+    /// The pet is asleep and the action didn't wake it
+    Asleep,
+    /// Not enough energy to play or train
+    TooTired,
+    /// Hunger is already at the rules' `feed.full` or above
+    Full,
+}
+
+/// How much each stat changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatDeltas {
+    pub hunger: i32,
+    pub happiness: i32,
+    pub energy: i32,
+    pub cleanliness: i32,
+    pub potty_level: i32,
+    pub xp: i32,
+    pub level: i32,
+}
+
+impl StatDeltas {
+    pub fn between(before: &Pet, after: &Pet) -> Self {
+        Self {
+            hunger: after.hunger as i32 - before.hunger as i32,
+            happiness: after.happiness as i32 - before.happiness as i32,
+            energy: after.energy as i32 - before.energy as i32,
+            cleanliness: after.cleanliness as i32 - before.cleanliness as i32,
+            potty_level: after.potty_level as i32 - before.potty_level as i32,
+            xp: after.xp as i32 - before.xp as i32,
+            level: after.level as i32 - before.level as i32,
+        }
+    }
+}
+
+/// What an action that went ahead did
+#[derive(Debug, Clone, PartialEq)]
+pub struct Effects {
+    /// The pet as it was before the action, to undo back to
+    pub before: Pet,
+    /// The pet just after an accident it had on the way, if it had one
+    pub after_accident: Option<Pet>,
+    /// Stat changes from the whole action, accident included
+    pub deltas: StatDeltas,
+    /// The journal events the action amounts to, in order
+    pub events: Vec<Event>,
+    /// The pet was asleep and the action woke it
+    pub woke: bool,
+}
+
+impl Effects {
+    /// Returns true if the pet had an accident on the way
+    pub fn had_accident(&self) -> bool {
+        self.after_accident.is_some()
+    }
+
+    /// Levels gained by training
+    pub fn levels_gained(&self) -> u32 {
+        self.deltas.level.max(0) as u32
+    }
+}
+
+/// The result of asking the pet to do something
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionOutcome {
+    /// The pet is unchanged
+    Refused(Refusal),
+    Done(Box<Effects>),
+}
+
+/// The game's rules applied to a pet, free of storage and output
+/// Commands load the pet, hand it to the engine, then save and present the outcome
+#[derive(Debug, Clone, Copy)]
+pub struct Engine<'a> {
+    rules: &'a Rules,
+    asleep: bool,
+}
+
+impl<'a> Engine<'a> {
+    /// An engine playing by `rules`, at a time when pets are `asleep` or not
+    pub fn new(rules: &'a Rules, asleep: bool) -> Self {
+        Self { rules, asleep }
+    }
+
+    /// Returns why the pet won't do the action, if it won't
+    pub fn refusal(&self, action: PetAction, pet: &Pet) -> Option<Refusal> {
+        if pet.condition == Condition::RanAway {
+            return Some(Refusal::RanAway);
+        }
+        if let Some(wake) = action.wake() {
+            if pet.condition == Condition::Ill {
+                return Some(Refusal::Ill);
+            }
+            if self.asleep && !wake {
+                return Some(Refusal::Asleep);
+            }
+        }
+        let min_energy = match action {
+            PetAction::Play { .. } => self.rules.play.min_energy,
+            PetAction::Train { .. } => self.rules.train.min_energy,
+            _ => 0,
+        };
+        if pet.energy < min_energy {
+            return Some(Refusal::TooTired);
+        }
+        if action == PetAction::Feed && pet.hunger >= self.rules.feed.full {
+            return Some(Refusal::Full);
+        }
+        None
+    }
+
+    /// Applies an action to the pet, or leaves it untouched if the pet refuses
+    pub fn apply(&self, action: PetAction, pet: &mut Pet) -> ActionOutcome {
+        if let Some(refusal) = self.refusal(action, pet) {
+            return ActionOutcome::Refused(refusal);
+        }
+        let rules = self.rules;
+        let before = pet.clone();
+        let mut events = Vec::new();
+
+        // Accidents happen first, before a walk or potty break can help
+        let mut after_accident = None;
+        if matches!(action, PetAction::Walk { .. } | PetAction::Potty)
+            && actions::accident_due(pet, rules)
+        {
+            actions::accident(pet, rules);
+            events.push(Event::Accident);
+            after_accident = Some(pet.clone());
+        }
+
+        match action {
+            PetAction::Feed => {
+                actions::feed(pet, rules);
+                events.push(Event::Fed);
+            }
+            PetAction::Play { .. } => {
+                actions::play(pet, rules);
+                events.push(Event::Played);
+            }
+            PetAction::Walk { relieved, .. } => {
+                actions::walk(pet, relieved, rules);
+                events.push(Event::Walked { relieved });
+            }
+            PetAction::Train { .. } => {
+                let levels = actions::train(pet, rules);
+                events.push(Event::Trained);
+                if levels > 0 {
+                    events.push(Event::LevelledUp { level: pet.level });
+                }
+            }
+            PetAction::Potty => {
+                let accident = after_accident.is_some();
+                actions::potty(pet, accident, rules);
+                events.push(Event::Pottied { accident });
+            }
+            PetAction::Clean => {
+                actions::clean(pet, rules);
+                events.push(Event::Cleaned);
+            }
+        }
+
+        ActionOutcome::Done(Box::new(Effects {
+            deltas: StatDeltas::between(&before, pet),
+            woke: self.asleep && action.wake().is_some(),
+            before,
+            after_accident,
+            events,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refused_action_leaves_the_pet_alone() {
+        // Given: a tired pet, a full one and a sleeping engine
+        let rules = Rules::default();
+        let mut tired = Pet::new("Kylo".to_string(), "dog".to_string());
+        tired.energy = 5;
+        let mut full = Pet::new("Luna".to_string(), "cat".to_string());
+        full.hunger = 98;
+        let before = tired.clone();
+
+        // When/Then: each refusal says why and changes nothing
+        let awake = Engine::new(&rules, false);
+        assert_eq!(
+            awake.apply(PetAction::Train { wake: false }, &mut tired),
+            ActionOutcome::Refused(Refusal::TooTired)
+        );
+        assert_eq!(tired, before);
+        assert_eq!(
+            awake.apply(PetAction::Feed, &mut full),
+            ActionOutcome::Refused(Refusal::Full)
+        );
+        let asleep = Engine::new(&rules, true);
+        assert_eq!(
+            asleep.refusal(PetAction::Play { wake: false }, &before),
+            Some(Refusal::Asleep)
+        );
+        assert_eq!(asleep.refusal(PetAction::Clean, &before), None);
+    }
+
+    #[test]
+    fn test_walk_outcome_includes_the_accident_on_the_way() {
+        // Given: a pet that can't hold it any longer
+        let rules = Rules::default();
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.potty_level = 90;
+
+        // When: walking it while it is asleep, waking it up
+        let outcome = Engine::new(&rules, true).apply(
+            PetAction::Walk {
+                wake: true,
+                relieved: true,
+            },
+            &mut pet,
+        );
+
+        // Then: the accident and the walk are both in the outcome
+        let ActionOutcome::Done(effects) = outcome else {
+            panic!("walk refused: {:?}", outcome);
+        };
+        assert!(effects.woke);
+        assert!(effects.had_accident());
+        assert_eq!(
+            effects.events,
+            vec![Event::Accident, Event::Walked { relieved: true }]
+        );
+        assert_eq!(effects.deltas.cleanliness, -30);
+        assert_eq!(effects.deltas.potty_level, -50);
+        assert_eq!(effects.after_accident.unwrap().potty_level, 90);
+    }
+
+    #[test]
+    fn test_train_outcome_reports_the_level_up() {
+        // Given: a pet 10 XP short of a level
+        let rules = Rules::default();
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.xp = 90;
+
+        // When: training it
+        let outcome = Engine::new(&rules, false).apply(PetAction::Train { wake: false }, &mut pet);
+
+        // Then: the level-up is an event of its own
+        let ActionOutcome::Done(effects) = outcome else {
+            panic!("training refused: {:?}", outcome);
+        };
+        assert_eq!(effects.levels_gained(), 1);
+        assert_eq!(
+            effects.events,
+            vec![Event::Trained, Event::LevelledUp { level: 2 }]
+        );
+        assert_eq!(effects.deltas.energy, -15);
+    }
+}
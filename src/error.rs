@@ -0,0 +1,191 @@
+use crate::engine::{PetAction, Refusal};
+use crate::history::Action;
+use crate::pet::Pet;
+use std::{error, fmt, io};
+
+/// Why a command didn't do what was asked
+/// Each kind has its own process exit code, so scripts and shell prompts can
+/// tell a pet that won't play from a save file that won't load
+#[derive(Debug)]
+pub enum TermipetError {
+    /// No pet has been adopted yet
+    NoPet,
+    /// `--pet` or `switch` named a pet that isn't in the roster
+    UnknownPet(String),
+    /// The pet ran away through neglect and can't be looked after any more
+    RanAway(String),
+    /// An ill pet won't play, walk or train
+    Ill(String),
+    /// The pet is asleep and the action didn't wake it
+    Asleep(String),
+    /// Not enough energy for the action
+    TooTired { name: String, action: Action },
+    /// The pet turned food down
+    AlreadyFull(String),
+    /// There's nothing to undo, or the last step can no longer be undone; says why
+    NothingToUndo(String),
+    /// An argument or setting couldn't be understood
+    InvalidInput(String),
+    /// Another termipet process held the data directory for too long
+    Locked(io::Error),
+    /// A save file, journal or backup couldn't be read back
+    StorageCorrupt(io::Error),
+    /// Reading or writing failed for any other reason, e.g. git or the database
+    Io(io::Error),
+}
+
+impl TermipetError {
+    /// The error for the pet refusing an action
+    pub fn refused(pet: &Pet, action: PetAction, refusal: Refusal) -> Self {
+        let name = pet.name.clone();
+        match refusal {
+            Refusal::RanAway => TermipetError::RanAway(name),
+            Refusal::Ill => TermipetError::Ill(name),
+            Refusal::Asleep => TermipetError::Asleep(name),
+            Refusal::TooTired => TermipetError::TooTired {
+                name,
+                action: action.kind(),
+            },
+            Refusal::Full => TermipetError::AlreadyFull(name),
+        }
+    }
+
+    /// The process exit code for the error
+    /// 2 matches clap's code for bad arguments; codes from 10 up are the pet
+    /// refusing, rather than something going wrong
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TermipetError::InvalidInput(_) => 2,
+            TermipetError::NoPet => 3,
+            TermipetError::UnknownPet(_) => 4,
+            TermipetError::Locked(_) => 5,
+            TermipetError::StorageCorrupt(_) => 6,
+            TermipetError::Io(_) => 7,
+            TermipetError::RanAway(_) => 10,
+            TermipetError::Ill(_) => 11,
+            TermipetError::Asleep(_) => 12,
+            TermipetError::TooTired { .. } => 13,
+            TermipetError::AlreadyFull(_) => 14,
+            TermipetError::NothingToUndo(_) => 15,
+        }
+    }
+
+    /// Returns true if something went wrong, as opposed to there being no pet
+    /// or the pet saying no, which are shown without an "Error:" prefix
+    pub fn is_failure(&self) -> bool {
+        !matches!(self, TermipetError::NoPet) && self.exit_code() < 10
+    }
+}
+
+impl fmt::Display for TermipetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TermipetError::NoPet => write!(
+                f,
+                "No pet adopted yet. Use 'termipet adopt --name <NAME> --species <SPECIES>' to get started."
+            ),
+            TermipetError::UnknownPet(name) => write!(
+                f,
+                "No pet named '{}'. Use 'termipet list' to see your pets.",
+                name
+            ),
+            TermipetError::RanAway(name) => write!(
+                f,
+                "💔 {} ran away after being neglected for too long. Adopt a new pet to start again.",
+                name
+            ),
+            TermipetError::Ill(name) => write!(
+                f,
+                "🤒 {} is ill. Feed them and clean them up to nurse them back to health.",
+                name
+            ),
+            TermipetError::Asleep(name) => write!(
+                f,
+                "💤 {} is asleep. Let them rest, or use --wake to wake them up.",
+                name
+            ),
+            TermipetError::TooTired { name, action } => {
+                write!(f, "{} is too tired to {} right now.", name, action)
+            }
+            TermipetError::AlreadyFull(name) => {
+                write!(f, "{} is already full! Maybe later.", name)
+            }
+            TermipetError::InvalidInput(message) | TermipetError::NothingToUndo(message) => {
+                write!(f, "{}", message)
+            }
+            TermipetError::Locked(e) | TermipetError::StorageCorrupt(e) | TermipetError::Io(e) => {
+                write!(f, "{}", e)
+            }
+        }
+    }
+}
+
+impl error::Error for TermipetError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            TermipetError::Locked(e) | TermipetError::StorageCorrupt(e) | TermipetError::Io(e) => {
+                Some(e)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Sorts storage errors by their kind: the data directory lock times out with
+/// `WouldBlock`, and files that can't be parsed are `InvalidData`
+impl From<io::Error> for TermipetError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::WouldBlock => TermipetError::Locked(e),
+            io::ErrorKind::InvalidData => TermipetError::StorageCorrupt(e),
+            io::ErrorKind::InvalidInput => TermipetError::InvalidInput(e.to_string()),
+            _ => TermipetError::Io(e),
+        }
+    }
+}
+
+/// Parsers such as `parse_time` describe bad input as a plain message
+impl From<String> for TermipetError {
+    fn from(message: String) -> Self {
+        TermipetError::InvalidInput(message)
+    }
+}
+
+impl From<&str> for TermipetError {
+    fn from(message: &str) -> Self {
+        TermipetError::InvalidInput(message.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_errors_are_sorted_by_kind() {
+        // Given: the errors the stores return for a held lock, a damaged file and a full disk
+        let locked = io::Error::new(io::ErrorKind::WouldBlock, "held");
+        let corrupt = io::Error::new(io::ErrorKind::InvalidData, "bad json");
+        let full = io::Error::other("disk full");
+
+        // When/Then: each gets its own exit code
+        assert_eq!(TermipetError::from(locked).exit_code(), 5);
+        assert_eq!(TermipetError::from(corrupt).exit_code(), 6);
+        assert_eq!(TermipetError::from(full).exit_code(), 7);
+    }
+
+    #[test]
+    fn test_refusals_are_not_failures() {
+        // Given: a pet too tired to play
+        let pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        let error =
+            TermipetError::refused(&pet, PetAction::Play { wake: false }, Refusal::TooTired);
+
+        // Then: it says why, with an exit code of its own, and isn't shown as an error
+        assert_eq!(error.to_string(), "Kylo is too tired to play right now.");
+        assert_eq!(error.exit_code(), 13);
+        assert!(!error.is_failure());
+        assert!(!TermipetError::NoPet.is_failure());
+        assert!(TermipetError::from("Unknown backup").is_failure());
+    }
+}
@@ -0,0 +1,163 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Files in the data dir that never belong in a commit: the lock, half-written temp
+/// files, the history database and SQLite's scratch files, and the daily backups
+/// (git history covers those). `termipet.db` holds the SQLite store's pets, so it stays in
+const GITIGNORE: &str = ".lock\n.*.tmp-*\nhistory.db\n*.db-journal\n*.db-wal\n*.db-shm\nbackups/\n";
+
+/// The ignore pattern older versions wrote, which left `termipet.db` out of every commit
+const IGNORE_ALL_DATABASES: &str = "*.db";
+
+/// Who commits are made as when git has no user configured
+const FALLBACK_NAME: &str = "termipet";
+const FALLBACK_EMAIL: &str = "termipet@localhost";
+
+/// Returns true if the data directory is a git repository
+pub fn is_repo(data_dir: &Path) -> bool {
+    data_dir.join(".git").exists()
+}
+
+fn git(dir: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| {
+            if e.kind() == io::ErrorKind::NotFound {
+                io::Error::new(io::ErrorKind::NotFound, "git isn't installed")
+            } else {
+                e
+            }
+        })?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Commits everything that changed in the data directory
+/// Returns false if there was nothing to commit
+pub fn commit_all(data_dir: &Path, message: &str) -> io::Result<bool> {
+    update_gitignore(data_dir)?;
+    git(data_dir, &["add", "--all"])?;
+    if git(data_dir, &["status", "--porcelain"])?.trim().is_empty() {
+        return Ok(false);
+    }
+
+    let name = format!("user.name={}", FALLBACK_NAME);
+    let email = format!("user.email={}", FALLBACK_EMAIL);
+    let mut args = Vec::new();
+    if git(data_dir, &["config", "user.email"]).is_err() {
+        args.extend(["-c", name.as_str(), "-c", email.as_str()]);
+    }
+    args.extend(["commit", "--quiet", "--message", message]);
+    git(data_dir, &args)?;
+    Ok(true)
+}
+
+/// Narrows the `*.db` pattern in a `.gitignore` written by an older version to the
+/// history database, so SQLite-backed pets start being committed
+fn update_gitignore(data_dir: &Path) -> io::Result<()> {
+    let path = data_dir.join(".gitignore");
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !contents
+        .lines()
+        .any(|line| line.trim() == IGNORE_ALL_DATABASES)
+    {
+        return Ok(());
+    }
+
+    let mut updated = String::new();
+    for line in contents.lines() {
+        match line.trim() {
+            IGNORE_ALL_DATABASES => updated.push_str("history.db"),
+            "*-journal" => updated.push_str("*.db-journal"),
+            "*-wal" => updated.push_str("*.db-wal"),
+            "*-shm" => updated.push_str("*.db-shm"),
+            _ => updated.push_str(line),
+        }
+        updated.push('\n');
+    }
+    fs::write(path, updated)
+}
+
+/// Makes the data directory a git repository and commits the pets as they are
+/// Returns false if it already was one
+pub fn init_repo(data_dir: &Path) -> io::Result<bool> {
+    if is_repo(data_dir) {
+        return Ok(false);
+    }
+    fs::create_dir_all(data_dir)?;
+    git(data_dir, &["init", "--quiet"])?;
+    fs::write(data_dir.join(".gitignore"), GITIGNORE)?;
+    commit_all(data_dir, "Start tracking pets with git")?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_commit_all_only_commits_changes() {
+        // Given: a data directory turned into a repository
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("active_pet"), "Kylo").unwrap();
+        assert!(init_repo(temp_dir.path()).unwrap());
+        assert!(!init_repo(temp_dir.path()).unwrap());
+
+        // When: committing with and without changes
+        let unchanged = commit_all(temp_dir.path(), "Nothing").unwrap();
+        fs::write(temp_dir.path().join("active_pet"), "Luna").unwrap();
+        fs::write(temp_dir.path().join(".lock"), "").unwrap();
+        fs::write(temp_dir.path().join("history.db"), "").unwrap();
+        let changed = commit_all(temp_dir.path(), "Switched to Luna").unwrap();
+
+        // Then: only the change is committed, and the lock file and database are left out
+        assert!(!unchanged);
+        assert!(changed);
+        let log = git(temp_dir.path(), &["log", "--format=%s"]).unwrap();
+        assert_eq!(log, "Switched to Luna\nStart tracking pets with git\n");
+        let files = git(temp_dir.path(), &["ls-files"]).unwrap();
+        assert!(!files.contains(".lock"));
+        assert!(!files.contains("history.db"));
+    }
+
+    #[test]
+    fn test_commit_all_commits_the_sqlite_store() {
+        // Given: a repository whose .gitignore still ignores every database
+        let temp_dir = TempDir::new().unwrap();
+        assert!(init_repo(temp_dir.path()).unwrap());
+        fs::write(
+            temp_dir.path().join(".gitignore"),
+            ".lock\n.*.tmp-*\n*.db\n*-journal\n*-wal\n*-shm\nbackups/\n",
+        )
+        .unwrap();
+
+        // When: committing a pet database next to the history database and its journal
+        fs::write(temp_dir.path().join("termipet.db"), "pets").unwrap();
+        fs::write(temp_dir.path().join("history.db"), "history").unwrap();
+        fs::write(temp_dir.path().join("history.db-journal"), "").unwrap();
+        commit_all(temp_dir.path(), "Adopted Kylo").unwrap();
+
+        // Then: the pets are committed and the history database still isn't
+        let files = git(temp_dir.path(), &["ls-files"]).unwrap();
+        assert!(files.lines().any(|file| file == "termipet.db"));
+        assert!(!files.contains("history.db"));
+        let gitignore = fs::read_to_string(temp_dir.path().join(".gitignore")).unwrap();
+        assert_eq!(gitignore, GITIGNORE);
+    }
+}
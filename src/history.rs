@@ -0,0 +1,498 @@
+use crate::away::DecaySummary;
+use crate::clock::Clock;
+use crate::journal::{Event, JournalEntry};
+use crate::persistence::crypto::{append_sealed_line, read_sealed_lines};
+use crate::persistence::{decay_until, pet_slug};
+use crate::pet::Pet;
+use crate::rules::Rules;
+use chrono::{DateTime, Utc};
+use rusqlite::types::{Type, Value};
+use rusqlite::{Connection, Row, params_from_iter};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Something that changed a pet's stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Adopt,
+    Feed,
+    Play,
+    Walk,
+    Train,
+    Potty,
+    Clean,
+    Accident,
+    Decay,
+    Undo,
+    Restore,
+    Import,
+    Sync,
+    Vacation,
+}
+
+impl Action {
+    pub const ALL: [Action; 14] = [
+        Action::Adopt,
+        Action::Feed,
+        Action::Play,
+        Action::Walk,
+        Action::Train,
+        Action::Potty,
+        Action::Clean,
+        Action::Accident,
+        Action::Decay,
+        Action::Undo,
+        Action::Restore,
+        Action::Import,
+        Action::Sync,
+        Action::Vacation,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Adopt => "adopt",
+            Action::Feed => "feed",
+            Action::Play => "play",
+            Action::Walk => "walk",
+            Action::Train => "train",
+            Action::Potty => "potty",
+            Action::Clean => "clean",
+            Action::Accident => "accident",
+            Action::Decay => "decay",
+            Action::Undo => "undo",
+            Action::Restore => "restore",
+            Action::Import => "import",
+            Action::Sync => "sync",
+            Action::Vacation => "vacation",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        Action::ALL
+            .into_iter()
+            .find(|action| action.as_str() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Action::ALL.iter().map(Action::as_str).collect();
+                format!(
+                    "unknown action '{}' (expected one of: {})",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// The stats a history entry records before and after an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PetStats {
+    pub hunger: u8,
+    pub happiness: u8,
+    pub energy: u8,
+    pub cleanliness: u8,
+    pub potty_level: u8,
+    pub xp: u8,
+    pub level: u32,
+}
+
+impl From<&Pet> for PetStats {
+    fn from(pet: &Pet) -> Self {
+        Self {
+            hunger: pet.hunger,
+            happiness: pet.happiness,
+            energy: pet.energy,
+            cleanliness: pet.cleanliness,
+            potty_level: pet.potty_level,
+            xp: pet.xp,
+            level: pet.level,
+        }
+    }
+}
+
+impl PetStats {
+    /// Stat names paired with their values, in display order
+    pub fn fields(&self) -> [(&'static str, i64); 7] {
+        [
+            ("hunger", self.hunger as i64),
+            ("happiness", self.happiness as i64),
+            ("energy", self.energy as i64),
+            ("cleanliness", self.cleanliness as i64),
+            ("potty_level", self.potty_level as i64),
+            ("xp", self.xp as i64),
+            ("level", self.level as i64),
+        ]
+    }
+
+    /// Sets the pet's stats to these values
+    pub fn apply_to(&self, pet: &mut Pet) {
+        pet.hunger = self.hunger;
+        pet.happiness = self.happiness;
+        pet.energy = self.energy;
+        pet.cleanliness = self.cleanliness;
+        pet.potty_level = self.potty_level;
+        pet.xp = self.xp;
+        pet.level = self.level;
+    }
+}
+
+/// One recorded action with the pet's stats on either side of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub pet: String,
+    pub action: Action,
+    pub at: DateTime<Utc>,
+    pub before: PetStats,
+    pub after: PetStats,
+}
+
+impl HistoryEntry {
+    /// Records `action` as having turned `before` into `after` at `at`
+    pub fn new(action: Action, before: &Pet, after: &Pet, at: DateTime<Utc>) -> Self {
+        Self {
+            pet: after.name.clone(),
+            action,
+            at,
+            before: before.into(),
+            after: after.into(),
+        }
+    }
+
+    /// Returns true if any stat is different after the action
+    pub fn changed_stats(&self) -> bool {
+        self.before != self.after
+    }
+}
+
+/// Which history entries to return
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// Only entries for this pet (matched like any other pet name)
+    pub pet: Option<String>,
+    /// Only entries for this action
+    pub action: Option<Action>,
+    /// Only entries at or after this time
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.pet
+            .as_ref()
+            .is_none_or(|pet| pet_slug(pet) == pet_slug(&entry.pet))
+            && self.action.is_none_or(|action| action == entry.action)
+            && self.since.is_none_or(|since| entry.at >= since)
+    }
+}
+
+/// Decay applied while loading, held until the pet is saved
+/// Decay only becomes part of a pet's history once the decayed stats are written,
+/// so merely looking at a pet (e.g. `status`) never records it twice. Every save
+/// journals the time decayed, but only decay that cost a stat point is recorded
+/// in the history
+#[derive(Debug, Default)]
+pub(crate) struct PendingDecay {
+    entries: RefCell<HashMap<String, (HistoryEntry, JournalEntry, DecaySummary)>>,
+}
+
+impl PendingDecay {
+    /// Applies the decay due by the clock's time to a freshly loaded pet, remembering what changed
+    pub(crate) fn apply(&self, pet: &mut Pet, clock: &dyn Clock, rules: &Rules) {
+        let before = pet.clone();
+        let now = clock.now();
+        let summary = decay_until(pet, clock, rules, now);
+        if summary.seconds > 0 {
+            let entry = HistoryEntry::new(Action::Decay, &before, pet, now);
+            let event = JournalEntry::new(
+                &pet.name,
+                Event::decayed(summary.seconds, summary.asleep),
+                now,
+            );
+            self.entries
+                .borrow_mut()
+                .insert(pet_slug(&pet.name), (entry, event, summary));
+        }
+    }
+
+    /// What the decay waiting to be saved with the named pet did
+    pub(crate) fn summary(&self, name: &str) -> Option<DecaySummary> {
+        self.entries
+            .borrow()
+            .get(&pet_slug(name))
+            .map(|(_, _, summary)| summary.clone())
+    }
+
+    /// Takes the decay to record alongside a save of the named pet
+    pub(crate) fn take(&self, name: &str) -> Option<(HistoryEntry, JournalEntry)> {
+        self.entries
+            .borrow_mut()
+            .remove(&pet_slug(name))
+            .map(|(entry, event, _)| (entry, event))
+    }
+}
+
+/// Timestamp format used in the database; fixed width so text order is time order
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3fZ";
+
+/// Appends an entry to a JSON-lines history file, encrypted if the data directory is
+/// An encrypted data directory keeps its history this way instead of in SQLite
+pub(crate) fn append_to_file(data_dir: &Path, path: &Path, entry: &HistoryEntry) -> io::Result<()> {
+    let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+    append_sealed_line(data_dir, path, &line)
+}
+
+/// Returns the entries in a JSON-lines history file matching the filter, oldest first
+/// A line cut short by a crash is skipped with a warning rather than failing the read
+pub(crate) fn read_file(
+    data_dir: &Path,
+    path: &Path,
+    filter: &HistoryFilter,
+) -> io::Result<Vec<HistoryEntry>> {
+    let mut entries = Vec::new();
+    for (number, line) in read_sealed_lines(data_dir, path)?.into_iter().enumerate() {
+        match line.and_then(|line| serde_json::from_str(&line).map_err(io::Error::other)) {
+            Ok(entry) if filter.matches(&entry) => entries.push(entry),
+            Ok(_) => {}
+            Err(e) => eprintln!(
+                "⚠️  Skipping unreadable line {} of {} ({})",
+                number + 1,
+                path.display(),
+                e
+            ),
+        }
+    }
+    entries.sort_by_key(|entry: &HistoryEntry| entry.at);
+    Ok(entries)
+}
+
+fn sql_error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+/// Creates the history table if the database doesn't have it yet
+pub(crate) fn create_history_table(conn: &Connection) -> io::Result<()> {
+    let stat_columns: Vec<String> = PetStats::from(&Pet::default())
+        .fields()
+        .iter()
+        .map(|(name, _)| {
+            format!(
+                "{0}_before INTEGER NOT NULL, {0}_after INTEGER NOT NULL",
+                name
+            )
+        })
+        .collect();
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS history (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             pet_slug TEXT NOT NULL,
+             pet_name TEXT NOT NULL,
+             action TEXT NOT NULL,
+             at TEXT NOT NULL,
+             {}
+         );
+         CREATE INDEX IF NOT EXISTS history_by_pet ON history (pet_slug, at);",
+        stat_columns.join(",\n             ")
+    ))
+    .map_err(sql_error)
+}
+
+/// Appends an entry to the history table
+pub(crate) fn insert_history(conn: &Connection, entry: &HistoryEntry) -> io::Result<()> {
+    let mut columns = vec!["pet_slug", "pet_name", "action", "at"]
+        .into_iter()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    let mut values = vec![
+        Value::Text(pet_slug(&entry.pet)),
+        Value::Text(entry.pet.clone()),
+        Value::Text(entry.action.as_str().to_string()),
+        Value::Text(entry.at.format(TIMESTAMP_FORMAT).to_string()),
+    ];
+    for ((name, before), (_, after)) in entry.before.fields().into_iter().zip(entry.after.fields())
+    {
+        columns.push(format!("{}_before", name));
+        values.push(Value::Integer(before));
+        columns.push(format!("{}_after", name));
+        values.push(Value::Integer(after));
+    }
+
+    let placeholders: Vec<String> = (1..=values.len()).map(|i| format!("?{}", i)).collect();
+    conn.execute(
+        &format!(
+            "INSERT INTO history ({}) VALUES ({})",
+            columns.join(", "),
+            placeholders.join(", ")
+        ),
+        params_from_iter(values),
+    )
+    .map(|_| ())
+    .map_err(sql_error)
+}
+
+fn read_stats(row: &Row, suffix: &str) -> rusqlite::Result<PetStats> {
+    let get = |name: &str| row.get::<_, i64>(format!("{}_{}", name, suffix).as_str());
+    Ok(PetStats {
+        hunger: get("hunger")? as u8,
+        happiness: get("happiness")? as u8,
+        energy: get("energy")? as u8,
+        cleanliness: get("cleanliness")? as u8,
+        potty_level: get("potty_level")? as u8,
+        xp: get("xp")? as u8,
+        level: get("level")? as u32,
+    })
+}
+
+/// Returns the entries matching the filter, oldest first
+pub(crate) fn query_history(
+    conn: &Connection,
+    filter: &HistoryFilter,
+) -> io::Result<Vec<HistoryEntry>> {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    if let Some(pet) = &filter.pet {
+        values.push(Value::Text(pet_slug(pet)));
+        conditions.push(format!("pet_slug = ?{}", values.len()));
+    }
+    if let Some(action) = filter.action {
+        values.push(Value::Text(action.as_str().to_string()));
+        conditions.push(format!("action = ?{}", values.len()));
+    }
+    if let Some(since) = filter.since {
+        values.push(Value::Text(since.format(TIMESTAMP_FORMAT).to_string()));
+        conditions.push(format!("at >= ?{}", values.len()));
+    }
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let mut statement = conn
+        .prepare(&format!(
+            "SELECT * FROM history {} ORDER BY at, id",
+            where_clause
+        ))
+        .map_err(sql_error)?;
+    let rows = statement
+        .query_map(params_from_iter(values), |row| {
+            let action: String = row.get("action")?;
+            let at: String = row.get("at")?;
+            Ok(HistoryEntry {
+                pet: row.get("pet_name")?,
+                action: action.parse().map_err(|e: String| {
+                    rusqlite::Error::FromSqlConversionFailure(0, Type::Text, e.into())
+                })?,
+                at: DateTime::parse_from_rfc3339(&at)
+                    .map(|at| at.with_timezone(&Utc))
+                    .map_err(|e| {
+                        rusqlite::Error::FromSqlConversionFailure(0, Type::Text, e.into())
+                    })?,
+                before: read_stats(row, "before")?,
+                after: read_stats(row, "after")?,
+            })
+        })
+        .map_err(sql_error)?;
+    rows.collect::<Result<_, _>>().map_err(sql_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use chrono::{Duration, FixedOffset, TimeZone};
+
+    fn sample_entry(name: &str, action: Action, hours_ago: i64) -> HistoryEntry {
+        let before = Pet::new(name.to_string(), "dog".to_string());
+        let mut after = before.clone();
+        after.cleanliness = 50;
+        let mut entry = HistoryEntry::new(action, &before, &after, Utc::now());
+        entry.at = Utc::now() - Duration::hours(hours_ago);
+        entry
+    }
+
+    #[test]
+    fn test_history_round_trips_through_sqlite() {
+        // Given: a history table with entries for two pets
+        let conn = Connection::open_in_memory().unwrap();
+        create_history_table(&conn).unwrap();
+        insert_history(&conn, &sample_entry("Kylo", Action::Accident, 48)).unwrap();
+        insert_history(&conn, &sample_entry("Kylo", Action::Feed, 2)).unwrap();
+        insert_history(&conn, &sample_entry("Luna", Action::Accident, 1)).unwrap();
+
+        // When: asking for Kylo's accidents
+        let filter = HistoryFilter {
+            pet: Some("kylo".to_string()),
+            action: Some(Action::Accident),
+            since: None,
+        };
+        let entries = query_history(&conn, &filter).unwrap();
+
+        // Then: only the matching entry comes back, stats intact
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].pet, "Kylo");
+        assert_eq!(entries[0].before.cleanliness, 80);
+        assert_eq!(entries[0].after.cleanliness, 50);
+        assert!(entries.iter().all(|entry| filter.matches(entry)));
+    }
+
+    #[test]
+    fn test_history_filters_by_time() {
+        // Given: an old and a recent entry
+        let conn = Connection::open_in_memory().unwrap();
+        create_history_table(&conn).unwrap();
+        insert_history(&conn, &sample_entry("Kylo", Action::Play, 24 * 40)).unwrap();
+        insert_history(&conn, &sample_entry("Kylo", Action::Play, 24)).unwrap();
+
+        // When: asking for the last 30 days
+        let filter = HistoryFilter {
+            since: Some(Utc::now() - Duration::days(30)),
+            ..Default::default()
+        };
+
+        // Then: only the recent entry is returned
+        assert_eq!(query_history(&conn, &filter).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_pending_decay_is_taken_once() {
+        // Given: a pet last updated at 20:00, 16 hours ago
+        let pending = PendingDecay::default();
+        let now = Utc.with_ymd_and_hms(2025, 10, 17, 12, 0, 0).unwrap();
+        let clock = FixedClock::new(now).with_offset(FixedOffset::east_opt(0).unwrap());
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.last_updated = now - Duration::hours(16);
+
+        // When: decay is applied on load
+        pending.apply(&mut pet, &clock, &Rules::default());
+
+        // Then: one decay entry is waiting for the save, and only one
+        let (entry, event) = pending.take("kylo").unwrap();
+        assert_eq!(entry.action, Action::Decay);
+        assert_eq!(event.event, Event::decayed(16 * 3600, 9 * 3600));
+        assert_eq!(entry.before.hunger, 80);
+        assert_eq!(entry.after.hunger, 74);
+        assert!(pending.take("Kylo").is_none());
+    }
+
+    #[test]
+    fn test_action_parses_names() {
+        // Given/When/Then: action names parse case-insensitively
+        assert_eq!("Accident".parse::<Action>().unwrap(), Action::Accident);
+        assert!("nap".parse::<Action>().is_err());
+    }
+}
@@ -0,0 +1,545 @@
+use crate::actions;
+use crate::difficulty::{Condition, Difficulty};
+use crate::history::PetStats;
+use crate::persistence::crypto::{append_sealed_line, read_sealed_lines};
+use crate::persistence::pet_slug;
+use crate::pet::Pet;
+use crate::rules::Rules;
+use crate::vacation::Vacation;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+
+use std::io;
+use std::path::Path;
+
+/// Something that happened to a pet
+/// Events record what happened, not the resulting stats, so replaying them
+/// applies the current rules from `actions`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Event {
+    Adopted {
+        species: String,
+        #[serde(default, skip_serializing_if = "is_default")]
+        difficulty: Difficulty,
+    },
+    Fed,
+    Played,
+    /// `relieved` is whether the pet managed to go potty on the walk
+    Walked {
+        relieved: bool,
+    },
+    Trained,
+    Accident,
+    /// `accident` is whether the break came too late
+    Pottied {
+        accident: bool,
+    },
+    Cleaned,
+    /// Decay over `seconds` of elapsed time, `asleep` of which the pet rested
+    /// through; journals from before decay was continuous record whole 8-hour
+    /// `intervals` instead
+    Decayed {
+        #[serde(default, skip_serializing_if = "is_default")]
+        seconds: u64,
+        #[serde(default, skip_serializing_if = "is_default")]
+        asleep: u64,
+        #[serde(default, skip_serializing_if = "is_default")]
+        intervals: u32,
+    },
+    /// Recorded for the audit trail; replaying `Trained` already levels up
+    LevelledUp {
+        level: u32,
+    },
+    /// The most recent action was undone
+    Undone,
+    Released,
+    /// Brought back from a backup with these stats
+    Restored {
+        species: String,
+        stats: PetStats,
+        #[serde(flatten)]
+        state: PetState,
+    },
+    /// Moved in from an exported file with these stats
+    Imported {
+        species: String,
+        stats: PetStats,
+        #[serde(flatten)]
+        state: PetState,
+    },
+    /// `sitter` is whether a pet-sitter looks after the pet, rather than its stats freezing
+    VacationStarted {
+        #[serde(default, skip_serializing_if = "is_default")]
+        sitter: bool,
+    },
+    VacationEnded,
+}
+
+/// Everything a restored or imported pet brings with it besides its stats
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PetState {
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub decay_progress: u32,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub rest_progress: u32,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub difficulty: Difficulty,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub condition: Condition,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub neglect_seconds: u32,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub vacation: Option<Vacation>,
+}
+
+impl From<&Pet> for PetState {
+    fn from(pet: &Pet) -> Self {
+        Self {
+            decay_progress: pet.decay_progress,
+            rest_progress: pet.rest_progress,
+            difficulty: pet.difficulty,
+            condition: pet.condition,
+            neglect_seconds: pet.neglect_seconds,
+            vacation: pet.vacation.clone(),
+        }
+    }
+}
+
+impl PetState {
+    /// Gives the pet this state
+    pub fn apply_to(&self, pet: &mut Pet) {
+        pet.decay_progress = self.decay_progress;
+        pet.rest_progress = self.rest_progress;
+        pet.difficulty = self.difficulty;
+        pet.condition = self.condition;
+        pet.neglect_seconds = self.neglect_seconds;
+        pet.vacation = self.vacation.clone();
+    }
+}
+
+impl Event {
+    /// Decay for a pet's journal, covering `seconds` of elapsed time with `asleep` spent asleep
+    pub fn decayed(seconds: u64, asleep: u64) -> Self {
+        Event::Decayed {
+            seconds,
+            asleep,
+            intervals: 0,
+        }
+    }
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// One line of the journal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub at: DateTime<Utc>,
+    pub pet: String,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+impl JournalEntry {
+    /// An event that happened to the named pet at `at`
+    pub fn new(pet: &str, event: Event, at: DateTime<Utc>) -> Self {
+        Self {
+            at,
+            pet: pet.to_string(),
+            event,
+        }
+    }
+}
+
+/// A pet as rebuilt so far, with its state before each action that `Undone` can revert
+#[derive(Default)]
+struct Replayed {
+    pet: Option<Pet>,
+    checkpoints: Vec<Pet>,
+    /// The previous event was an accident, which belongs to the walk or potty break after it
+    after_accident: bool,
+    /// When decay was last applied, so decay recorded on two synced machines
+    /// for the same hours is only applied once
+    decayed_at: Option<DateTime<Utc>>,
+}
+
+impl Replayed {
+    /// Applies one event, mirroring what the undo stack remembers for it
+    fn apply(mut self, entry: &JournalEntry, rules: &Rules) -> Self {
+        let after_accident = std::mem::replace(&mut self.after_accident, false);
+        match &entry.event {
+            Event::Adopted {
+                species,
+                difficulty,
+            } => {
+                let mut pet = Pet::new(entry.pet.clone(), species.clone());
+                pet.difficulty = *difficulty;
+                self.pet = Some(pet);
+                self.checkpoints.clear();
+                self.decayed_at = None;
+            }
+            Event::Released => {
+                self.pet = None;
+                self.checkpoints.clear();
+                self.decayed_at = None;
+            }
+            Event::Restored {
+                species,
+                stats,
+                state,
+            }
+            | Event::Imported {
+                species,
+                stats,
+                state,
+            } => {
+                let mut pet = Pet::new(entry.pet.clone(), species.clone());
+                stats.apply_to(&mut pet);
+                state.apply_to(&mut pet);
+                self.pet = Some(pet);
+                self.checkpoints.clear();
+                self.decayed_at = None;
+            }
+            event => {
+                // Nothing to apply an event to before the adoption
+                let Some(mut pet) = self.pet.take() else {
+                    return self;
+                };
+                let starts_action = match event {
+                    Event::Walked { .. } | Event::Pottied { .. } => !after_accident,
+                    Event::Fed
+                    | Event::Played
+                    | Event::Trained
+                    | Event::Accident
+                    | Event::Cleaned => true,
+                    _ => false,
+                };
+                if starts_action {
+                    self.checkpoints.push(pet.clone());
+                }
+
+                match event {
+                    Event::Fed => actions::feed(&mut pet, rules),
+                    Event::Played => actions::play(&mut pet, rules),
+                    Event::Walked { relieved } => actions::walk(&mut pet, *relieved, rules),
+                    Event::Trained => {
+                        actions::train(&mut pet, rules);
+                    }
+                    Event::Accident => {
+                        actions::accident(&mut pet, rules);
+                        self.after_accident = true;
+                    }
+                    Event::Pottied { accident } => actions::potty(&mut pet, *accident, rules),
+                    Event::Cleaned => actions::clean(&mut pet, rules),
+                    Event::Decayed {
+                        seconds,
+                        asleep,
+                        intervals,
+                    } => {
+                        let elapsed = seconds + *intervals as u64 * actions::DECAY_INTERVAL_SECONDS;
+                        // Time already covered by an earlier decay isn't applied again
+                        let due = self.decayed_at.map_or(elapsed, |decayed_at| {
+                            (entry.at - decayed_at).num_seconds().max(0) as u64
+                        });
+                        let before = PetStats::from(&pet);
+                        actions::decay(&mut pet, elapsed.min(due), rules);
+                        // Sleep is taken to be the latest part of the time, which is
+                        // how it falls when the overlap is an evening synced twice
+                        actions::rest(&mut pet, (*asleep).min(due));
+                        self.decayed_at = Some(entry.at);
+                        // Undo never reaches back past decay that cost a stat point
+                        if PetStats::from(&pet) != before {
+                            self.checkpoints.clear();
+                        }
+                    }
+                    Event::VacationStarted { sitter } => {
+                        pet.vacation = Some(Vacation::new(entry.at, *sitter));
+                        self.checkpoints.clear();
+                    }
+                    Event::VacationEnded => {
+                        pet.vacation = None;
+                        self.checkpoints.clear();
+                    }
+                    Event::Undone => {
+                        // Undoing an action never gives back time towards decay, rest,
+                        // neglect or the sitter's next visit
+                        if let Some(before) = self.checkpoints.pop() {
+                            pet = Pet {
+                                decay_progress: pet.decay_progress,
+                                rest_progress: pet.rest_progress,
+                                neglect_seconds: pet.neglect_seconds,
+                                vacation: pet.vacation.clone(),
+                                ..before
+                            };
+                        }
+                    }
+                    Event::LevelledUp { .. }
+                    | Event::Adopted { .. }
+                    | Event::Released
+                    | Event::Restored { .. }
+                    | Event::Imported { .. } => {}
+                }
+                self.pet = Some(pet);
+            }
+        }
+
+        if let Some(pet) = &mut self.pet {
+            pet.last_updated = entry.at;
+        }
+        self
+    }
+}
+
+/// Rebuilds a pet by replaying its journal entries in time order under `rules`
+/// Entries after `until` are ignored; returns None if the pet hadn't been
+/// adopted by then (or had been released)
+pub fn replay(
+    entries: &[JournalEntry],
+    name: &str,
+    until: Option<DateTime<Utc>>,
+    rules: &Rules,
+) -> Option<Pet> {
+    let slug = pet_slug(name);
+    let mut entries: Vec<&JournalEntry> = entries
+        .iter()
+        .filter(|entry| pet_slug(&entry.pet) == slug)
+        .filter(|entry| until.is_none_or(|until| entry.at <= until))
+        .collect();
+    entries.sort_by_key(|entry| entry.at);
+    entries
+        .into_iter()
+        .fold(Replayed::default(), |replayed, entry| {
+            replayed.apply(entry, rules)
+        })
+        .pet
+}
+
+/// Appends an entry to a JSON-lines journal file, encrypted if the data directory is
+pub(crate) fn append_to_file(data_dir: &Path, path: &Path, entry: &JournalEntry) -> io::Result<()> {
+    let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+    append_sealed_line(data_dir, path, &line)
+}
+
+/// Reads every entry from a JSON-lines journal file
+/// A line cut short by a crash is skipped with a warning rather than failing the read
+pub(crate) fn read_file(data_dir: &Path, path: &Path) -> io::Result<Vec<JournalEntry>> {
+    let mut entries = Vec::new();
+    for (number, line) in read_sealed_lines(data_dir, path)?.into_iter().enumerate() {
+        match line.and_then(|line| serde_json::from_str(&line).map_err(io::Error::other)) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => eprintln!(
+                "⚠️  Skipping unreadable line {} of {} ({})",
+                number + 1,
+                path.display(),
+                e
+            ),
+        }
+    }
+    Ok(entries)
+}
+
+fn sql_error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+/// Creates the journal table if the database doesn't have it yet
+pub(crate) fn create_journal_table(conn: &Connection) -> io::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS journal (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             pet_slug TEXT NOT NULL,
+             entry TEXT NOT NULL
+         );",
+    )
+    .map_err(sql_error)
+}
+
+/// Appends an entry to the journal table
+pub(crate) fn insert_journal(conn: &Connection, entry: &JournalEntry) -> io::Result<()> {
+    let json = serde_json::to_string(entry).map_err(io::Error::other)?;
+    conn.execute(
+        "INSERT INTO journal (pet_slug, entry) VALUES (?1, ?2)",
+        params![pet_slug(&entry.pet), json],
+    )
+    .map(|_| ())
+    .map_err(sql_error)
+}
+
+/// Reads every entry from the journal table, in the order they were appended
+pub(crate) fn query_journal(conn: &Connection) -> io::Result<Vec<JournalEntry>> {
+    let mut statement = conn
+        .prepare("SELECT entry FROM journal ORDER BY id")
+        .map_err(sql_error)?;
+    let rows = statement
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(sql_error)?;
+
+    let mut entries = Vec::new();
+    for json in rows {
+        let json = json.map_err(sql_error)?;
+        entries.push(
+            serde_json::from_str(&json)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        );
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn entry_at(hours: i64, event: Event) -> JournalEntry {
+        let start = DateTime::parse_from_rfc3339("2025-10-16T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        JournalEntry {
+            at: start + Duration::hours(hours),
+            pet: "Kylo".to_string(),
+            event,
+        }
+    }
+
+    fn sample_journal() -> Vec<JournalEntry> {
+        vec![
+            entry_at(
+                0,
+                Event::Adopted {
+                    species: "dog".to_string(),
+                    difficulty: Difficulty::Normal,
+                },
+            ),
+            entry_at(1, Event::Fed),
+            entry_at(2, Event::Walked { relieved: false }),
+            entry_at(10, Event::decayed(8 * 3600, 0)),
+            entry_at(10, Event::Trained),
+        ]
+    }
+
+    #[test]
+    fn test_replay_rebuilds_state_from_events() {
+        // Given: a journal of adopt, feed, walk, decay and train
+        let journal = sample_journal();
+
+        // When: replaying everything
+        let pet = replay(&journal, "kylo", None, &Rules::default()).unwrap();
+
+        // Then: every event's rule was applied in order
+        assert_eq!(pet.name, "Kylo");
+        assert_eq!(pet.hunger, 97); // 80 + 20, then -3
+        assert_eq!(pet.happiness, 88); // 80 + 10, then -2
+        assert_eq!(pet.energy, 80); // 80 + 15 capped at 95, then -15
+        assert_eq!(pet.xp, 20);
+        assert_eq!(pet.last_updated, journal[4].at);
+    }
+
+    #[test]
+    fn test_replay_stops_at_until() {
+        // Given: the same journal
+        let journal = sample_journal();
+
+        // When: replaying only up to just after the feed
+        let pet = replay(&journal, "Kylo", Some(journal[1].at), &Rules::default()).unwrap();
+
+        // Then: later events are ignored
+        assert_eq!(pet.hunger, 100);
+        assert_eq!(pet.energy, 80);
+
+        // And: before the adoption there is no pet at all
+        let before_adoption = journal[0].at - Duration::minutes(1);
+        assert!(replay(&journal, "Kylo", Some(before_adoption), &Rules::default()).is_none());
+    }
+
+    #[test]
+    fn test_replay_reverts_undone_actions() {
+        // Given: a walk with an accident, then a feed, both undone
+        let mut journal = sample_journal();
+        journal.extend([
+            entry_at(11, Event::Accident),
+            entry_at(11, Event::Walked { relieved: true }),
+            entry_at(12, Event::Fed),
+            entry_at(12, Event::Undone),
+            entry_at(13, Event::Undone),
+        ]);
+
+        // When: replaying everything
+        let pet = replay(&journal, "Kylo", None, &Rules::default()).unwrap();
+
+        // Then: the accident went with the walk it happened on
+        let before_walk = replay(&journal, "Kylo", Some(journal[4].at), &Rules::default()).unwrap();
+        assert_eq!(pet.cleanliness, before_walk.cleanliness);
+        assert_eq!(pet.energy, before_walk.energy);
+        assert_eq!(pet.hunger, before_walk.hunger);
+    }
+
+    #[test]
+    fn test_replay_released_pet_is_gone() {
+        // Given: a journal ending with the pet being released
+        let mut journal = sample_journal();
+        journal.push(entry_at(11, Event::Released));
+
+        // When/Then: replaying yields no pet
+        assert!(replay(&journal, "Kylo", None, &Rules::default()).is_none());
+    }
+
+    #[test]
+    fn test_replay_applies_overlapping_decay_once() {
+        // Given: two synced machines that both recorded decay for the same night
+        let mut journal = sample_journal();
+        journal.extend([
+            entry_at(26, Event::decayed(16 * 3600, 9 * 3600)),
+            entry_at(27, Event::decayed(16 * 3600, 9 * 3600)),
+        ]);
+
+        // When: replaying the merged journal
+        let pet = replay(&journal, "Kylo", None, &Rules::default()).unwrap();
+
+        // Then: the second machine's decay only adds the hour the first didn't cover
+        let after_first = replay(&journal, "Kylo", Some(journal[5].at), &Rules::default()).unwrap();
+        assert_eq!(after_first.hunger, 91); // 97, then -3 twice
+        assert_eq!(pet.hunger, 91); // 17 hours in all, not 32
+        assert_eq!(pet.decay_progress, 3600);
+        // And: the night's rest is only counted once too
+        assert_eq!(pet.energy, after_first.energy);
+        assert_eq!(pet.rest_progress, 3600);
+    }
+
+    #[test]
+    fn test_replay_reads_decay_from_older_journals() {
+        // Given: a decay line written before decay was continuous
+        let line = r#"{"at":"2025-10-16T19:00:00Z","pet":"Kylo","type":"Decayed","intervals":1}"#;
+        let mut journal = sample_journal();
+        journal[3] = serde_json::from_str(line).unwrap();
+
+        // When: replaying it
+        let pet = replay(&journal, "Kylo", None, &Rules::default()).unwrap();
+
+        // Then: it counts as a whole interval
+        assert_eq!(pet.hunger, 97);
+    }
+
+    #[test]
+    fn test_journal_file_round_trip_skips_torn_line() {
+        // Given: a journal file whose last line was cut short
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("journal.jsonl");
+        for entry in sample_journal() {
+            append_to_file(temp_dir.path(), &path, &entry).unwrap();
+        }
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"at\":\"2025-10-").unwrap();
+
+        // When: reading it back
+        let entries = read_file(temp_dir.path(), &path).unwrap();
+
+        // Then: every complete line is returned in order
+        assert_eq!(entries, sample_journal());
+    }
+}
@@ -1,12 +1,35 @@
+pub mod actions;
+pub mod away;
+pub mod backup;
+pub mod clock;
 pub mod commands;
+pub mod difficulty;
+pub mod engine;
+pub mod error;
+pub mod git;
+pub mod history;
+pub mod journal;
 pub mod mood;
 pub mod persistence;
 pub mod pet;
+pub mod recovery;
+pub mod rng;
+pub mod rules;
+pub mod sleep;
+pub mod store;
+pub mod sync;
+pub mod transfer;
+pub mod undo;
 pub mod utils;
+pub mod vacation;
 
 pub use commands::{
-    adopt_pet, clean_pet, feed_pet, play_pet, potty_pet, reset_pet, run_shell, show_status,
-    train_pet, walk_pet,
+    adopt_pet, catch_up, clean_pet, decrypt_saves, encrypt_saves, end_vacation, export_pet,
+    feed_pet, import_pet, init_git, list_roster, play_pet, potty_pet, replay_pet, reset_pet,
+    restore_backup, run_doctor, run_shell, show_backups, show_history, show_status, start_vacation,
+    switch_pet, sync_pets, train_pet, undo_pet, walk_pet,
 };
-pub use persistence::{load_pet, save_pet};
+pub use error::TermipetError;
+pub use persistence::save_pet;
 pub use pet::Pet;
+pub use store::{JsonFileStore, MemoryStore, PetStore, SqliteStore};
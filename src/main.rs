@@ -1,13 +1,48 @@
+use chrono::Utc;
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::rc::Rc;
+use termipet::backup::take_daily_backup;
+use termipet::clock::{Clock, FixedClock, SystemClock};
+use termipet::difficulty::Difficulty;
+use termipet::history::Action;
+use termipet::persistence::resolve_data_dir;
+use termipet::rng::{GameRng, resolve_seed};
+use termipet::rules::Rules;
+use termipet::store::{StoreKind, open_store, resolve_store_kind};
+use termipet::transfer::Format;
+use termipet::utils::parse_time;
 use termipet::{
-    adopt_pet, clean_pet, feed_pet, play_pet, potty_pet, reset_pet, run_shell, show_status,
-    train_pet, walk_pet,
+    TermipetError, adopt_pet, catch_up, clean_pet, decrypt_saves, encrypt_saves, end_vacation,
+    export_pet, feed_pet, import_pet, init_git, list_roster, play_pet, potty_pet, replay_pet,
+    reset_pet, restore_backup, run_doctor, run_shell, show_backups, show_history, show_status,
+    start_vacation, switch_pet, sync_pets, train_pet, undo_pet, walk_pet,
 };
 
 #[derive(Parser)]
 #[command(name = "termipet")]
 #[command(about = "A virtual pet that lives in your terminal", long_about = None)]
 struct Cli {
+    /// Name of the pet to act on (defaults to the active pet)
+    #[arg(long, global = true)]
+    pet: Option<String>,
+
+    /// Directory where pet data is stored (overrides TERMIPET_HOME)
+    #[arg(long, global = true, value_name = "DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// Storage backend: json or sqlite (overrides TERMIPET_STORE)
+    #[arg(long, global = true, value_name = "BACKEND")]
+    store: Option<StoreKind>,
+
+    /// Pretend it is this time, for debugging decay (e.g. 2025-10-20T09:00:00Z)
+    #[arg(long, global = true, value_name = "TIMESTAMP")]
+    now: Option<String>,
+
+    /// Seed for random events, to reproduce a run exactly (overrides TERMIPET_SEED)
+    #[arg(long, global = true, value_name = "SEED")]
+    seed: Option<u64>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -23,100 +58,384 @@ enum Commands {
         /// Species of your pet (e.g., dog, cat, dragon)
         #[arg(short, long)]
         species: String,
+
+        /// How hard your pet is to look after: casual, normal or hardcore
+        #[arg(long, default_value = "normal")]
+        difficulty: Difficulty,
     },
     /// Check your pet's current status
     Status,
+    /// List all your pets
+    List,
+    /// Switch the active pet
+    Switch {
+        /// Name of the pet to make active
+        name: String,
+    },
     /// Feed your pet to restore hunger and happiness
     Feed,
     /// Play with your pet to increase happiness
-    Play,
+    Play {
+        /// Wake your pet up if it is asleep
+        #[arg(long)]
+        wake: bool,
+    },
     /// Walk your pet to restore energy and manage potty needs
-    Walk,
+    Walk {
+        /// Wake your pet up if it is asleep
+        #[arg(long)]
+        wake: bool,
+    },
     /// Train your pet to gain XP and level up
-    Train,
+    Train {
+        /// Wake your pet up if it is asleep
+        #[arg(long)]
+        wake: bool,
+    },
     /// Help your pet go potty to reset potty level
     Potty,
     /// Clean your pet to increase cleanliness
     Clean,
+    /// Undo your last action
+    Undo,
     /// Reset your pet and start over
     Reset,
     /// Enter interactive shell mode
     Shell,
+    /// Show what has happened to your pet
+    History {
+        /// Only show one kind of action (e.g. feed, accident, decay)
+        #[arg(long)]
+        action: Option<Action>,
+
+        /// Only show entries since a time: 30d, 12h, 2w or a date like 2025-10-01
+        #[arg(long, value_name = "WHEN")]
+        since: Option<String>,
+    },
+    /// Rebuild your pet from its event journal
+    Replay {
+        /// Stop at a time: 30d, 12h, 2w, a date like 2025-10-01 or 2025-10-01T18:30:00Z
+        #[arg(long, value_name = "WHEN")]
+        until: Option<String>,
+    },
+    /// List or restore the daily backups of your pets
+    Backup {
+        #[command(subcommand)]
+        command: BackupCommands,
+    },
+    /// Pause your pet's decay while you're away, or leave it with a pet-sitter
+    Vacation {
+        #[command(subcommand)]
+        command: VacationCommands,
+    },
+    /// Export your pet to a file you can import on another machine
+    Export {
+        /// File format: json, toml or yaml
+        #[arg(long, default_value = "json")]
+        format: Format,
+
+        /// File to write (prints to the terminal if omitted)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Import a pet from an exported file
+    Import {
+        /// The exported file
+        file: PathBuf,
+
+        /// File format: json, toml or yaml (guessed from the extension if omitted)
+        #[arg(long)]
+        format: Option<Format>,
+
+        /// Import the pet under a different name
+        #[arg(long, value_name = "NAME")]
+        rename: Option<String>,
+
+        /// Replace a pet with the same name
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Merge your pets with a synced directory shared between machines
+    Sync {
+        /// The synced directory, e.g. a folder in your dotfiles checkout
+        #[arg(long, value_name = "PATH")]
+        dir: PathBuf,
+    },
+    /// Track your pets' data directory with git
+    Git {
+        #[command(subcommand)]
+        command: GitCommands,
+    },
+    /// Encrypt your save files and backups with the passphrase in TERMIPET_PASSPHRASE
+    Encrypt,
+    /// Turn encryption off, saving your pets as plain JSON again
+    Decrypt,
+    /// Check the save directory for damaged files and repair them
+    Doctor {
+        /// Fix the problems found instead of only reporting them
+        #[arg(long)]
+        repair: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BackupCommands {
+    /// List the backups, newest first
+    List,
+    /// Bring pets back from a backup (only the --pet one, if given)
+    Restore {
+        /// The backup to restore, as shown by 'termipet backup list'
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum VacationCommands {
+    /// Go on vacation: your pet's stats freeze until you come back
+    Start {
+        /// Leave your pet with a pet-sitter who feeds and walks it twice a day instead
+        #[arg(long)]
+        sitter: bool,
+    },
+    /// Come back from vacation, so your pet's stats decay as usual again
+    End,
+}
+
+#[derive(Subcommand)]
+enum GitCommands {
+    /// Make the data directory a git repository that every change is committed to
+    Init,
+}
+
+/// Reports why a command didn't go ahead and exits with the error's own code
+fn fail(e: TermipetError) -> ! {
+    if e.is_failure() {
+        eprintln!("Error: {}", e);
+    } else {
+        eprintln!("{}", e);
+    }
+    std::process::exit(e.exit_code())
 }
 
 fn main() {
     let cli = Cli::parse();
 
+    let data_dir = match resolve_data_dir(cli.data_dir.as_deref()) {
+        Ok(dir) => dir,
+        Err(e) => fail(e.into()),
+    };
+    let kind = match resolve_store_kind(cli.store) {
+        Ok(kind) => kind,
+        Err(e) => fail(e.into()),
+    };
+    let clock: Rc<dyn Clock> = match cli.now.as_deref() {
+        Some(now) => match parse_time(now, Utc::now()) {
+            Ok(now) => Rc::new(FixedClock::new(now)),
+            Err(e) => fail(TermipetError::InvalidInput(format!("--now {}", e))),
+        },
+        None => Rc::new(SystemClock),
+    };
+    let rng = match resolve_seed(cli.seed) {
+        Ok(Some(seed)) => GameRng::seeded(seed),
+        Ok(None) => GameRng::from_entropy(),
+        Err(e) => fail(e.into()),
+    };
+    // A broken rules.toml mustn't stop the commands that look after the data
+    // directory itself, so those fall back to the built-in rules
+    let rules = match Rules::load(&data_dir) {
+        Ok(rules) => rules,
+        Err(e)
+            if matches!(
+                cli.command,
+                Commands::Doctor { .. }
+                    | Commands::Backup { .. }
+                    | Commands::Git { .. }
+                    | Commands::Encrypt
+                    | Commands::Decrypt
+            ) =>
+        {
+            eprintln!("⚠️  {}; using the built-in rules", e);
+            Rules::default()
+        }
+        Err(e) => fail(e.into()),
+    };
+    let store = match open_store(kind, &data_dir, clock, rng, rules) {
+        Ok(store) => store,
+        Err(e) => fail(e.into()),
+    };
+    let store = store.as_ref();
+
+    // Fail early if --pet names a pet that is not in the roster
+    // (released pets can still be replayed from the journal or restored from a backup)
+    if let Some(name) = &cli.pet
+        && !matches!(
+            cli.command,
+            Commands::Replay { .. } | Commands::Backup { .. }
+        )
+    {
+        match store.pet_exists(name) {
+            Ok(true) => {}
+            Ok(false) => fail(TermipetError::UnknownPet(name.clone())),
+            Err(e) => fail(e.into()),
+        }
+    }
+    let pet = cli.pet.as_deref();
+
+    // Back up the pets once a day, before the first command changes anything
+    // (except doctor, which must see damaged files before anything loads them)
+    if !matches!(cli.command, Commands::Doctor { .. })
+        && let Err(e) = take_daily_backup(store)
+    {
+        eprintln!("⚠️  Couldn't take today's backup: {}", e);
+    }
+
+    // Tell what happened while the pet was left alone before the command's own output
+    // (the shell does this before each of its commands instead)
+    if matches!(
+        cli.command,
+        Commands::Status
+            | Commands::Feed
+            | Commands::Play { .. }
+            | Commands::Walk { .. }
+            | Commands::Train { .. }
+            | Commands::Potty
+            | Commands::Clean
+            | Commands::History { .. }
+    ) {
+        match catch_up(store, pet) {
+            Ok(Some(digest)) => println!("{}", digest),
+            Ok(None) => {}
+            Err(e) => fail(e),
+        }
+    }
+
     match cli.command {
-        Commands::Adopt { name, species } => match adopt_pet(&name, &species) {
+        Commands::Adopt {
+            name,
+            species,
+            difficulty,
+        } => match adopt_pet(store, &name, &species, difficulty) {
             Ok(message) => println!("{}", message),
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
+            Err(e) => fail(e),
         },
-        Commands::Status => match show_status() {
+        Commands::Status => match show_status(store, pet) {
             Ok(_) => {}
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
+            Err(e) => fail(e),
         },
-        Commands::Feed => match feed_pet() {
+        Commands::List => match list_roster(store) {
             Ok(_) => {}
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
+            Err(e) => fail(e),
         },
-        Commands::Play => match play_pet() {
+        Commands::Switch { name } => match switch_pet(store, &name) {
+            Ok(message) => println!("{}", message),
+            Err(e) => fail(e),
+        },
+        Commands::Feed => match feed_pet(store, pet) {
             Ok(_) => {}
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
+            Err(e) => fail(e),
         },
-        Commands::Walk => match walk_pet() {
+        Commands::Play { wake } => match play_pet(store, pet, wake) {
             Ok(_) => {}
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
+            Err(e) => fail(e),
         },
-        Commands::Train => match train_pet() {
+        Commands::Walk { wake } => match walk_pet(store, pet, wake) {
             Ok(_) => {}
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
+            Err(e) => fail(e),
         },
-        Commands::Potty => match potty_pet() {
+        Commands::Train { wake } => match train_pet(store, pet, wake) {
             Ok(_) => {}
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
+            Err(e) => fail(e),
         },
-        Commands::Clean => match clean_pet() {
+        Commands::Potty => match potty_pet(store, pet) {
             Ok(_) => {}
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            }
+            Err(e) => fail(e),
+        },
+        Commands::Clean => match clean_pet(store, pet) {
+            Ok(_) => {}
+            Err(e) => fail(e),
+        },
+        Commands::Undo => match undo_pet(store, pet) {
+            Ok(_) => {}
+            Err(e) => fail(e),
+        },
+        Commands::Reset => match reset_pet(store, pet) {
+            Ok(_) => {}
+            Err(e) => fail(e),
         },
-        Commands::Reset => match reset_pet() {
+        Commands::Shell => match run_shell(store, pet) {
             Ok(_) => {}
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
+            Err(e) => fail(e),
+        },
+        Commands::History { action, since } => {
+            match show_history(store, pet, action, since.as_deref()) {
+                Ok(_) => {}
+                Err(e) => fail(e),
             }
+        }
+        Commands::Replay { until } => match replay_pet(store, pet, until.as_deref()) {
+            Ok(_) => {}
+            Err(e) => fail(e),
+        },
+        Commands::Backup {
+            command: BackupCommands::List,
+        } => match show_backups(store) {
+            Ok(_) => {}
+            Err(e) => fail(e),
+        },
+        Commands::Backup {
+            command: BackupCommands::Restore { id },
+        } => match restore_backup(store, &id, pet) {
+            Ok(_) => {}
+            Err(e) => fail(e),
+        },
+        Commands::Vacation {
+            command: VacationCommands::Start { sitter },
+        } => match start_vacation(store, pet, sitter) {
+            Ok(_) => {}
+            Err(e) => fail(e),
         },
-        Commands::Shell => match run_shell() {
+        Commands::Vacation {
+            command: VacationCommands::End,
+        } => match end_vacation(store, pet) {
             Ok(_) => {}
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
+            Err(e) => fail(e),
+        },
+        Commands::Export { format, output } => {
+            match export_pet(store, pet, format, output.as_deref()) {
+                Ok(_) => {}
+                Err(e) => fail(e),
             }
+        }
+        Commands::Import {
+            file,
+            format,
+            rename,
+            overwrite,
+        } => match import_pet(store, &file, format, rename.as_deref(), overwrite) {
+            Ok(message) => println!("{}", message),
+            Err(e) => fail(e),
+        },
+        Commands::Sync { dir } => match sync_pets(store, &dir) {
+            Ok(_) => {}
+            Err(e) => fail(e),
+        },
+        Commands::Git {
+            command: GitCommands::Init,
+        } => match init_git(&data_dir) {
+            Ok(_) => {}
+            Err(e) => fail(e),
+        },
+        Commands::Encrypt => match encrypt_saves(&data_dir, kind) {
+            Ok(_) => {}
+            Err(e) => fail(e),
+        },
+        Commands::Decrypt => match decrypt_saves(&data_dir) {
+            Ok(_) => {}
+            Err(e) => fail(e),
+        },
+        Commands::Doctor { repair } => match run_doctor(store, repair) {
+            Ok(_) => {}
+            Err(e) => fail(e),
         },
     }
 }
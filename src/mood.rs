@@ -84,6 +84,7 @@ mod tests {
             cleanliness: 80,
             potty_level: 0,
             last_updated: chrono::Utc::now(),
+            ..Default::default()
         };
 
         // When: calculating mood
@@ -107,6 +108,7 @@ mod tests {
             cleanliness: 80,
             potty_level: 0,
             last_updated: chrono::Utc::now(),
+            ..Default::default()
         };
 
         // When: calculating mood
@@ -130,6 +132,7 @@ mod tests {
             cleanliness: 80,
             potty_level: 0,
             last_updated: chrono::Utc::now(),
+            ..Default::default()
         };
 
         // When: calculating mood
@@ -153,6 +156,7 @@ mod tests {
             cleanliness: 80,
             potty_level: 0,
             last_updated: chrono::Utc::now(),
+            ..Default::default()
         };
 
         // When: calculating mood
@@ -176,6 +180,7 @@ mod tests {
             cleanliness: 80,
             potty_level: 0,
             last_updated: chrono::Utc::now(),
+            ..Default::default()
         };
 
         // When: calculating mood
@@ -199,6 +204,7 @@ mod tests {
             cleanliness: 80,
             potty_level: 85,
             last_updated: chrono::Utc::now(),
+            ..Default::default()
         };
 
         // When: calculating mood
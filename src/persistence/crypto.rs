@@ -0,0 +1,347 @@
+use super::write_atomic;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use blake2::Blake2sMac256;
+use blake2::digest::Mac;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Environment variable holding the passphrase of an encrypted data directory
+pub const PASSPHRASE_ENV: &str = "TERMIPET_PASSPHRASE";
+
+/// File in the data dir holding how the key is derived; while it exists, pet
+/// files, undo stacks, backups, the active pet, the journal and the history are
+/// written encrypted, and pet files are named by a keyed hash of the pet's name
+pub const KEY_FILE: &str = "encryption.json";
+
+/// First line of every encrypted file, so they are never mistaken for damaged JSON
+const MAGIC: &str = "termipet-encrypted v1\n";
+
+/// Start of every encrypted line in a JSON-lines file, which is sealed line by
+/// line so it can still be appended to
+const LINE_MAGIC: &str = "termipet-encrypted v1 ";
+
+/// Hex digits of the keyed hash that stands in for a pet's name in file names
+const HASHED_NAME_LEN: usize = 32;
+
+/// Encrypted into the key file so a wrong passphrase is caught before any pet is read
+const CHECK_PLAINTEXT: &[u8] = b"termipet";
+
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+
+/// The key file: Argon2id settings and salt, plus the check value
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    salt: String,
+    check: String,
+}
+
+/// Keys already derived in this process, by data dir, so Argon2 runs once per command
+fn unlocked_keys() -> &'static Mutex<HashMap<PathBuf, Key>> {
+    static KEYS: OnceLock<Mutex<HashMap<PathBuf, Key>>> = OnceLock::new();
+    KEYS.get_or_init(Mutex::default)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Returns true if the data directory's files are written encrypted
+pub fn is_encrypted(data_dir: &Path) -> bool {
+    data_dir.join(KEY_FILE).exists()
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], params: Params) -> io::Result<Key> {
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| io::Error::other(format!("couldn't derive the key ({})", e)))?;
+    Ok(key)
+}
+
+fn seal(key: &Key, plaintext: &[u8]) -> io::Result<String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(key)
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| io::Error::other("encryption failed"))?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    Ok(BASE64.encode(sealed))
+}
+
+/// Decrypts sealed data, or None if the key is wrong or the data was altered
+fn open(key: &Key, sealed: &str) -> Option<Vec<u8>> {
+    let sealed = BASE64.decode(sealed.trim()).ok()?;
+    if sealed.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(key)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .ok()
+}
+
+/// Starts encrypting the data directory with a key derived from the passphrase
+pub(crate) fn create_key_file(data_dir: &Path, passphrase: &str, params: Params) -> io::Result<()> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt, params.clone())?;
+
+    let key_file = KeyFile {
+        memory_kib: params.m_cost(),
+        iterations: params.t_cost(),
+        parallelism: params.p_cost(),
+        salt: BASE64.encode(salt),
+        check: seal(&key, CHECK_PLAINTEXT)?,
+    };
+    let json = serde_json::to_string_pretty(&key_file).map_err(io::Error::other)?;
+    fs::create_dir_all(data_dir)?;
+    write_atomic(&data_dir.join(KEY_FILE), json.as_bytes())?;
+
+    unlocked_keys()
+        .lock()
+        .expect("key cache poisoned")
+        .insert(data_dir.to_path_buf(), key);
+    Ok(())
+}
+
+/// Derives the data directory's key from the passphrase, failing clearly if it is wrong
+pub fn unlock(data_dir: &Path, passphrase: &str) -> io::Result<()> {
+    let path = data_dir.join(KEY_FILE);
+    let contents = fs::read_to_string(&path)?;
+    let key_file: KeyFile = serde_json::from_str(&contents)
+        .map_err(|e| invalid(format!("{} is damaged ({})", path.display(), e)))?;
+    let salt = BASE64
+        .decode(&key_file.salt)
+        .map_err(|e| invalid(format!("{} is damaged ({})", path.display(), e)))?;
+    let params = Params::new(
+        key_file.memory_kib,
+        key_file.iterations,
+        key_file.parallelism,
+        None,
+    )
+    .map_err(|e| invalid(format!("{} is damaged ({})", path.display(), e)))?;
+
+    let key = derive_key(passphrase, &salt, params)?;
+    if open(&key, &key_file.check).as_deref() != Some(CHECK_PLAINTEXT) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("Wrong passphrase for {}.", data_dir.display()),
+        ));
+    }
+
+    unlocked_keys()
+        .lock()
+        .expect("key cache poisoned")
+        .insert(data_dir.to_path_buf(), key);
+    Ok(())
+}
+
+/// Forgets the data directory's key, once its files are no longer encrypted
+pub(crate) fn forget_key(data_dir: &Path) {
+    unlocked_keys()
+        .lock()
+        .expect("key cache poisoned")
+        .remove(data_dir);
+}
+
+/// Returns the data directory's key, unlocking it with `TERMIPET_PASSPHRASE` the first time
+fn key_for(data_dir: &Path) -> io::Result<Key> {
+    if let Some(key) = unlocked_keys()
+        .lock()
+        .expect("key cache poisoned")
+        .get(data_dir)
+    {
+        return Ok(*key);
+    }
+
+    let passphrase = std::env::var(PASSPHRASE_ENV).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "{} is encrypted. Set {} to its passphrase.",
+                data_dir.display(),
+                PASSPHRASE_ENV
+            ),
+        )
+    })?;
+    unlock(data_dir, &passphrase)?;
+    key_for(data_dir)
+}
+
+/// Returns true if a file's contents were written encrypted
+pub(crate) fn is_sealed(contents: &str) -> bool {
+    contents.starts_with(MAGIC)
+}
+
+/// Reads a file that may be encrypted, decrypting it with the data directory's key
+/// A file that can't be decrypted is an error, never treated as damaged JSON
+pub(crate) fn read_sealed(data_dir: &Path, path: &Path) -> io::Result<String> {
+    let contents = fs::read_to_string(path)?;
+    let Some(sealed) = contents.strip_prefix(MAGIC) else {
+        return Ok(contents);
+    };
+    if !is_encrypted(data_dir) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "{} is encrypted, but {} has no {}.",
+                path.display(),
+                data_dir.display(),
+                KEY_FILE
+            ),
+        ));
+    }
+
+    let key = key_for(data_dir)?;
+    let plaintext = open(&key, sealed).ok_or_else(|| {
+        invalid(format!(
+            "Can't decrypt {}: it was encrypted with another passphrase or has been altered.",
+            path.display()
+        ))
+    })?;
+    String::from_utf8(plaintext).map_err(|e| invalid(format!("{}: {}", path.display(), e)))
+}
+
+/// Writes a file atomically, encrypted if the data directory is
+pub(crate) fn write_sealed(data_dir: &Path, path: &Path, contents: &str) -> io::Result<()> {
+    if !is_encrypted(data_dir) {
+        return write_atomic(path, contents.as_bytes());
+    }
+    let key = key_for(data_dir)?;
+    let sealed = format!("{}{}\n", MAGIC, seal(&key, contents.as_bytes())?);
+    write_atomic(path, sealed.as_bytes())
+}
+
+/// The file stem for a pet's slug: the slug itself, or a keyed hash of it if the
+/// data directory is encrypted, so file names don't give the pets' names away
+pub(crate) fn file_stem(data_dir: &Path, slug: &str) -> io::Result<String> {
+    if !is_encrypted(data_dir) {
+        return Ok(slug.to_string());
+    }
+    let mut mac = <Blake2sMac256 as KeyInit>::new_from_slice(&key_for(data_dir)?)
+        .map_err(|_| io::Error::other("couldn't hash the name"))?;
+    mac.update(slug.as_bytes());
+    let hash = mac.finalize().into_bytes();
+    let mut stem: String = hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+    stem.truncate(HASHED_NAME_LEN);
+    Ok(stem)
+}
+
+/// Encrypts one line of a JSON-lines file if the data directory is encrypted
+pub(crate) fn seal_line(data_dir: &Path, line: &str) -> io::Result<String> {
+    if !is_encrypted(data_dir) {
+        return Ok(line.to_string());
+    }
+    let key = key_for(data_dir)?;
+    Ok(format!("{}{}", LINE_MAGIC, seal(&key, line.as_bytes())?))
+}
+
+/// Decrypts one line of a JSON-lines file, or returns it as it is if it isn't encrypted
+/// A line that can't be decrypted, e.g. one cut short by a crash, is `InvalidData`
+pub(crate) fn open_line(data_dir: &Path, line: &str) -> io::Result<String> {
+    let Some(sealed) = line.strip_prefix(LINE_MAGIC) else {
+        return Ok(line.to_string());
+    };
+    let key = key_for(data_dir)?;
+    let plaintext =
+        open(&key, sealed).ok_or_else(|| invalid("the line can't be decrypted".to_string()))?;
+    String::from_utf8(plaintext).map_err(|e| invalid(e.to_string()))
+}
+
+/// Returns true if a line of a JSON-lines file was written encrypted
+pub(crate) fn is_sealed_line(line: &str) -> bool {
+    line.starts_with(LINE_MAGIC)
+}
+
+/// Appends a line to a JSON-lines file, encrypted if the data directory is
+pub(crate) fn append_sealed_line(data_dir: &Path, path: &Path, line: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut line = seal_line(data_dir, line)?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    file.sync_data()
+}
+
+/// Reads the non-empty lines of a JSON-lines file, decrypting the encrypted ones
+/// Each line is read on its own, so one that can't be decrypted doesn't stop the rest
+pub(crate) fn read_sealed_lines(
+    data_dir: &Path,
+    path: &Path,
+) -> io::Result<Vec<io::Result<String>>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    if contents.lines().any(is_sealed_line) {
+        // Fail as a whole if the key can't be had, rather than line by line
+        key_for(data_dir)?;
+    }
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| open_line(data_dir, line))
+        .collect())
+}
+
+#[cfg(test)]
+pub(crate) fn test_params() -> Params {
+    // Cheap settings so tests don't spend seconds in Argon2
+    Params::new(Params::MIN_M_COST, 1, 1, None).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sealed_files_round_trip_and_hide_their_contents() {
+        // Given: an encrypted data directory
+        let temp_dir = TempDir::new().unwrap();
+        create_key_file(temp_dir.path(), "hunter2", test_params()).unwrap();
+        let path = temp_dir.path().join("kylo.json");
+
+        // When: writing a file and reading it back
+        write_sealed(temp_dir.path(), &path, "{\"name\":\"Kylo\"}").unwrap();
+
+        // Then: it reads back as written but isn't readable on disk
+        assert!(!fs::read_to_string(&path).unwrap().contains("Kylo"));
+        assert_eq!(
+            read_sealed(temp_dir.path(), &path).unwrap(),
+            "{\"name\":\"Kylo\"}"
+        );
+    }
+
+    #[test]
+    fn test_wrong_passphrase_is_refused() {
+        // Given: a data directory encrypted with one passphrase
+        let temp_dir = TempDir::new().unwrap();
+        create_key_file(temp_dir.path(), "hunter2", test_params()).unwrap();
+
+        // When: unlocking it with another
+        let error = unlock(temp_dir.path(), "hunter3").unwrap_err();
+
+        // Then: the error says so
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(error.to_string().contains("Wrong passphrase"));
+        assert!(unlock(temp_dir.path(), "hunter2").is_ok());
+    }
+}
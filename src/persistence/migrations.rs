@@ -0,0 +1,329 @@
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+
+/// Schema version written by this build of termipet
+pub const CURRENT_SCHEMA_VERSION: u32 = 7;
+
+/// Key holding the schema version at the top level of every save file
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Upgrades a save file object by exactly one version, given the time it is
+/// being migrated at for fields that have to start from now
+type Migration = fn(&mut Map<String, Value>, DateTime<Utc>);
+
+/// Upgrade steps in order: `MIGRATIONS[n]` turns a version n file into version n + 1
+/// To change the save format, bump CURRENT_SCHEMA_VERSION, append a step here and
+/// add a fixture for the new version under tests/fixtures/
+const MIGRATIONS: [Migration; CURRENT_SCHEMA_VERSION as usize] = [
+    v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6, v6_to_v7,
+];
+
+/// Why a save file couldn't be migrated
+#[derive(Debug, Clone, PartialEq)]
+pub enum MigrationError {
+    /// The top-level JSON value isn't an object
+    NotAnObject,
+    /// The schema_version field isn't a non-negative integer
+    InvalidVersion,
+    /// The file was written by a newer termipet than this one
+    TooNew(u32),
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::NotAnObject => write!(f, "save file is not a JSON object"),
+            MigrationError::InvalidVersion => write!(f, "schema_version is not a valid number"),
+            MigrationError::TooNew(version) => write!(
+                f,
+                "schema v{} is newer than the supported v{}",
+                version, CURRENT_SCHEMA_VERSION
+            ),
+        }
+    }
+}
+
+/// v0 (slices 01–10) → v1: adds last_updated for decay tracking
+/// The time the file was last written is unknown, so it is taken as now and no
+/// decay is charged for the gap
+fn v0_to_v1(pet: &mut Map<String, Value>, now: DateTime<Utc>) {
+    pet.entry("last_updated")
+        .or_insert_with(|| Value::String(now.to_rfc3339()));
+}
+
+/// v1 → v2: introduces schema_version itself; no pet data changes
+fn v1_to_v2(_pet: &mut Map<String, Value>, _now: DateTime<Utc>) {}
+
+/// v2 → v3: adds decay_progress for continuous decay, starting a fresh interval
+fn v2_to_v3(pet: &mut Map<String, Value>, _now: DateTime<Utc>) {
+    pet.entry("decay_progress").or_insert(Value::from(0));
+}
+
+/// v3 → v4: adds rest_progress for energy regained while asleep
+fn v3_to_v4(pet: &mut Map<String, Value>, _now: DateTime<Utc>) {
+    pet.entry("rest_progress").or_insert(Value::from(0));
+}
+
+/// v4 → v5: adds difficulty and what neglect has done; existing pets are normal and healthy
+fn v4_to_v5(pet: &mut Map<String, Value>, _now: DateTime<Utc>) {
+    pet.entry("difficulty").or_insert(Value::from("normal"));
+    pet.entry("condition").or_insert(Value::from("healthy"));
+    pet.entry("neglect_seconds").or_insert(Value::from(0));
+}
+
+/// v5 → v6: adds vacations; a pet without one is at home, so existing pets need
+/// nothing added
+fn v5_to_v6(_pet: &mut Map<String, Value>, _now: DateTime<Utc>) {}
+
+/// v6 → v7: a pet named "Pet" is a real pet from v7 on; before it, a "Pet" of
+/// species "unknown" was the stand-in saved when nothing was adopted
+/// The stand-in is deleted from the roster rather than migrated (see
+/// `is_placeholder`), so pet data needs nothing added
+fn v6_to_v7(_pet: &mut Map<String, Value>, _now: DateTime<Utc>) {}
+
+/// Works out the version of a file, inferring it for files older than the field
+fn detect_version(pet: &Map<String, Value>) -> Result<u32, MigrationError> {
+    match pet.get(SCHEMA_VERSION_KEY) {
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or(MigrationError::InvalidVersion),
+        // Unversioned files are v1 once decay tracking was added, v0 before it
+        None if pet.contains_key("last_updated") => Ok(1),
+        None => Ok(0),
+    }
+}
+
+/// Upgrades a parsed save file in place to CURRENT_SCHEMA_VERSION, one step at a time
+/// Returns the version the file was at before migrating
+pub fn migrate(value: &mut Value, now: DateTime<Utc>) -> Result<u32, MigrationError> {
+    let pet = value.as_object_mut().ok_or(MigrationError::NotAnObject)?;
+    let original_version = detect_version(pet)?;
+
+    if original_version > CURRENT_SCHEMA_VERSION {
+        return Err(MigrationError::TooNew(original_version));
+    }
+
+    for (from_version, step) in MIGRATIONS
+        .iter()
+        .enumerate()
+        .skip(original_version as usize)
+    {
+        step(pet, now);
+        pet.insert(
+            SCHEMA_VERSION_KEY.to_string(),
+            Value::from(from_version + 1),
+        );
+    }
+
+    Ok(original_version)
+}
+
+/// Stamps a freshly serialized pet with the current schema version
+pub fn stamp_current_version(value: &mut Value) {
+    if let Some(pet) = value.as_object_mut() {
+        pet.insert(
+            SCHEMA_VERSION_KEY.to_string(),
+            Value::from(CURRENT_SCHEMA_VERSION),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::{Condition, Difficulty};
+    use crate::pet::Pet;
+    use chrono::{DateTime, Utc};
+
+    // One fixture per historic save format, as written by that version of termipet
+    const FIXTURE_V0: &str = include_str!("../../tests/fixtures/pet_v0.json");
+    const FIXTURE_V1: &str = include_str!("../../tests/fixtures/pet_v1.json");
+    const FIXTURE_V2: &str = include_str!("../../tests/fixtures/pet_v2.json");
+    const FIXTURE_V3: &str = include_str!("../../tests/fixtures/pet_v3.json");
+    const FIXTURE_V4: &str = include_str!("../../tests/fixtures/pet_v4.json");
+    const FIXTURE_V5: &str = include_str!("../../tests/fixtures/pet_v5.json");
+    const FIXTURE_V6: &str = include_str!("../../tests/fixtures/pet_v6.json");
+    const FIXTURE_V7: &str = include_str!("../../tests/fixtures/pet_v7.json");
+
+    // The time fixtures are migrated at
+    fn now() -> DateTime<Utc> {
+        "2026-01-05T12:00:00Z".parse().unwrap()
+    }
+
+    // Helper to run a fixture through the pipeline and deserialize the result
+    fn load_fixture(contents: &str) -> (u32, Value, Pet) {
+        let mut value: Value = serde_json::from_str(contents).unwrap();
+        let original_version = migrate(&mut value, now()).unwrap();
+        let pet: Pet = serde_json::from_value(value.clone()).unwrap();
+        (original_version, value, pet)
+    }
+
+    fn assert_fixture_stats(pet: &Pet) {
+        assert_eq!(pet.name, "Kylo");
+        assert_eq!(pet.species, "dog");
+        assert_eq!(pet.hunger, 85);
+        assert_eq!(pet.happiness, 90);
+        assert_eq!(pet.energy, 75);
+        assert_eq!(pet.xp, 50);
+        assert_eq!(pet.level, 2);
+        assert_eq!(pet.cleanliness, 80);
+        assert_eq!(pet.potty_level, 10);
+    }
+
+    #[test]
+    fn test_migrate_v0_fixture() {
+        // Given: a save file from before decay tracking existed
+        // When: migrating it
+        let (original_version, value, pet) = load_fixture(FIXTURE_V0);
+
+        // Then: it is detected as v0, upgraded to current and gets last_updated = now
+        assert_eq!(original_version, 0);
+        assert_eq!(value[SCHEMA_VERSION_KEY], CURRENT_SCHEMA_VERSION);
+        assert_fixture_stats(&pet);
+        assert_eq!(pet.last_updated, now());
+    }
+
+    #[test]
+    fn test_migrate_v1_fixture() {
+        // Given: an unversioned save file with last_updated
+        // When: migrating it
+        let (original_version, value, pet) = load_fixture(FIXTURE_V1);
+
+        // Then: it is detected as v1 and its timestamp is preserved
+        assert_eq!(original_version, 1);
+        assert_eq!(value[SCHEMA_VERSION_KEY], CURRENT_SCHEMA_VERSION);
+        assert_fixture_stats(&pet);
+        let expected: DateTime<Utc> = "2025-10-16T09:30:00Z".parse().unwrap();
+        assert_eq!(pet.last_updated, expected);
+    }
+
+    #[test]
+    fn test_migrate_v2_fixture() {
+        // Given: a save file from before decay carried over between loads
+        // When: migrating it
+        let (original_version, value, pet) = load_fixture(FIXTURE_V2);
+
+        // Then: it starts a fresh decay interval
+        assert_eq!(original_version, 2);
+        assert_eq!(value[SCHEMA_VERSION_KEY], CURRENT_SCHEMA_VERSION);
+        assert_fixture_stats(&pet);
+        assert_eq!(pet.decay_progress, 0);
+    }
+
+    #[test]
+    fn test_migrate_v3_fixture() {
+        // Given: a save file from before pets rested overnight
+        // When: migrating it
+        let (original_version, value, pet) = load_fixture(FIXTURE_V3);
+
+        // Then: its decay progress is kept and it starts with no rest banked
+        assert_eq!(original_version, 3);
+        assert_eq!(value[SCHEMA_VERSION_KEY], CURRENT_SCHEMA_VERSION);
+        assert_fixture_stats(&pet);
+        assert_eq!(pet.decay_progress, 5400);
+        assert_eq!(pet.rest_progress, 0);
+    }
+
+    #[test]
+    fn test_migrate_v4_fixture() {
+        // Given: a save file from before difficulty settings
+        // When: migrating it
+        let (original_version, value, pet) = load_fixture(FIXTURE_V4);
+
+        // Then: it becomes a healthy pet on normal difficulty
+        assert_eq!(original_version, 4);
+        assert_eq!(value[SCHEMA_VERSION_KEY], CURRENT_SCHEMA_VERSION);
+        assert_fixture_stats(&pet);
+        assert_eq!(pet.rest_progress, 1200);
+        assert_eq!(pet.difficulty, Difficulty::Normal);
+        assert_eq!(pet.condition, Condition::Healthy);
+    }
+
+    #[test]
+    fn test_migrate_v5_fixture() {
+        // Given: a save file from before vacations
+        // When: migrating it
+        let (original_version, value, pet) = load_fixture(FIXTURE_V5);
+
+        // Then: it keeps its difficulty and condition and is at home
+        assert_eq!(original_version, 5);
+        assert_eq!(value[SCHEMA_VERSION_KEY], CURRENT_SCHEMA_VERSION);
+        assert_fixture_stats(&pet);
+        assert_eq!(pet.difficulty, Difficulty::Hardcore);
+        assert_eq!(pet.condition, Condition::Ill);
+        assert_eq!(pet.neglect_seconds, 3600);
+        assert_eq!(pet.vacation, None);
+    }
+
+    #[test]
+    fn test_migrate_v6_fixture() {
+        // Given: a save file from before "Pet" was a name like any other
+        // When: migrating it
+        let (original_version, value, pet) = load_fixture(FIXTURE_V6);
+
+        // Then: only the version changes, and the vacation is kept
+        assert_eq!(original_version, 6);
+        assert_eq!(value[SCHEMA_VERSION_KEY], CURRENT_SCHEMA_VERSION);
+        assert_fixture_stats(&pet);
+        let vacation = pet.vacation.unwrap();
+        assert!(vacation.sitter);
+        assert_eq!(vacation.visit_progress, 7200);
+    }
+
+    #[test]
+    fn test_migrate_v7_fixture_is_unchanged() {
+        // Given: a save file already at v7
+        let original: Value = serde_json::from_str(FIXTURE_V7).unwrap();
+
+        // When: migrating it
+        let (original_version, value, pet) = load_fixture(FIXTURE_V7);
+
+        // Then: nothing changes
+        assert_eq!(original_version, 7);
+        assert_eq!(value, original);
+        assert_fixture_stats(&pet);
+    }
+
+    #[test]
+    fn test_migrate_rejects_newer_schema() {
+        // Given: a file written by a future termipet
+        let mut value = serde_json::json!({ "schema_version": CURRENT_SCHEMA_VERSION + 1 });
+
+        // When/Then: it is refused rather than misread
+        assert_eq!(
+            migrate(&mut value, now()),
+            Err(MigrationError::TooNew(CURRENT_SCHEMA_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn test_migrate_rejects_invalid_input() {
+        // Given: JSON that isn't a pet object, or has a bogus version
+        let mut not_object = serde_json::json!([1, 2, 3]);
+        let mut bad_version = serde_json::json!({ "schema_version": "two" });
+
+        // Then: both are reported as errors
+        assert_eq!(
+            migrate(&mut not_object, now()),
+            Err(MigrationError::NotAnObject)
+        );
+        assert_eq!(
+            migrate(&mut bad_version, now()),
+            Err(MigrationError::InvalidVersion)
+        );
+    }
+
+    #[test]
+    fn test_stamp_current_version() {
+        // Given: a serialized pet
+        let mut value =
+            serde_json::to_value(Pet::new("Kylo".to_string(), "dog".to_string())).unwrap();
+
+        // When: stamping it
+        stamp_current_version(&mut value);
+
+        // Then: it carries the current schema version
+        assert_eq!(value[SCHEMA_VERSION_KEY], CURRENT_SCHEMA_VERSION);
+    }
+}
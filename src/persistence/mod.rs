@@ -0,0 +1,1928 @@
+pub mod crypto;
+pub mod migrations;
+
+use crate::actions;
+use crate::away::DecaySummary;
+use crate::backup::BACKUP_DIR;
+use crate::clock::Clock;
+use crate::history::{self, HistoryFilter, create_history_table, insert_history, query_history};
+use crate::pet::Pet;
+use crate::recovery::recover_pet;
+use crate::rules::Rules;
+use crate::sleep;
+use crate::undo::{UndoStep, push_step};
+use chrono::{DateTime, Utc};
+use crypto::{read_sealed, write_sealed};
+use migrations::{CURRENT_SCHEMA_VERSION, MigrationError, migrate, stamp_current_version};
+use rusqlite::Connection;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration as StdDuration, Instant};
+
+#[cfg(test)]
+use chrono::Duration;
+
+/// Directory (inside the data dir) holding one JSON file per adopted pet
+const PETS_DIR: &str = "pets";
+
+/// Directory (inside the data dir) holding each pet's undo stack
+const UNDO_DIR: &str = "undo";
+
+/// File recording the name of the pet that commands act on by default
+const ACTIVE_PET_FILE: &str = "active_pet";
+
+/// SQLite database (inside the data dir) holding the JSON store's action history
+pub(crate) const HISTORY_FILE: &str = "history.db";
+
+/// JSON-lines file (inside the data dir) holding the action history instead of
+/// `HISTORY_FILE` while the data dir is encrypted
+pub(crate) const HISTORY_LOG_FILE: &str = "history.jsonl";
+
+/// Append-only event journal (inside the data dir), one JSON object per line
+pub(crate) const JOURNAL_FILE: &str = "journal.jsonl";
+
+/// Single-pet save file used before the roster existed
+const LEGACY_PET_FILE: &str = "pet.json";
+
+/// Suffix (followed by a timestamp) given to damaged files moved out of the way
+const QUARANTINE_MARKER: &str = ".corrupt-";
+
+/// Lock file guarding the data directory against concurrent termipet processes
+const LOCK_FILE: &str = ".lock";
+
+/// How long to wait for another process to release the lock before giving up
+const LOCK_TIMEOUT: StdDuration = StdDuration::from_secs(5);
+
+/// How often to retry while waiting for the lock
+const LOCK_RETRY_INTERVAL: StdDuration = StdDuration::from_millis(50);
+
+/// Environment variable that overrides where pet data is stored
+pub const DATA_DIR_ENV: &str = "TERMIPET_HOME";
+
+/// Data directory name used under the home directory (and historically the only location)
+const HOME_DATA_DIR: &str = ".termipet";
+
+/// Picks the data directory, in order of preference:
+/// 1. the --data-dir flag
+/// 2. the TERMIPET_HOME environment variable
+/// 3. an existing ~/.termipet, so current users keep their pets
+/// 4. the XDG data directory (Linux only), e.g. ~/.local/share/termipet
+/// 5. ~/.termipet
+fn choose_data_dir(
+    flag: Option<&Path>,
+    env: Option<PathBuf>,
+    home: Option<PathBuf>,
+    xdg_data: Option<PathBuf>,
+) -> io::Result<PathBuf> {
+    if let Some(dir) = flag {
+        return Ok(dir.to_path_buf());
+    }
+    if let Some(dir) = env {
+        return Ok(dir);
+    }
+
+    let home_data_dir = home.map(|home| home.join(HOME_DATA_DIR));
+    if let Some(dir) = home_data_dir.as_ref().filter(|dir| dir.exists()) {
+        return Ok(dir.clone());
+    }
+    if let Some(xdg_data) = xdg_data {
+        return Ok(xdg_data.join("termipet"));
+    }
+    home_data_dir
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not find home directory"))
+}
+
+/// Returns the path to the termipet data directory
+/// `flag` is the value of --data-dir, which takes precedence over everything else
+pub fn resolve_data_dir(flag: Option<&Path>) -> io::Result<PathBuf> {
+    let env = std::env::var_os(DATA_DIR_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from);
+    let xdg_data = if cfg!(target_os = "linux") {
+        dirs::data_dir()
+    } else {
+        None
+    };
+    choose_data_dir(flag, env, dirs::home_dir(), xdg_data)
+}
+
+/// Converts a pet name into the file stem used in the roster
+/// Names are matched case-insensitively, so "Kylo" and "kylo" are the same pet
+pub fn pet_slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if (c == ' ' || c == '-' || c == '_') && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+/// Returns the roster file path for a pet with the given name
+/// The file is named after the pet's slug, or a keyed hash of it while the data
+/// dir is encrypted
+pub fn get_pet_file_path(data_dir: &Path, name: &str) -> io::Result<PathBuf> {
+    let slug = pet_slug(name);
+    if slug.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("'{}' is not a valid pet name", name),
+        ));
+    }
+    let stem = crypto::file_stem(data_dir, &slug)?;
+    Ok(data_dir.join(PETS_DIR).join(format!("{}.json", stem)))
+}
+
+/// Why the contents of a save file couldn't be turned into a pet
+#[derive(Debug)]
+pub(crate) enum PetFileError {
+    /// Not valid JSON, or not a valid pet once migrated
+    Corrupt(String),
+    /// Written by a newer termipet whose schema this build doesn't know
+    TooNew(u32),
+}
+
+/// Parses a save file, upgrading older schema versions through the migration pipeline
+/// Returns the pet and the schema version the file was written with
+pub(crate) fn parse_pet(contents: &str, now: DateTime<Utc>) -> Result<(Pet, u32), PetFileError> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(contents).map_err(|e| PetFileError::Corrupt(e.to_string()))?;
+    let version = migrate(&mut value, now).map_err(|e| match e {
+        MigrationError::TooNew(version) => PetFileError::TooNew(version),
+        other => PetFileError::Corrupt(other.to_string()),
+    })?;
+    let pet = serde_json::from_value(value).map_err(|e| PetFileError::Corrupt(e.to_string()))?;
+    Ok((pet, version))
+}
+
+/// Serializes a pet as a save file stamped with the current schema version
+pub(crate) fn serialize_pet(pet: &Pet) -> io::Result<String> {
+    let mut value =
+        serde_json::to_value(pet).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    stamp_current_version(&mut value);
+    serde_json::to_string_pretty(&value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Error for a save file this build is too old to read
+/// Such files are left untouched rather than quarantined
+pub(crate) fn too_new_error(path: &Path, version: u32) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "{} was saved by a newer termipet (schema v{}, this version understands up to v{}). Please upgrade termipet.",
+            path.display(),
+            version,
+            CURRENT_SCHEMA_VERSION
+        ),
+    )
+}
+
+/// Brings a data directory written by an older termipet up to date: moves the
+/// single-pet save file into the roster and drops the placeholder pet
+fn migrate_data_dir(data_dir: &Path, clock: &dyn Clock) -> io::Result<()> {
+    migrate_legacy_pet(data_dir, clock)?;
+    drop_placeholder_pet(data_dir, clock)
+}
+
+/// Returns true for the stand-in pet that termipet saved before schema v7 when
+/// there was nothing to load, rather than for a pet someone adopted
+fn is_placeholder(pet: &Pet, version: u32) -> bool {
+    version < 7 && pet.name == "Pet" && pet.species == "unknown"
+}
+
+/// Removes the placeholder pet from the roster, if an older termipet put it there
+fn drop_placeholder_pet(data_dir: &Path, clock: &dyn Clock) -> io::Result<()> {
+    let pet_path = get_pet_file_path(data_dir, "Pet")?;
+    if !pet_path.exists() {
+        return Ok(());
+    }
+    // Files that can't be read are left for quarantine or doctor to deal with
+    if let Ok(contents) = read_sealed(data_dir, &pet_path)
+        && let Ok((pet, version)) = parse_pet(&contents, clock.now())
+        && is_placeholder(&pet, version)
+    {
+        delete_pet(data_dir, &pet.name, clock)?;
+    }
+    Ok(())
+}
+
+/// Moves a save file from the single-pet layout into the roster
+/// The migrated pet becomes the active pet if none is set yet; the placeholder
+/// pet is deleted instead
+fn migrate_legacy_pet(data_dir: &Path, clock: &dyn Clock) -> io::Result<()> {
+    let legacy_path = data_dir.join(LEGACY_PET_FILE);
+    if !legacy_path.exists() {
+        return Ok(());
+    }
+
+    let contents = read_sealed(data_dir, &legacy_path)?;
+    let pet = match parse_pet(&contents, clock.now()) {
+        Ok((pet, version)) if is_placeholder(&pet, version) => {
+            return fs::remove_file(&legacy_path);
+        }
+        Ok((pet, _)) => pet,
+        Err(PetFileError::TooNew(version)) => return Err(too_new_error(&legacy_path, version)),
+        Err(PetFileError::Corrupt(_)) => {
+            // The old layout has no name to fall back on
+            if let Some(pet) =
+                quarantine_and_recover(data_dir, &legacy_path, &contents, None, clock)?
+                && read_active_pet(data_dir)?.is_none()
+            {
+                write_active_pet(data_dir, &pet.name)?;
+            }
+            return Ok(());
+        }
+    };
+
+    let pet_path = get_pet_file_path(data_dir, &pet.name)?;
+    fs::create_dir_all(data_dir.join(PETS_DIR))?;
+    if pet_path.exists() {
+        // The roster already has this pet, the legacy copy is stale
+        fs::remove_file(&legacy_path)?;
+    } else if let Err(e) = fs::rename(&legacy_path, &pet_path) {
+        // Another process may have migrated the file first
+        if e.kind() != io::ErrorKind::NotFound {
+            return Err(e);
+        }
+    }
+
+    if read_active_pet(data_dir)?.is_none() {
+        write_active_pet(data_dir, &pet.name)?;
+    }
+    Ok(())
+}
+
+/// Reads the name of the active pet, if one has been chosen
+fn read_active_pet(data_dir: &Path) -> io::Result<Option<String>> {
+    match read_sealed(data_dir, &data_dir.join(ACTIVE_PET_FILE)) {
+        Ok(contents) => {
+            let name = contents.trim();
+            Ok((!name.is_empty()).then(|| name.to_string()))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn write_active_pet(data_dir: &Path, name: &str) -> io::Result<()> {
+    fs::create_dir_all(data_dir)?;
+    write_sealed(data_dir, &data_dir.join(ACTIVE_PET_FILE), name)
+}
+
+/// Writes a file so that readers see either the old or the new contents, never a mix
+/// The data goes to a temp file in the same directory which is synced and then
+/// renamed over the target
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid file path"))?;
+    let tmp_path = path.with_file_name(format!(
+        ".{}.tmp-{}",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = (|| {
+        let mut tmp_file = File::create(&tmp_path)?;
+        tmp_file.write_all(contents)?;
+        tmp_file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        // Don't leave half-written temp files behind
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+fn undo_file_path(data_dir: &Path, name: &str) -> io::Result<PathBuf> {
+    let pet_path = get_pet_file_path(data_dir, name)?;
+    let file_name = pet_path.file_name().expect("pet paths end in a file name");
+    Ok(data_dir.join(UNDO_DIR).join(file_name))
+}
+
+/// Reads a pet's undo stack, oldest step first
+/// An unreadable stack only costs the ability to undo, so it is treated as empty
+pub(crate) fn read_undo_steps(data_dir: &Path, name: &str) -> io::Result<Vec<UndoStep>> {
+    let path = undo_file_path(data_dir, name)?;
+    let contents = match read_sealed(data_dir, &path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    match serde_json::from_str(&contents) {
+        Ok(steps) => Ok(steps),
+        Err(e) => {
+            eprintln!(
+                "⚠️  Ignoring unreadable undo history {} ({})",
+                path.display(),
+                e
+            );
+            Ok(Vec::new())
+        }
+    }
+}
+
+fn write_undo_steps(data_dir: &Path, name: &str, steps: &[UndoStep]) -> io::Result<()> {
+    let path = undo_file_path(data_dir, name)?;
+    fs::create_dir_all(data_dir.join(UNDO_DIR))?;
+    let json = serde_json::to_string_pretty(steps).map_err(io::Error::other)?;
+    write_sealed(data_dir, &path, &json)
+}
+
+/// Adds a step to the pet's undo stack, keeping only the most recent ones
+pub(crate) fn push_undo_step(data_dir: &Path, step: &UndoStep) -> io::Result<()> {
+    let mut steps = read_undo_steps(data_dir, &step.pet.name)?;
+    push_step(&mut steps, step.clone());
+    write_undo_steps(data_dir, &step.pet.name, &steps)
+}
+
+/// Removes and returns the most recent step of the pet's undo stack
+pub(crate) fn pop_undo_step(data_dir: &Path, name: &str) -> io::Result<Option<UndoStep>> {
+    let mut steps = read_undo_steps(data_dir, name)?;
+    let step = steps.pop();
+    if step.is_some() {
+        write_undo_steps(data_dir, name, &steps)?;
+    }
+    Ok(step)
+}
+
+/// Exclusive advisory lock on the data directory
+/// Held across a command's load → mutate → save cycle; released when dropped
+#[derive(Debug)]
+pub struct DataDirLock {
+    _file: File,
+}
+
+fn lock_data_dir_with_timeout(data_dir: &Path, timeout: StdDuration) -> io::Result<DataDirLock> {
+    fs::create_dir_all(data_dir)?;
+    let lock_path = data_dir.join(LOCK_FILE);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)?;
+
+    let started = Instant::now();
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(DataDirLock { _file: file }),
+            Err(TryLockError::WouldBlock) if started.elapsed() < timeout => {
+                thread::sleep(LOCK_RETRY_INTERVAL);
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    format!(
+                        "Another termipet process has been holding {} for more than {}s. Try again in a moment.",
+                        lock_path.display(),
+                        timeout.as_secs()
+                    ),
+                ));
+            }
+            Err(TryLockError::Error(e)) => return Err(e),
+        }
+    }
+}
+
+/// Takes the data directory lock, waiting briefly if another process holds it
+/// Fails with a clear error if the lock isn't released in time
+pub fn lock_data_dir(data_dir: &Path) -> io::Result<DataDirLock> {
+    lock_data_dir_with_timeout(data_dir, LOCK_TIMEOUT)
+}
+
+/// Returns the name of the pet commands act on when no --pet is given
+pub fn active_pet_name(data_dir: &Path, clock: &dyn Clock) -> io::Result<Option<String>> {
+    migrate_data_dir(data_dir, clock)?;
+    read_active_pet(data_dir)
+}
+
+/// Returns true if a pet with the given name is in the roster
+pub fn pet_exists(data_dir: &Path, name: &str, clock: &dyn Clock) -> io::Result<bool> {
+    migrate_data_dir(data_dir, clock)?;
+    Ok(get_pet_file_path(data_dir, name)?.exists())
+}
+
+/// Makes the named pet the default target for commands
+pub fn set_active_pet(data_dir: &Path, name: &str, clock: &dyn Clock) -> io::Result<()> {
+    migrate_data_dir(data_dir, clock)?;
+    let pet_path = get_pet_file_path(data_dir, name)?;
+    if !pet_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No pet named '{}'", name),
+        ));
+    }
+
+    // Record the name as the pet spells it, not as it was typed
+    let stored_name = read_sealed(data_dir, &pet_path)
+        .ok()
+        .and_then(|contents| parse_pet(&contents, clock.now()).ok())
+        .map(|(pet, _)| pet.name);
+    write_active_pet(data_dir, stored_name.as_deref().unwrap_or(name))
+}
+
+/// Applies stat decay for the time between last_updated and the clock's time,
+/// and rest for the part of it the pet spent asleep
+/// Returns what the decay and rest did, including the seconds of decay applied
+/// and how many of them were slept through
+#[cfg(test)]
+pub(crate) fn apply_decay(pet: &mut Pet, clock: &dyn Clock, rules: &Rules) -> DecaySummary {
+    decay_until(pet, clock, rules, clock.now())
+}
+
+/// Applies decay up to a time the caller has already read from the clock,
+/// so the decay and whatever the caller stamps with that time stay in order
+pub(crate) fn decay_until(
+    pet: &mut Pet,
+    clock: &dyn Clock,
+    rules: &Rules,
+    now: DateTime<Utc>,
+) -> DecaySummary {
+    let before = pet.clone();
+    let seconds = now
+        .signed_duration_since(pet.last_updated)
+        .num_seconds()
+        .max(0) as u64;
+    let asleep = sleep::seconds_asleep(clock, pet.last_updated, now);
+    if seconds > 0 {
+        actions::decay(pet, seconds, rules);
+        actions::rest(pet, asleep);
+        pet.last_updated = now;
+    }
+    DecaySummary::new(&before, pet, seconds, asleep, rules)
+}
+
+/// Saves a pet to its JSON file in the roster
+/// Updates the last_updated timestamp to the clock's time before saving
+pub fn save_pet(data_dir: &Path, pet: &Pet, clock: &dyn Clock) -> io::Result<()> {
+    let pet_path = get_pet_file_path(data_dir, &pet.name)?;
+    fs::create_dir_all(data_dir.join(PETS_DIR))?;
+
+    // Create a mutable copy to update last_updated
+    let mut pet_to_save = pet.clone();
+    pet_to_save.last_updated = clock.now();
+
+    let json = serialize_pet(&pet_to_save)?;
+    write_sealed(data_dir, &pet_path, &json)?;
+
+    // The first pet saved into an empty roster becomes the active one
+    if read_active_pet(data_dir)?.is_none() {
+        write_active_pet(data_dir, &pet.name)?;
+    }
+    Ok(())
+}
+
+/// Moves a damaged file aside to `<file>.corrupt-<timestamp>` so it is never lost
+/// Returns the path of the quarantined copy
+fn quarantine_file(path: &Path, now: DateTime<Utc>) -> io::Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid file path"))?;
+    let quarantine_path = path.with_file_name(format!(
+        "{}{}{}",
+        file_name.to_string_lossy(),
+        QUARANTINE_MARKER,
+        now.format("%Y%m%dT%H%M%S%.3fZ")
+    ));
+    fs::rename(path, &quarantine_path)?;
+    Ok(quarantine_path)
+}
+
+/// Quarantines a damaged pet file and saves whatever could be recovered from it
+/// in its place. Returns the recovered pet, or None if nothing was recoverable.
+fn quarantine_and_recover(
+    data_dir: &Path,
+    pet_path: &Path,
+    contents: &str,
+    fallback_name: Option<&str>,
+    clock: &dyn Clock,
+) -> io::Result<Option<Pet>> {
+    let quarantine_path = quarantine_file(pet_path, clock.now())?;
+    let Some(recovered) = recover_pet(contents, fallback_name) else {
+        eprintln!(
+            "⚠️  A damaged save file was moved to {} (nothing could be recovered).",
+            quarantine_path.display()
+        );
+        return Ok(None);
+    };
+
+    eprintln!(
+        "⚠️  {}'s save file was damaged and moved to {}. Recovered {} field(s); the rest were reset to defaults.",
+        recovered.pet.name,
+        quarantine_path.display(),
+        recovered.recovered_fields.len()
+    );
+
+    // Keep the recovered timestamp so decay continues from where it left off
+    let json = serialize_pet(&recovered.pet)?;
+    fs::create_dir_all(data_dir.join(PETS_DIR))?;
+    write_sealed(
+        data_dir,
+        &get_pet_file_path(data_dir, &recovered.pet.name)?,
+        &json,
+    )?;
+    Ok(Some(recovered.pet))
+}
+
+/// Reads a single pet file
+/// A file that isn't a valid pet is quarantined and rebuilt from the fields that
+/// can still be read; its file stem stands in for the name if that is lost
+/// Returns None if nothing could be recovered
+fn read_pet_file(data_dir: &Path, pet_path: &Path, clock: &dyn Clock) -> io::Result<Option<Pet>> {
+    let contents = read_sealed(data_dir, pet_path)?;
+    match parse_pet(&contents, clock.now()) {
+        Ok((pet, _)) => Ok(Some(pet)),
+        Err(PetFileError::TooNew(version)) => Err(too_new_error(pet_path, version)),
+        Err(PetFileError::Corrupt(_)) => {
+            let file_stem = pet_path.file_stem().map(|stem| stem.to_string_lossy());
+            quarantine_and_recover(data_dir, pet_path, &contents, file_stem.as_deref(), clock)
+        }
+    }
+}
+
+/// Loads a pet from the roster
+/// `name` selects a specific pet; `None` loads the active pet
+/// Returns None if nothing has been adopted or the named pet doesn't exist
+/// Applies decay by the built-in rules for the time between last_updated and the clock's time
+#[cfg(test)]
+pub(crate) fn load_pet(
+    data_dir: &Path,
+    name: Option<&str>,
+    clock: &dyn Clock,
+) -> io::Result<Option<Pet>> {
+    let mut pet = load_stored_pet(data_dir, name, clock)?;
+    if let Some(pet) = &mut pet {
+        apply_decay(pet, clock, &Rules::default());
+    }
+    Ok(pet)
+}
+
+/// Loads a pet exactly as it was saved, without applying decay
+pub(crate) fn load_stored_pet(
+    data_dir: &Path,
+    name: Option<&str>,
+    clock: &dyn Clock,
+) -> io::Result<Option<Pet>> {
+    migrate_data_dir(data_dir, clock)?;
+
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => match read_active_pet(data_dir)? {
+            Some(name) => name,
+            // Nothing adopted yet
+            None => return Ok(None),
+        },
+    };
+
+    let pet_path = get_pet_file_path(data_dir, &name)?;
+    if !pet_path.exists() {
+        return Ok(None);
+    }
+
+    read_pet_file(data_dir, &pet_path, clock)
+}
+
+/// Loads every pet in the roster, sorted by name, applying decay by the built-in rules
+#[cfg(test)]
+pub(crate) fn list_pets(data_dir: &Path, clock: &dyn Clock) -> io::Result<Vec<Pet>> {
+    let mut pets = list_stored_pets(data_dir, clock)?;
+    for pet in &mut pets {
+        apply_decay(pet, clock, &Rules::default());
+    }
+    Ok(pets)
+}
+
+/// Loads every pet in the roster exactly as saved, sorted by name
+pub(crate) fn list_stored_pets(data_dir: &Path, clock: &dyn Clock) -> io::Result<Vec<Pet>> {
+    migrate_data_dir(data_dir, clock)?;
+
+    let pets_dir = data_dir.join(PETS_DIR);
+    if !pets_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut pets = Vec::new();
+    for entry in fs::read_dir(pets_dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json")
+            && let Some(pet) = read_pet_file(data_dir, &path, clock)?
+        {
+            pets.push(pet);
+        }
+    }
+    pets.sort_by_key(|pet| pet_slug(&pet.name));
+    Ok(pets)
+}
+
+/// Removes a pet from the roster
+/// If it was the active pet, the next pet in the roster becomes active
+pub fn delete_pet(data_dir: &Path, name: &str, clock: &dyn Clock) -> io::Result<()> {
+    fs::remove_file(get_pet_file_path(data_dir, name)?)?;
+    match fs::remove_file(undo_file_path(data_dir, name)?) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+
+    // Hand the active slot to another pet so commands keep working
+    let was_active =
+        read_active_pet(data_dir)?.is_some_and(|active| pet_slug(&active) == pet_slug(name));
+    if was_active {
+        match list_stored_pets(data_dir, clock)?.first() {
+            Some(next) => write_active_pet(data_dir, &next.name)?,
+            None => fs::remove_file(data_dir.join(ACTIVE_PET_FILE))?,
+        }
+    }
+    Ok(())
+}
+
+/// Every file in the data dir that is encrypted as a whole: the roster, undo
+/// stacks, backups, the active pet and an old single-pet save file
+fn sealed_data_files(data_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let is_json = |name: &str| name.ends_with(".json");
+    let mut files = files_matching(&data_dir.join(PETS_DIR), is_json)?;
+    files.extend(files_matching(&data_dir.join(UNDO_DIR), is_json)?);
+    files.extend(files_matching(&data_dir.join(BACKUP_DIR), is_json)?);
+    for file in [LEGACY_PET_FILE, ACTIVE_PET_FILE] {
+        let path = data_dir.join(file);
+        if path.exists() {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Returns true if the file is named after a pet: a roster file or an undo stack
+fn is_named_after_pet(data_dir: &Path, path: &Path) -> bool {
+    path.parent()
+        .is_some_and(|dir| dir == data_dir.join(PETS_DIR) || dir == data_dir.join(UNDO_DIR))
+}
+
+/// The name of the pet a decrypted roster file or undo stack belongs to
+fn pet_named_in(data_dir: &Path, path: &Path, contents: &str) -> Option<String> {
+    if path.parent()? == data_dir.join(PETS_DIR) {
+        // Only the name is needed, and every schema version keeps it at the top level
+        let pet: serde_json::Value = serde_json::from_str(contents).ok()?;
+        pet.get("name")?.as_str().map(str::to_string)
+    } else {
+        let steps: Vec<UndoStep> = serde_json::from_str(contents).ok()?;
+        steps.last().map(|step| step.pet.name.clone())
+    }
+}
+
+/// Rewrites a JSON-lines file with every line encrypted, or every line decrypted
+/// A line that already couldn't be decrypted is dropped
+/// Returns 1 if the file had to change, so it counts towards the files done
+fn reseal_lines(data_dir: &Path, path: &Path, seal: bool) -> io::Result<usize> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    if contents
+        .lines()
+        .all(|line| line.trim().is_empty() || crypto::is_sealed_line(line) == seal)
+    {
+        return Ok(0);
+    }
+
+    let mut rewritten = String::new();
+    for line in crypto::read_sealed_lines(data_dir, path)?
+        .into_iter()
+        .flatten()
+    {
+        let line = if seal {
+            crypto::seal_line(data_dir, &line)?
+        } else {
+            line
+        };
+        rewritten.push_str(&line);
+        rewritten.push('\n');
+    }
+    write_atomic(path, rewritten.as_bytes())?;
+    Ok(1)
+}
+
+/// Moves the action history from its SQLite database into an encrypted
+/// JSON-lines file, leaving out entries an interrupted run already moved
+/// Returns 1 if there was a database to move
+fn seal_history(data_dir: &Path) -> io::Result<usize> {
+    let db_path = data_dir.join(HISTORY_FILE);
+    if !db_path.exists() {
+        return Ok(0);
+    }
+    let log_path = data_dir.join(HISTORY_LOG_FILE);
+    let conn = Connection::open(&db_path).map_err(io::Error::other)?;
+    create_history_table(&conn)?;
+    let moved = history::read_file(data_dir, &log_path, &HistoryFilter::default())?;
+
+    let mut contents = match fs::read_to_string(&log_path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    for entry in query_history(&conn, &HistoryFilter::default())? {
+        if !moved.contains(&entry) {
+            let line = serde_json::to_string(&entry).map_err(io::Error::other)?;
+            contents.push_str(&crypto::seal_line(data_dir, &line)?);
+            contents.push('\n');
+        }
+    }
+    drop(conn);
+    write_atomic(&log_path, contents.as_bytes())?;
+    fs::remove_file(&db_path)?;
+    Ok(1)
+}
+
+/// Moves the action history from its encrypted JSON-lines file back into the
+/// SQLite database, leaving out entries an interrupted run already moved
+/// Returns 1 if there was a history file to move
+fn open_history(data_dir: &Path) -> io::Result<usize> {
+    let log_path = data_dir.join(HISTORY_LOG_FILE);
+    if !log_path.exists() {
+        return Ok(0);
+    }
+    let mut conn = Connection::open(data_dir.join(HISTORY_FILE)).map_err(io::Error::other)?;
+    create_history_table(&conn)?;
+    let moved = query_history(&conn, &HistoryFilter::default())?;
+
+    let transaction = conn.transaction().map_err(io::Error::other)?;
+    for entry in history::read_file(data_dir, &log_path, &HistoryFilter::default())? {
+        if !moved.contains(&entry) {
+            insert_history(&transaction, &entry)?;
+        }
+    }
+    transaction.commit().map_err(io::Error::other)?;
+    fs::remove_file(&log_path)?;
+    Ok(1)
+}
+
+/// Encrypts the pet files, undo stacks, backups, active pet, journal and history
+/// with a key derived from the passphrase, and renames the files named after pets
+/// Files already encrypted are left alone, so an interrupted run can simply be repeated
+/// Returns how many files were encrypted; callers should hold the data dir lock
+pub fn encrypt_data_dir(data_dir: &Path, passphrase: &str) -> io::Result<usize> {
+    encrypt_data_dir_with(data_dir, passphrase, argon2::Params::default())
+}
+
+fn encrypt_data_dir_with(
+    data_dir: &Path,
+    passphrase: &str,
+    params: argon2::Params,
+) -> io::Result<usize> {
+    if crypto::is_encrypted(data_dir) {
+        crypto::unlock(data_dir, passphrase)?;
+    } else {
+        crypto::create_key_file(data_dir, passphrase, params)?;
+    }
+
+    let mut encrypted = 0;
+    for path in sealed_data_files(data_dir)? {
+        let contents = fs::read_to_string(&path)?;
+        if crypto::is_sealed(&contents) {
+            continue;
+        }
+        // Plain roster and undo files are named after the pet's slug
+        let mut target = path.clone();
+        if is_named_after_pet(data_dir, &path)
+            && let Some(slug) = path.file_stem()
+        {
+            let stem = crypto::file_stem(data_dir, &slug.to_string_lossy())?;
+            target.set_file_name(format!("{}.json", stem));
+        }
+        write_sealed(data_dir, &target, &contents)?;
+        if target != path {
+            fs::remove_file(&path)?;
+        }
+        encrypted += 1;
+    }
+    encrypted += reseal_lines(data_dir, &data_dir.join(JOURNAL_FILE), true)?;
+    encrypted += seal_history(data_dir)?;
+    Ok(encrypted)
+}
+
+/// Decrypts every encrypted file, gives files named after pets their plain names
+/// back and turns encryption off
+/// Returns how many files were decrypted; callers should hold the data dir lock
+pub fn decrypt_data_dir(data_dir: &Path, passphrase: &str) -> io::Result<usize> {
+    if !crypto::is_encrypted(data_dir) {
+        return Ok(0);
+    }
+    crypto::unlock(data_dir, passphrase)?;
+
+    let mut decrypted = 0;
+    for path in sealed_data_files(data_dir)? {
+        if !crypto::is_sealed(&fs::read_to_string(&path)?) {
+            continue;
+        }
+        let contents = read_sealed(data_dir, &path)?;
+        let mut target = path.clone();
+        if is_named_after_pet(data_dir, &path)
+            && let Some(name) = pet_named_in(data_dir, &path, &contents)
+        {
+            target.set_file_name(format!("{}.json", pet_slug(&name)));
+        }
+        write_atomic(&target, contents.as_bytes())?;
+        if target != path {
+            fs::remove_file(&path)?;
+        }
+        decrypted += 1;
+    }
+    decrypted += reseal_lines(data_dir, &data_dir.join(JOURNAL_FILE), false)?;
+    decrypted += open_history(data_dir)?;
+    // Only once every file is readable without it
+    fs::remove_file(data_dir.join(crypto::KEY_FILE))?;
+    crypto::forget_key(data_dir);
+    Ok(decrypted)
+}
+
+/// A problem found in the data directory by `diagnose_data_dir`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnosis {
+    /// What is wrong
+    pub problem: String,
+    /// What a repair does about it, or None if it is only reported
+    pub repair: Option<String>,
+    /// Whether the repair was carried out
+    pub repaired: bool,
+}
+
+impl Diagnosis {
+    pub(crate) fn report(problem: String) -> Self {
+        Self {
+            problem,
+            repair: None,
+            repaired: false,
+        }
+    }
+
+    pub(crate) fn fixable(problem: String, repair: String) -> Self {
+        Self {
+            problem,
+            repair: Some(repair),
+            repaired: false,
+        }
+    }
+}
+
+/// Returns the files in `dir` whose names satisfy `matches`, sorted by name
+fn files_matching(dir: &Path, matches: impl Fn(&str) -> bool) -> io::Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file()
+            && path
+                .file_name()
+                .is_some_and(|name| matches(&name.to_string_lossy()))
+        {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Checks the data directory for damaged or inconsistent files
+/// With `repair`, fixes what it can; callers should hold the data dir lock
+pub fn diagnose_data_dir(
+    data_dir: &Path,
+    repair: bool,
+    clock: &dyn Clock,
+) -> io::Result<Vec<Diagnosis>> {
+    let mut findings = Vec::new();
+    let pets_dir = data_dir.join(PETS_DIR);
+
+    // Temp files left behind by a save that was interrupted before its rename
+    for dir in [data_dir, pets_dir.as_path()] {
+        for path in files_matching(dir, |name| name.starts_with('.') && name.contains(".tmp-"))? {
+            let mut finding = Diagnosis::fixable(
+                format!(
+                    "Leftover temp file from an interrupted save: {}",
+                    path.display()
+                ),
+                "delete it".to_string(),
+            );
+            if repair {
+                fs::remove_file(&path)?;
+                finding.repaired = true;
+            }
+            findings.push(finding);
+        }
+    }
+
+    // Single-pet save file that hasn't been moved into the roster yet
+    if data_dir.join(LEGACY_PET_FILE).exists() {
+        let mut finding = Diagnosis::fixable(
+            format!(
+                "Old single-pet save file {} is not in the roster",
+                LEGACY_PET_FILE
+            ),
+            "move it into the roster".to_string(),
+        );
+        if repair {
+            migrate_legacy_pet(data_dir, clock)?;
+            finding.repaired = true;
+        }
+        findings.push(finding);
+    }
+
+    // Each pet file must parse and live under the file name its pet's name maps to
+    for path in files_matching(&pets_dir, |name| name.ends_with(".json"))? {
+        let file_name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let contents = match read_sealed(data_dir, &path) {
+            Ok(contents) => contents,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::PermissionDenied | io::ErrorKind::InvalidData
+                ) =>
+            {
+                // Encrypted files that can't be decrypted are never quarantined
+                findings.push(Diagnosis::report(format!(
+                    "{} can't be read ({})",
+                    file_name, e
+                )));
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        match parse_pet(&contents, clock.now()) {
+            Err(PetFileError::TooNew(version)) => {
+                findings.push(Diagnosis::report(format!(
+                    "{} was saved by a newer termipet (schema v{}); upgrade termipet to read it",
+                    file_name, version
+                )));
+            }
+            Err(PetFileError::Corrupt(e)) => {
+                let mut finding = Diagnosis::fixable(
+                    format!("{} is damaged ({})", file_name, e),
+                    "quarantine it and recover what can be read".to_string(),
+                );
+                if repair {
+                    let file_stem = path.file_stem().map(|stem| stem.to_string_lossy());
+                    quarantine_and_recover(
+                        data_dir,
+                        &path,
+                        &contents,
+                        file_stem.as_deref(),
+                        clock,
+                    )?;
+                    finding.repaired = true;
+                }
+                findings.push(finding);
+            }
+            Ok((pet, version)) => {
+                if version < CURRENT_SCHEMA_VERSION {
+                    let mut finding = Diagnosis::fixable(
+                        format!("{} uses save schema v{}", file_name, version),
+                        format!("upgrade it to v{}", CURRENT_SCHEMA_VERSION),
+                    );
+                    if repair {
+                        // Rewrite as-is so last_updated (and so decay) is untouched
+                        write_sealed(data_dir, &path, &serialize_pet(&pet)?)?;
+                        finding.repaired = true;
+                    }
+                    findings.push(finding);
+                }
+
+                let expected = get_pet_file_path(data_dir, &pet.name)?;
+                if expected != path {
+                    let mut finding = Diagnosis::fixable(
+                        format!("{} holds a pet named '{}'", file_name, pet.name),
+                        "rename the file to match".to_string(),
+                    );
+                    if repair && !expected.exists() {
+                        fs::rename(&path, &expected)?;
+                        finding.repaired = true;
+                    }
+                    findings.push(finding);
+                }
+            }
+        }
+    }
+
+    // The active pet pointer must name a pet that exists
+    let active = match read_active_pet(data_dir) {
+        Ok(active) => active,
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::PermissionDenied | io::ErrorKind::InvalidData
+            ) =>
+        {
+            findings.push(Diagnosis::report(format!(
+                "{} can't be read ({})",
+                ACTIVE_PET_FILE, e
+            )));
+            None
+        }
+        Err(e) => return Err(e),
+    };
+    if let Some(active) = active
+        && !get_pet_file_path(data_dir, &active).is_ok_and(|path| path.exists())
+    {
+        let mut finding = Diagnosis::fixable(
+            format!("Active pet '{}' is not in the roster", active),
+            "make the first pet in the roster active".to_string(),
+        );
+        if repair {
+            match list_stored_pets(data_dir, clock)?.first() {
+                Some(pet) => write_active_pet(data_dir, &pet.name)?,
+                None => fs::remove_file(data_dir.join(ACTIVE_PET_FILE))?,
+            }
+            finding.repaired = true;
+        }
+        findings.push(finding);
+    }
+
+    // Quarantined files are kept for manual inspection, never deleted
+    for dir in [data_dir, pets_dir.as_path()] {
+        for path in files_matching(dir, |name| name.contains(QUARANTINE_MARKER))? {
+            findings.push(Diagnosis::report(format!(
+                "Quarantined damaged file kept for inspection: {}",
+                path.display()
+            )));
+        }
+    }
+
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SystemClock};
+    use crate::history::HistoryEntry;
+    use crate::journal::{self, Event, JournalEntry};
+    use chrono::{FixedOffset, TimeZone};
+    use std::fs;
+    use tempfile::TempDir;
+
+    // Helper to create a temporary test directory
+    fn setup_test_env() -> TempDir {
+        TempDir::new().unwrap()
+    }
+
+    // Helper to override the data directory for testing
+    fn get_test_pet_path(temp_dir: &TempDir) -> PathBuf {
+        temp_dir.path().join("pet.json")
+    }
+
+    #[test]
+    fn test_save_creates_file() {
+        // Given: a new pet named "Kylo" of species "dog"
+        let temp_dir = setup_test_env();
+        let pet = Pet::new("Kylo".to_string(), "dog".to_string());
+
+        // When: I call save_pet() (using manual path for test)
+        let pet_path = get_test_pet_path(&temp_dir);
+        let json = serde_json::to_string_pretty(&pet).unwrap();
+        fs::write(&pet_path, json).unwrap();
+
+        // Then: a file should be created with valid JSON
+        assert!(pet_path.exists());
+        let contents = fs::read_to_string(&pet_path).unwrap();
+        let loaded_pet: Pet = serde_json::from_str(&contents).unwrap();
+        assert_eq!(loaded_pet.name, "Kylo");
+        assert_eq!(loaded_pet.species, "dog");
+    }
+
+    #[test]
+    fn test_load_returns_pet_from_file() {
+        // Given: a file already exists with valid pet JSON
+        let temp_dir = setup_test_env();
+        let pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        let pet_path = get_test_pet_path(&temp_dir);
+        let json = serde_json::to_string_pretty(&pet).unwrap();
+        fs::write(&pet_path, json).unwrap();
+
+        // When: I load the pet
+        let contents = fs::read_to_string(&pet_path).unwrap();
+        let loaded_pet: Pet = serde_json::from_str(&contents).unwrap();
+
+        // Then: the pet data should load correctly
+        assert_eq!(loaded_pet.name, "Kylo");
+        assert_eq!(loaded_pet.species, "dog");
+        assert_eq!(loaded_pet.hunger, 80);
+        assert_eq!(loaded_pet.happiness, 80);
+    }
+
+    #[test]
+    fn test_load_handles_missing_file() {
+        // Given: no file exists
+        let temp_dir = setup_test_env();
+        let pet_path = get_test_pet_path(&temp_dir);
+
+        // When: attempting to load
+        // Then: there is no pet
+        assert!(!pet_path.exists());
+        assert_eq!(load_pet(temp_dir.path(), None, &SystemClock).unwrap(), None);
+    }
+
+    #[test]
+    fn test_load_handles_invalid_json() {
+        // Given: a corrupted or invalid JSON file
+        let temp_dir = setup_test_env();
+        let pet_path = get_test_pet_path(&temp_dir);
+        fs::write(&pet_path, "{ invalid json }").unwrap();
+
+        // When: attempting to parse
+        let contents = fs::read_to_string(&pet_path).unwrap();
+        let result = serde_json::from_str::<Pet>(&contents);
+
+        // Then: should fail to parse
+        assert!(result.is_err());
+
+        // And: should return default pet (simulated recovery)
+        let default_pet = Pet::default();
+        assert_eq!(default_pet.hunger, 80);
+    }
+
+    #[test]
+    fn test_apply_decay_one_interval_passed() {
+        // Given: a pet with full stats, last updated 8 hours ago
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.hunger = 100;
+        pet.happiness = 100;
+        pet.cleanliness = 100;
+        pet.potty_level = 0;
+        pet.last_updated = Utc::now() - Duration::hours(8);
+
+        // When: apply_decay is called
+        apply_decay(&mut pet, &SystemClock, &Rules::default());
+
+        // Then: stats should decay by one interval
+        assert_eq!(pet.hunger, 97); // 100 - 3
+        assert_eq!(pet.happiness, 98); // 100 - 2
+        assert_eq!(pet.cleanliness, 98); // 100 - 2
+        assert_eq!(pet.potty_level, 2); // 0 + 2
+    }
+
+    #[test]
+    fn test_apply_decay_multiple_intervals() {
+        // Given: a pet with full stats, last updated 24 hours ago (3 intervals)
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.hunger = 100;
+        pet.happiness = 100;
+        pet.cleanliness = 100;
+        pet.potty_level = 0;
+        pet.last_updated = Utc::now() - Duration::hours(24);
+
+        // When: apply_decay is called
+        apply_decay(&mut pet, &SystemClock, &Rules::default());
+
+        // Then: stats should decay by three intervals
+        assert_eq!(pet.hunger, 91); // 100 - (3 * 3)
+        assert_eq!(pet.happiness, 94); // 100 - (3 * 2)
+        assert_eq!(pet.cleanliness, 94); // 100 - (3 * 2)
+        assert_eq!(pet.potty_level, 6); // 0 + (3 * 2)
+    }
+
+    #[test]
+    fn test_apply_decay_caps_stats() {
+        // Given: a pet with low hunger and high potty_level, 24 hours ago
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.hunger = 5;
+        pet.happiness = 3;
+        pet.cleanliness = 1;
+        pet.potty_level = 97;
+        pet.last_updated = Utc::now() - Duration::hours(24);
+
+        // When: apply_decay is called
+        apply_decay(&mut pet, &SystemClock, &Rules::default());
+
+        // Then: stats should be capped at boundaries
+        assert_eq!(pet.hunger, 0); // 5 - 9 = capped at 0
+        assert_eq!(pet.happiness, 0); // 3 - 6 = capped at 0
+        assert_eq!(pet.cleanliness, 0); // 1 - 6 = capped at 0
+        assert_eq!(pet.potty_level, 100); // 97 + 6 = capped at 100
+    }
+
+    #[test]
+    fn test_apply_decay_counts_part_of_an_interval() {
+        // Given: a pet last updated 4 hours ago (half an interval)
+        let now = Utc::now();
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.hunger = 100;
+        pet.happiness = 100;
+        pet.cleanliness = 100;
+        pet.potty_level = 0;
+        pet.last_updated = now - Duration::hours(4);
+
+        // When: apply_decay is called
+        let seconds = apply_decay(&mut pet, &FixedClock::new(now), &Rules::default()).seconds;
+
+        // Then: the whole points due so far are taken and the rest is carried
+        assert_eq!(seconds, 4 * 3600);
+        assert_eq!(pet.hunger, 99); // 1.5 points due
+        assert_eq!(pet.happiness, 99);
+        assert_eq!(pet.cleanliness, 99);
+        assert_eq!(pet.potty_level, 1);
+        assert_eq!(pet.decay_progress, 4 * 3600);
+    }
+
+    #[test]
+    fn test_apply_decay_restores_energy_overnight() {
+        // Given: a tired pet last seen at 20:00 UTC
+        let evening = Utc.with_ymd_and_hms(2025, 10, 16, 20, 0, 0).unwrap();
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.energy = 40;
+        pet.last_updated = evening;
+
+        // When: it is loaded at 08:00 the next morning
+        let clock = FixedClock::new(evening + Duration::hours(12))
+            .with_offset(FixedOffset::east_opt(0).unwrap());
+        let summary = apply_decay(&mut pet, &clock, &Rules::default());
+
+        // Then: the nine hours asleep gave back five points of energy
+        assert_eq!(summary.seconds, 12 * 3600);
+        assert_eq!(summary.asleep, 9 * 3600);
+        assert_eq!(pet.energy, 45);
+        assert_eq!(pet.rest_progress, 0);
+    }
+
+    #[test]
+    fn test_apply_decay_updates_last_updated() {
+        // Given: a pet with old last_updated timestamp
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        let old_timestamp = Utc::now() - Duration::hours(16);
+        pet.last_updated = old_timestamp;
+
+        // When: apply_decay is called
+        let before_call = Utc::now();
+        apply_decay(&mut pet, &SystemClock, &Rules::default());
+        let after_call = Utc::now();
+
+        // Then: last_updated should be updated to current time
+        assert!(pet.last_updated >= before_call);
+        assert!(pet.last_updated <= after_call);
+        assert!(pet.last_updated > old_timestamp);
+    }
+
+    #[test]
+    fn test_load_handles_missing_last_updated_field() {
+        // Given: an old JSON file without last_updated field (backward compatibility)
+        let temp_dir = setup_test_env();
+        let pet_path = get_test_pet_path(&temp_dir);
+
+        // Create JSON manually without last_updated field (simulating old format)
+        let old_json = r#"{
+            "name": "Kylo",
+            "species": "dog",
+            "hunger": 85,
+            "happiness": 90,
+            "energy": 75,
+            "xp": 50,
+            "level": 2,
+            "cleanliness": 80,
+            "potty_level": 10
+        }"#;
+        fs::write(&pet_path, old_json).unwrap();
+
+        // When: loading the pet through the migration pipeline
+        let contents = fs::read_to_string(&pet_path).unwrap();
+        let (pet, version) = parse_pet(&contents, Utc::now()).unwrap();
+
+        // Then: pet should load successfully as a v0 file with default last_updated
+        assert_eq!(version, 0);
+        assert_eq!(pet.name, "Kylo");
+        assert_eq!(pet.species, "dog");
+        assert_eq!(pet.hunger, 85);
+        assert_eq!(pet.happiness, 90);
+        assert_eq!(pet.energy, 75);
+        assert_eq!(pet.xp, 50);
+        assert_eq!(pet.level, 2);
+        assert_eq!(pet.cleanliness, 80);
+        assert_eq!(pet.potty_level, 10);
+
+        // last_updated should be set to current time (within reasonable bounds)
+        let now = Utc::now();
+        let time_diff = (now - pet.last_updated).num_seconds().abs();
+        assert!(time_diff < 5, "last_updated should be close to now");
+    }
+
+    #[test]
+    fn test_save_writes_current_schema_version() {
+        // Given: a pet
+        let temp_dir = setup_test_env();
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Kylo".to_string(), "dog".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+
+        // When: reading the raw save file
+        let contents = fs::read_to_string(temp_dir.path().join("pets").join("kylo.json")).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&contents).unwrap();
+
+        // Then: it is stamped with the current schema version
+        assert_eq!(value["schema_version"], CURRENT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_load_refuses_newer_schema_without_quarantine() {
+        // Given: a pet file written by a future termipet
+        let temp_dir = setup_test_env();
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Kylo".to_string(), "dog".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+        let pet_path = temp_dir.path().join("pets").join("kylo.json");
+        let future = format!(
+            r#"{{"schema_version": {}, "name": "Kylo"}}"#,
+            CURRENT_SCHEMA_VERSION + 1
+        );
+        fs::write(&pet_path, &future).unwrap();
+
+        // When: loading it
+        let result = load_pet(temp_dir.path(), None, &SystemClock);
+
+        // Then: loading fails with an upgrade hint and the file is left alone
+        assert!(result.unwrap_err().to_string().contains("newer termipet"));
+        assert_eq!(fs::read_to_string(&pet_path).unwrap(), future);
+    }
+
+    #[test]
+    fn test_doctor_upgrades_old_schema_files() {
+        // Given: a roster file in the v1 format
+        let temp_dir = setup_test_env();
+        let pets_dir = temp_dir.path().join("pets");
+        fs::create_dir_all(&pets_dir).unwrap();
+        fs::write(
+            pets_dir.join("kylo.json"),
+            include_str!("../../tests/fixtures/pet_v1.json"),
+        )
+        .unwrap();
+
+        // When: repairing the data dir
+        let findings = diagnose_data_dir(temp_dir.path(), true, &SystemClock).unwrap();
+
+        // Then: the file is rewritten at the current version with its timestamp intact
+        assert!(
+            findings
+                .iter()
+                .any(|finding| finding.problem.contains("schema v1") && finding.repaired)
+        );
+        let (pet, version) = parse_pet(
+            &fs::read_to_string(pets_dir.join("kylo.json")).unwrap(),
+            Utc::now(),
+        )
+        .unwrap();
+        assert_eq!(version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(pet.last_updated.to_rfc3339(), "2025-10-16T09:30:00+00:00");
+    }
+
+    #[test]
+    fn test_data_dir_flag_wins() {
+        // Given: every source of a data dir is available
+        let home = setup_test_env();
+        fs::create_dir_all(home.path().join(".termipet")).unwrap();
+
+        // When: choosing with a --data-dir flag
+        let dir = choose_data_dir(
+            Some(Path::new("/flag")),
+            Some(PathBuf::from("/env")),
+            Some(home.path().to_path_buf()),
+            Some(PathBuf::from("/xdg")),
+        )
+        .unwrap();
+
+        // Then: the flag is used
+        assert_eq!(dir, PathBuf::from("/flag"));
+    }
+
+    #[test]
+    fn test_data_dir_env_beats_home() {
+        // Given: TERMIPET_HOME is set and ~/.termipet exists
+        let home = setup_test_env();
+        fs::create_dir_all(home.path().join(".termipet")).unwrap();
+
+        // When: choosing without a flag
+        let dir = choose_data_dir(
+            None,
+            Some(PathBuf::from("/env")),
+            Some(home.path().to_path_buf()),
+            None,
+        )
+        .unwrap();
+
+        // Then: the environment variable is used
+        assert_eq!(dir, PathBuf::from("/env"));
+    }
+
+    #[test]
+    fn test_data_dir_keeps_existing_home_dir() {
+        // Given: an existing ~/.termipet and an XDG data dir
+        let home = setup_test_env();
+        fs::create_dir_all(home.path().join(".termipet")).unwrap();
+
+        // When: choosing without flag or env
+        let dir = choose_data_dir(
+            None,
+            None,
+            Some(home.path().to_path_buf()),
+            Some(PathBuf::from("/xdg")),
+        )
+        .unwrap();
+
+        // Then: the existing directory is kept so no pets are stranded
+        assert_eq!(dir, home.path().join(".termipet"));
+    }
+
+    #[test]
+    fn test_data_dir_prefers_xdg_for_new_installs() {
+        // Given: no ~/.termipet yet
+        let home = setup_test_env();
+
+        // When/Then: the XDG data dir is used when available, ~/.termipet otherwise
+        let xdg = choose_data_dir(
+            None,
+            None,
+            Some(home.path().to_path_buf()),
+            Some(PathBuf::from("/xdg")),
+        )
+        .unwrap();
+        assert_eq!(xdg, PathBuf::from("/xdg/termipet"));
+
+        let fallback = choose_data_dir(None, None, Some(home.path().to_path_buf()), None).unwrap();
+        assert_eq!(fallback, home.path().join(".termipet"));
+    }
+
+    #[test]
+    fn test_pet_slug_normalises_names() {
+        // Given: names with mixed case, spaces and punctuation
+        // Then: they map to stable lowercase file stems
+        assert_eq!(pet_slug("Kylo"), "kylo");
+        assert_eq!(pet_slug("  Sir Barks-a-lot! "), "sir-barks-a-lot");
+        assert_eq!(pet_slug("???"), "");
+    }
+
+    #[test]
+    fn test_save_adds_pets_to_roster() {
+        // Given: an empty data directory
+        let temp_dir = setup_test_env();
+
+        // When: saving two different pets
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Kylo".to_string(), "dog".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Luna".to_string(), "cat".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+
+        // Then: both pets are kept and the first one is active
+        let pets = list_pets(temp_dir.path(), &SystemClock).unwrap();
+        let names: Vec<&str> = pets.iter().map(|pet| pet.name.as_str()).collect();
+        assert_eq!(names, vec!["Kylo", "Luna"]);
+        assert_eq!(
+            read_active_pet(temp_dir.path()).unwrap().as_deref(),
+            Some("Kylo")
+        );
+    }
+
+    #[test]
+    fn test_load_selects_named_or_active_pet() {
+        // Given: a roster with Kylo (active) and Luna
+        let temp_dir = setup_test_env();
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Kylo".to_string(), "dog".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Luna".to_string(), "cat".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+
+        // When/Then: no name loads the active pet, a name loads that pet
+        assert_eq!(
+            load_pet(temp_dir.path(), None, &SystemClock)
+                .unwrap()
+                .unwrap()
+                .name,
+            "Kylo"
+        );
+        assert_eq!(
+            load_pet(temp_dir.path(), Some("luna"), &SystemClock)
+                .unwrap()
+                .unwrap()
+                .name,
+            "Luna"
+        );
+
+        // And: switching changes what None resolves to
+        set_active_pet(temp_dir.path(), "Luna", &SystemClock).unwrap();
+        assert_eq!(
+            load_pet(temp_dir.path(), None, &SystemClock)
+                .unwrap()
+                .unwrap()
+                .name,
+            "Luna"
+        );
+    }
+
+    #[test]
+    fn test_set_active_pet_rejects_unknown_name() {
+        // Given: a roster containing only Kylo
+        let temp_dir = setup_test_env();
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Kylo".to_string(), "dog".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+
+        // When: switching to a pet that does not exist
+        let result = set_active_pet(temp_dir.path(), "Ghost", &SystemClock);
+
+        // Then: it fails and Kylo stays active
+        assert!(result.is_err());
+        assert_eq!(
+            read_active_pet(temp_dir.path()).unwrap().as_deref(),
+            Some("Kylo")
+        );
+    }
+
+    #[test]
+    fn test_delete_active_pet_hands_over_active_slot() {
+        // Given: a roster with Kylo (active) and Luna
+        let temp_dir = setup_test_env();
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Kylo".to_string(), "dog".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Luna".to_string(), "cat".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+
+        // When: deleting the active pet
+        delete_pet(temp_dir.path(), "Kylo", &SystemClock).unwrap();
+
+        // Then: Luna becomes active; deleting her empties the roster
+        assert_eq!(
+            read_active_pet(temp_dir.path()).unwrap().as_deref(),
+            Some("Luna")
+        );
+        delete_pet(temp_dir.path(), "Luna", &SystemClock).unwrap();
+        assert_eq!(read_active_pet(temp_dir.path()).unwrap(), None);
+        assert_eq!(load_pet(temp_dir.path(), None, &SystemClock).unwrap(), None);
+    }
+
+    #[test]
+    fn test_save_leaves_no_temp_files() {
+        // Given: an existing pet file
+        let temp_dir = setup_test_env();
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        save_pet(temp_dir.path(), &pet, &SystemClock).unwrap();
+
+        // When: saving over it
+        pet.hunger = 42;
+        save_pet(temp_dir.path(), &pet, &SystemClock).unwrap();
+
+        // Then: the file holds the new data and only the pet file remains
+        assert_eq!(
+            load_pet(temp_dir.path(), None, &SystemClock)
+                .unwrap()
+                .unwrap()
+                .hunger,
+            42
+        );
+        let entries: Vec<_> = fs::read_dir(temp_dir.path().join("pets"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec!["kylo.json"]);
+    }
+
+    #[test]
+    fn test_lock_times_out_while_held() {
+        // Given: the data directory lock is held
+        let temp_dir = setup_test_env();
+        let _held = lock_data_dir_with_timeout(temp_dir.path(), StdDuration::ZERO).unwrap();
+
+        // When: another caller tries to take it
+        let result = lock_data_dir_with_timeout(temp_dir.path(), StdDuration::from_millis(100));
+
+        // Then: it fails with a clear error instead of waiting forever
+        let err = result.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        assert!(err.to_string().contains("Another termipet process"));
+    }
+
+    #[test]
+    fn test_lock_is_released_on_drop() {
+        // Given: a lock that has been taken and dropped
+        let temp_dir = setup_test_env();
+        drop(lock_data_dir_with_timeout(temp_dir.path(), StdDuration::ZERO).unwrap());
+
+        // When: taking it again
+        let result = lock_data_dir_with_timeout(temp_dir.path(), StdDuration::ZERO);
+
+        // Then: it succeeds immediately
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_load_quarantines_and_recovers_corrupt_file() {
+        // Given: Kylo's save file was truncated mid-write
+        let temp_dir = setup_test_env();
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Kylo".to_string(), "dog".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+        let pet_path = temp_dir.path().join("pets").join("kylo.json");
+        fs::write(
+            &pet_path,
+            r#"{"name": "Kylo", "species": "dog", "hunger": 33, "happ"#,
+        )
+        .unwrap();
+
+        // When: loading the pet
+        let clock = FixedClock::new("2025-10-17T09:30:00Z".parse().unwrap());
+        let pet = load_pet(temp_dir.path(), None, &clock).unwrap().unwrap();
+
+        // Then: readable fields survive and the damaged file is kept aside,
+        // stamped with the clock's time
+        assert_eq!(pet.name, "Kylo");
+        assert_eq!(pet.hunger, 33);
+        assert_eq!(pet.happiness, 80);
+        let quarantined = files_matching(&temp_dir.path().join("pets"), |name| {
+            name.starts_with("kylo.json.corrupt-")
+        })
+        .unwrap();
+        assert_eq!(quarantined.len(), 1);
+        assert!(quarantined[0].ends_with("kylo.json.corrupt-20251017T093000.000Z"));
+        assert!(
+            fs::read_to_string(&quarantined[0])
+                .unwrap()
+                .contains("\"hunger\": 33")
+        );
+    }
+
+    #[test]
+    fn test_doctor_reports_without_changing_anything() {
+        // Given: a damaged pet file and a leftover temp file
+        let temp_dir = setup_test_env();
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Kylo".to_string(), "dog".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+        let pets_dir = temp_dir.path().join("pets");
+        fs::write(pets_dir.join("kylo.json"), "{ broken").unwrap();
+        fs::write(pets_dir.join(".kylo.json.tmp-42"), "{").unwrap();
+
+        // When: diagnosing without repair
+        let findings = diagnose_data_dir(temp_dir.path(), false, &SystemClock).unwrap();
+
+        // Then: both problems are reported and the files are untouched
+        assert_eq!(findings.len(), 2);
+        assert!(findings.iter().all(|finding| !finding.repaired));
+        assert_eq!(
+            fs::read_to_string(pets_dir.join("kylo.json")).unwrap(),
+            "{ broken"
+        );
+        assert!(pets_dir.join(".kylo.json.tmp-42").exists());
+    }
+
+    #[test]
+    fn test_doctor_repairs_data_dir() {
+        // Given: a damaged pet file, a temp file and an active pointer to a missing pet
+        let temp_dir = setup_test_env();
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Kylo".to_string(), "dog".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+        let pets_dir = temp_dir.path().join("pets");
+        fs::write(
+            pets_dir.join("kylo.json"),
+            r#"{"name": "Kylo", "hunger": 12"#,
+        )
+        .unwrap();
+        fs::write(pets_dir.join(".kylo.json.tmp-42"), "{").unwrap();
+        write_active_pet(temp_dir.path(), "Ghost").unwrap();
+
+        // When: diagnosing with repair
+        let findings = diagnose_data_dir(temp_dir.path(), true, &SystemClock).unwrap();
+
+        // Then: every fixable problem is repaired
+        assert!(
+            findings
+                .iter()
+                .filter(|finding| finding.repair.is_some())
+                .all(|finding| finding.repaired)
+        );
+        assert!(!pets_dir.join(".kylo.json.tmp-42").exists());
+        assert_eq!(
+            read_active_pet(temp_dir.path()).unwrap().as_deref(),
+            Some("Kylo")
+        );
+        assert_eq!(
+            load_pet(temp_dir.path(), None, &SystemClock)
+                .unwrap()
+                .unwrap()
+                .hunger,
+            12
+        );
+
+        // And: a second run only reports the quarantined copy
+        let findings = diagnose_data_dir(temp_dir.path(), false, &SystemClock).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].repair.is_none());
+    }
+
+    #[test]
+    fn test_legacy_pet_file_migrates_into_roster() {
+        // Given: a single-pet pet.json from before the roster existed
+        let temp_dir = setup_test_env();
+        let pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        let json = serde_json::to_string_pretty(&pet).unwrap();
+        fs::write(temp_dir.path().join("pet.json"), json).unwrap();
+
+        // When: loading the active pet
+        let loaded = load_pet(temp_dir.path(), None, &SystemClock)
+            .unwrap()
+            .unwrap();
+
+        // Then: the pet is moved into the roster and made active
+        assert_eq!(loaded.name, "Kylo");
+        assert!(!temp_dir.path().join("pet.json").exists());
+        assert!(temp_dir.path().join("pets").join("kylo.json").exists());
+        assert_eq!(
+            read_active_pet(temp_dir.path()).unwrap().as_deref(),
+            Some("Kylo")
+        );
+    }
+
+    #[test]
+    fn test_placeholder_pet_is_dropped_but_a_pet_named_pet_is_kept() {
+        // Given: the stand-in "Pet" an older termipet saved in the roster and made active
+        let temp_dir = setup_test_env();
+        let mut placeholder = serde_json::to_value(Pet::default()).unwrap();
+        placeholder[migrations::SCHEMA_VERSION_KEY] = serde_json::json!(6);
+        fs::create_dir_all(temp_dir.path().join("pets")).unwrap();
+        fs::write(
+            get_pet_file_path(temp_dir.path(), "Pet").unwrap(),
+            placeholder.to_string(),
+        )
+        .unwrap();
+        write_active_pet(temp_dir.path(), "Pet").unwrap();
+
+        // When: loading the active pet
+        // Then: there is none, and the stand-in is gone
+        assert_eq!(load_pet(temp_dir.path(), None, &SystemClock).unwrap(), None);
+        assert!(!pet_exists(temp_dir.path(), "Pet", &SystemClock).unwrap());
+        assert_eq!(read_active_pet(temp_dir.path()).unwrap(), None);
+
+        // And: a pet someone names "Pet" now is a pet like any other
+        save_pet(temp_dir.path(), &Pet::default(), &SystemClock).unwrap();
+        write_active_pet(temp_dir.path(), "Pet").unwrap();
+        let pet = load_pet(temp_dir.path(), None, &SystemClock)
+            .unwrap()
+            .unwrap();
+        assert_eq!(pet.name, "Pet");
+    }
+
+    #[test]
+    fn test_encrypt_and_decrypt_data_dir() {
+        // Given: a pet with an undo stack, an action history and a journal
+        let temp_dir = setup_test_env();
+        let pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        save_pet(temp_dir.path(), &pet, &SystemClock).unwrap();
+        set_active_pet(temp_dir.path(), "Kylo", &SystemClock).unwrap();
+        push_undo_step(
+            temp_dir.path(),
+            &UndoStep::new(crate::history::Action::Feed, &pet, Utc::now()),
+        )
+        .unwrap();
+        let fed = HistoryEntry::new(
+            crate::history::Action::Feed,
+            &pet,
+            &pet,
+            Utc.with_ymd_and_hms(2025, 10, 17, 9, 0, 0).unwrap(),
+        );
+        let conn = Connection::open(temp_dir.path().join(HISTORY_FILE)).unwrap();
+        create_history_table(&conn).unwrap();
+        insert_history(&conn, &fed).unwrap();
+        drop(conn);
+        let fed_event = JournalEntry::new("Kylo", Event::Fed, Utc::now());
+        let journal_path = temp_dir.path().join(JOURNAL_FILE);
+        journal::append_to_file(temp_dir.path(), &journal_path, &fed_event).unwrap();
+
+        // When: encrypting the data directory
+        let encrypted =
+            encrypt_data_dir_with(temp_dir.path(), "hunter2", crypto::test_params()).unwrap();
+
+        // Then: no file shows the pet's name, in its contents or its name, but
+        // the pet still loads with its history and journal
+        assert_eq!(encrypted, 5);
+        for path in all_files(temp_dir.path()) {
+            assert!(!path.to_string_lossy().to_lowercase().contains("kylo"));
+            assert!(!fs::read_to_string(&path).unwrap().contains("Kylo"));
+        }
+        assert!(!temp_dir.path().join(HISTORY_FILE).exists());
+        let log_path = temp_dir.path().join(HISTORY_LOG_FILE);
+        let history = history::read_file(temp_dir.path(), &log_path, &HistoryFilter::default());
+        assert_eq!(history.unwrap(), vec![fed.clone()]);
+        let journal = journal::read_file(temp_dir.path(), &journal_path).unwrap();
+        assert_eq!(journal, vec![fed_event.clone()]);
+        assert_eq!(
+            load_pet(temp_dir.path(), None, &SystemClock)
+                .unwrap()
+                .unwrap()
+                .name,
+            "Kylo"
+        );
+        assert_eq!(read_undo_steps(temp_dir.path(), "Kylo").unwrap().len(), 1);
+
+        // And: decrypting puts plain files back under their old names and turns
+        // encryption off
+        assert_eq!(decrypt_data_dir(temp_dir.path(), "hunter2").unwrap(), 5);
+        assert!(!crypto::is_encrypted(temp_dir.path()));
+        let pet_path = temp_dir.path().join(PETS_DIR).join("kylo.json");
+        assert!(fs::read_to_string(pet_path).unwrap().contains("Kylo"));
+        assert!(temp_dir.path().join(UNDO_DIR).join("kylo.json").exists());
+        let conn = Connection::open(temp_dir.path().join(HISTORY_FILE)).unwrap();
+        let history = query_history(&conn, &HistoryFilter::default()).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].pet, fed.pet);
+        assert!(!log_path.exists());
+        let journal = fs::read_to_string(&journal_path).unwrap();
+        assert_eq!(
+            journal,
+            format!("{}\n", serde_json::to_string(&fed_event).unwrap())
+        );
+    }
+
+    /// Every file under a directory, however deep
+    fn all_files(dir: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                files.extend(all_files(&path));
+            } else {
+                files.push(path);
+            }
+        }
+        files
+    }
+
+    #[test]
+    fn test_load_with_the_wrong_key_fails_instead_of_resetting() {
+        // Given: a pet encrypted with one passphrase, and a key file since replaced
+        let temp_dir = setup_test_env();
+        save_pet(
+            temp_dir.path(),
+            &Pet::new("Kylo".to_string(), "dog".to_string()),
+            &SystemClock,
+        )
+        .unwrap();
+        encrypt_data_dir_with(temp_dir.path(), "hunter2", crypto::test_params()).unwrap();
+        let pet_path = get_pet_file_path(temp_dir.path(), "Kylo").unwrap();
+        crypto::create_key_file(temp_dir.path(), "hunter3", crypto::test_params()).unwrap();
+
+        // When: loading the pet
+        let error = load_pet(temp_dir.path(), None, &SystemClock).unwrap_err();
+
+        // Then: it is an error, and the file is neither reset nor quarantined
+        assert!(error.to_string().contains("Can't decrypt"));
+        let findings = diagnose_data_dir(temp_dir.path(), true, &SystemClock).unwrap();
+        assert!(findings.iter().all(|finding| !finding.repaired));
+        assert!(pet_path.exists());
+    }
+}
@@ -1,6 +1,12 @@
+use crate::difficulty::{Condition, Difficulty};
+use crate::persistence::pet_slug;
+use crate::vacation::Vacation;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Longest name a pet can be given, in characters
+pub const MAX_NAME_LENGTH: usize = 32;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Pet {
     pub name: String,
@@ -12,13 +18,25 @@ pub struct Pet {
     pub level: u32,
     pub cleanliness: u8,
     pub potty_level: u8,
-    #[serde(default = "default_last_updated")]
     pub last_updated: DateTime<Utc>,
-}
-
-// Helper function for serde default
-fn default_last_updated() -> DateTime<Utc> {
-    Utc::now()
+    /// Seconds into the current decay interval, so time that hasn't yet cost a
+    /// whole stat point still counts towards the next one
+    #[serde(default)]
+    pub decay_progress: u32,
+    /// Seconds slept towards the next point of energy
+    #[serde(default)]
+    pub rest_progress: u32,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub condition: Condition,
+    /// Seconds a hardcore pet's happiness has sat at zero
+    #[serde(default)]
+    pub neglect_seconds: u32,
+    /// Set while the pet is being looked after by a sitter, or frozen
+    /// Left out when at home, as not every export format can hold a null
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vacation: Option<Vacation>,
 }
 
 impl Pet {
@@ -34,10 +52,36 @@ impl Pet {
             cleanliness: 80,
             potty_level: 0,
             last_updated: Utc::now(),
+            decay_progress: 0,
+            rest_progress: 0,
+            difficulty: Difficulty::Normal,
+            condition: Condition::Healthy,
+            neglect_seconds: 0,
+            vacation: None,
         }
     }
 }
 
+/// Checks a name for a new pet and returns it without surrounding spaces
+/// The save file is named after the pet, so the name needs a letter or number
+pub fn validate_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if pet_slug(name).is_empty() {
+        return Err("Pet name must contain at least one letter or number".to_string());
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!(
+            "Pet name can be at most {} characters long",
+            MAX_NAME_LENGTH
+        ));
+    }
+    if name.chars().any(char::is_control) {
+        return Err("Pet name can't contain control characters".to_string());
+    }
+    Ok(name)
+}
+
+/// A new pet with a stand-in name and species, for building pets field by field
 impl Default for Pet {
     fn default() -> Self {
         Self::new("Pet".to_string(), "unknown".to_string())
@@ -76,4 +120,15 @@ mod tests {
         assert_eq!(pet.energy, 80);
         assert_eq!(pet.level, 1);
     }
+
+    #[test]
+    fn test_validate_name_accepts_pet_and_trims() {
+        // Given/When/Then: "Pet" is a name like any other, without its spaces
+        assert_eq!(validate_name("  Pet "), Ok("Pet"));
+
+        // And: names without a letter or number, too long or with control characters are refused
+        assert!(validate_name("!!").is_err());
+        assert!(validate_name(&"a".repeat(MAX_NAME_LENGTH + 1)).is_err());
+        assert!(validate_name("Ky\nlo").is_err());
+    }
 }
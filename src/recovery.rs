@@ -0,0 +1,300 @@
+use crate::actions::REST_SECONDS_PER_POINT;
+use crate::pet::Pet;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Stat fields that are stored as 0-100 values
+const STAT_FIELDS: [&str; 6] = [
+    "hunger",
+    "happiness",
+    "energy",
+    "xp",
+    "cleanliness",
+    "potty_level",
+];
+
+/// A pet rebuilt from a damaged save file
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveredPet {
+    pub pet: Pet,
+    /// Fields that were read from the damaged file rather than defaulted
+    pub recovered_fields: Vec<String>,
+}
+
+/// Finds the raw value of `"key": <value>` in text that may not be valid JSON
+/// Handles string, number and object values, which is all a pet file contains
+fn scan_field(contents: &str, key: &str) -> Option<Value> {
+    let needle = format!("\"{}\"", key);
+    let mut search_from = 0;
+
+    while let Some(offset) = contents[search_from..].find(&needle) {
+        let after_key = search_from + offset + needle.len();
+        search_from = after_key;
+
+        let rest = contents[after_key..].trim_start();
+        let Some(rest) = rest.strip_prefix(':') else {
+            continue;
+        };
+        let rest = rest.trim_start();
+
+        if rest.starts_with('"') {
+            // Find the closing quote, skipping escaped characters
+            let mut escaped = false;
+            for (i, c) in rest.char_indices().skip(1) {
+                match c {
+                    '\\' if !escaped => escaped = true,
+                    '"' if !escaped => return serde_json::from_str(&rest[..=i]).ok(),
+                    _ => escaped = false,
+                }
+            }
+            return None;
+        }
+
+        if rest.starts_with('{') {
+            // Find the matching brace, ignoring any inside strings
+            let (mut depth, mut in_string, mut escaped) = (0, false, false);
+            for (i, c) in rest.char_indices() {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' if in_string => escaped = true,
+                    '"' => in_string = !in_string,
+                    '{' if !in_string => depth += 1,
+                    '}' if !in_string => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return serde_json::from_str(&rest[..=i]).ok();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            return None;
+        }
+
+        let end = rest
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')))
+            .unwrap_or(rest.len());
+        return serde_json::from_str(&rest[..end]).ok();
+    }
+    None
+}
+
+/// Reads a recovered value as `T`, if it is one
+fn typed<T: DeserializeOwned>(value: Option<Value>) -> Option<T> {
+    value.and_then(|v| serde_json::from_value(v).ok())
+}
+
+/// Rebuilds as much of a pet as possible from a damaged save file
+/// Works on both syntactically broken JSON (e.g. a truncated write) and valid JSON
+/// with missing or out-of-range fields. Anything unreadable keeps its default.
+/// `fallback_name` is used when the name itself can't be recovered; without one,
+/// a file with no readable name can't be recovered.
+pub fn recover_pet(contents: &str, fallback_name: Option<&str>) -> Option<RecoveredPet> {
+    let parsed = serde_json::from_str::<Value>(contents)
+        .ok()
+        .filter(Value::is_object);
+    let field = |key: &str| match &parsed {
+        Some(object) => object.get(key).cloned(),
+        None => scan_field(contents, key),
+    };
+
+    let mut recovered_fields = Vec::new();
+
+    let name = match field("name").and_then(|v| v.as_str().map(str::to_string)) {
+        Some(name) if !name.trim().is_empty() => {
+            recovered_fields.push("name".to_string());
+            name
+        }
+        _ => fallback_name?.to_string(),
+    };
+    let species = match field("species").and_then(|v| v.as_str().map(str::to_string)) {
+        Some(species) => {
+            recovered_fields.push("species".to_string());
+            species
+        }
+        None => "unknown".to_string(),
+    };
+
+    let mut pet = Pet::new(name, species);
+
+    for key in STAT_FIELDS {
+        let Some(value) = field(key)
+            .and_then(|v| v.as_u64())
+            .filter(|n| *n <= 100)
+            .map(|n| n as u8)
+        else {
+            continue;
+        };
+        match key {
+            "hunger" => pet.hunger = value,
+            "happiness" => pet.happiness = value,
+            "energy" => pet.energy = value,
+            "xp" => pet.xp = value,
+            "cleanliness" => pet.cleanliness = value,
+            _ => pet.potty_level = value,
+        }
+        recovered_fields.push(key.to_string());
+    }
+
+    if let Some(level) = field("level")
+        .and_then(|v| v.as_u64())
+        .filter(|n| *n >= 1)
+        .and_then(|n| u32::try_from(n).ok())
+    {
+        pet.level = level;
+        recovered_fields.push("level".to_string());
+    }
+
+    if let Some(last_updated) = field("last_updated")
+        .and_then(|v| v.as_str().map(str::to_string))
+        .and_then(|s| s.parse::<DateTime<Utc>>().ok())
+    {
+        pet.last_updated = last_updated;
+        recovered_fields.push("last_updated".to_string());
+    }
+
+    if let Some(difficulty) = typed(field("difficulty")) {
+        pet.difficulty = difficulty;
+        recovered_fields.push("difficulty".to_string());
+    }
+    if let Some(condition) = typed(field("condition")) {
+        pet.condition = condition;
+        recovered_fields.push("condition".to_string());
+    }
+    if let Some(neglect_seconds) = typed(field("neglect_seconds")) {
+        pet.neglect_seconds = neglect_seconds;
+        recovered_fields.push("neglect_seconds".to_string());
+    }
+    if let Some(vacation) = typed(field("vacation")) {
+        pet.vacation = Some(vacation);
+        recovered_fields.push("vacation".to_string());
+    }
+    // Progress only counts if it is part way through an interval
+    if let Some(decay_progress) = typed::<u32>(field("decay_progress"))
+        .filter(|n| (*n as u64) < pet.difficulty.decay_interval())
+    {
+        pet.decay_progress = decay_progress;
+        recovered_fields.push("decay_progress".to_string());
+    }
+    if let Some(rest_progress) =
+        typed::<u32>(field("rest_progress")).filter(|n| (*n as u64) < REST_SECONDS_PER_POINT)
+    {
+        pet.rest_progress = rest_progress;
+        recovered_fields.push("rest_progress".to_string());
+    }
+
+    Some(RecoveredPet {
+        pet,
+        recovered_fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::{Condition, Difficulty};
+
+    #[test]
+    fn test_recover_from_truncated_json() {
+        // Given: a save file cut off halfway through a write
+        let contents = r#"{
+  "name": "Kylo",
+  "species": "dog",
+  "hunger": 55,
+  "happiness": 61,
+  "ene"#;
+
+        // When: recovering the pet
+        let recovered = recover_pet(contents, None).unwrap();
+
+        // Then: the complete fields are kept and the rest are defaults
+        assert_eq!(recovered.pet.name, "Kylo");
+        assert_eq!(recovered.pet.species, "dog");
+        assert_eq!(recovered.pet.hunger, 55);
+        assert_eq!(recovered.pet.happiness, 61);
+        assert_eq!(recovered.pet.energy, 80);
+        assert_eq!(
+            recovered.recovered_fields,
+            vec!["name", "species", "hunger", "happiness"]
+        );
+    }
+
+    #[test]
+    fn test_recover_skips_out_of_range_values() {
+        // Given: valid JSON with a stat that doesn't fit the Pet struct
+        let contents = r#"{"name": "Kylo", "species": "dog", "hunger": 250, "level": 3}"#;
+
+        // When: recovering the pet
+        let recovered = recover_pet(contents, None).unwrap();
+
+        // Then: the bad stat falls back to its default, the rest is kept
+        assert_eq!(recovered.pet.hunger, 80);
+        assert_eq!(recovered.pet.level, 3);
+        assert!(!recovered.recovered_fields.contains(&"hunger".to_string()));
+    }
+
+    #[test]
+    fn test_recover_uses_fallback_name() {
+        // Given: garbage with no readable name
+        let contents = "\u{0}\u{0}garbage";
+
+        // When/Then: recovery needs a fallback name to rebuild the pet
+        assert!(recover_pet(contents, None).is_none());
+        let recovered = recover_pet(contents, Some("kylo")).unwrap();
+        assert_eq!(recovered.pet.name, "kylo");
+        assert!(recovered.recovered_fields.is_empty());
+    }
+
+    #[test]
+    fn test_recover_handles_escaped_strings() {
+        // Given: a broken file whose name contains an escaped quote
+        let contents = r#"{"name": "Sir \"Barks\"", "hunger": 12"#;
+
+        // When: recovering the pet
+        let recovered = recover_pet(contents, None).unwrap();
+
+        // Then: the name is unescaped correctly
+        assert_eq!(recovered.pet.name, "Sir \"Barks\"");
+        assert_eq!(recovered.pet.hunger, 12);
+    }
+
+    #[test]
+    fn test_recover_keeps_difficulty_condition_and_vacation() {
+        // Given: a hardcore pet on a frozen vacation whose file was cut off mid-write
+        let contents = r#"{"name": "Kylo", "difficulty": "hardcore", "condition": "ill",
+  "neglect_seconds": 3600, "decay_progress": 19000, "rest_progress": 120,
+  "vacation": {"since": "2026-10-01T08:00:00Z", "sitter": false, "visit_progress": 0},
+  "hung"#;
+
+        // When: recovering the pet
+        let recovered = recover_pet(contents, None).unwrap();
+
+        // Then: the game state comes back with the stats
+        let pet = recovered.pet;
+        assert_eq!(pet.difficulty, Difficulty::Hardcore);
+        assert_eq!(pet.condition, Condition::Ill);
+        assert_eq!(pet.neglect_seconds, 3600);
+        assert_eq!(pet.decay_progress, 19000);
+        assert_eq!(pet.rest_progress, 120);
+        let vacation = pet.vacation.unwrap();
+        assert!(!vacation.sitter);
+        assert_eq!(vacation.since.to_rfc3339(), "2026-10-01T08:00:00+00:00");
+    }
+
+    #[test]
+    fn test_recover_drops_progress_past_the_interval() {
+        // Given: valid JSON with decay progress longer than a hardcore interval
+        let contents = r#"{"name": "Kylo", "difficulty": "hardcore", "decay_progress": 20000,
+  "rest_progress": 99999999, "condition": "on_fire"}"#;
+
+        // When: recovering the pet
+        let recovered = recover_pet(contents, None).unwrap();
+
+        // Then: the bad values fall back to their defaults
+        assert_eq!(recovered.pet.decay_progress, 0);
+        assert_eq!(recovered.pet.rest_progress, 0);
+        assert_eq!(recovered.pet.condition, Condition::Healthy);
+        assert_eq!(recovered.recovered_fields, vec!["name", "difficulty"]);
+    }
+}
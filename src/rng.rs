@@ -0,0 +1,122 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::io;
+
+/// Environment variable holding the seed for random events
+pub const SEED_ENV: &str = "TERMIPET_SEED";
+
+/// Where random events, like a pet relieving itself on a walk, get their luck
+/// Two runs from the same seed make the same choices, so a bug report that
+/// gives its seed can be reproduced exactly
+#[derive(Debug)]
+pub struct GameRng {
+    rng: RefCell<StdRng>,
+}
+
+impl GameRng {
+    /// A generator that makes the same choices every time for the same seed
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// A generator seeded from the operating system
+    pub fn from_entropy() -> Self {
+        Self {
+            rng: RefCell::new(StdRng::from_entropy()),
+        }
+    }
+
+    /// Returns true with the given probability (0.0 to 1.0)
+    pub fn random_bool(&self, probability: f32) -> bool {
+        self.rng.borrow_mut().r#gen::<f32>() < probability
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_entropy()
+    }
+}
+
+/// Picks the seed: the --seed flag, then TERMIPET_SEED, then none
+pub fn resolve_seed(flag: Option<u64>) -> io::Result<Option<u64>> {
+    if flag.is_some() {
+        return Ok(flag);
+    }
+    match std::env::var(SEED_ENV) {
+        Ok(value) if !value.trim().is_empty() => value.trim().parse().map(Some).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} must be a whole number, not '{}'", SEED_ENV, value),
+            )
+        }),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_bool_probability_distribution() {
+        // Given: a probability of 0.8 (80%)
+        let rng = GameRng::from_entropy();
+        let probability = 0.8;
+        let iterations = 1000;
+        let mut true_count = 0;
+
+        // When: calling random_bool many times
+        for _ in 0..iterations {
+            if rng.random_bool(probability) {
+                true_count += 1;
+            }
+        }
+
+        // Then: approximately 80% should be true (within reasonable tolerance)
+        let actual_probability = true_count as f32 / iterations as f32;
+        // Allow 10% tolerance (0.7 to 0.9 range for 0.8 expected)
+        assert!(
+            (0.7..=0.9).contains(&actual_probability),
+            "Expected ~0.8, got {}",
+            actual_probability
+        );
+    }
+
+    #[test]
+    fn test_random_bool_always_true() {
+        // Given: probability of 1.0
+        let rng = GameRng::from_entropy();
+        // Then: should always return true
+        for _ in 0..100 {
+            assert!(rng.random_bool(1.0));
+        }
+    }
+
+    #[test]
+    fn test_random_bool_never_true() {
+        // Given: probability of 0.0
+        let rng = GameRng::from_entropy();
+        // Then: should always return false
+        for _ in 0..100 {
+            assert!(!rng.random_bool(0.0));
+        }
+    }
+
+    #[test]
+    fn test_same_seed_makes_the_same_choices() {
+        // Given: two generators with the same seed
+        let first = GameRng::seeded(42);
+        let second = GameRng::seeded(42);
+
+        // When: drawing from both
+        let a: Vec<bool> = (0..50).map(|_| first.random_bool(0.5)).collect();
+        let b: Vec<bool> = (0..50).map(|_| second.random_bool(0.5)).collect();
+
+        // Then: they agree every time
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,321 @@
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// File (inside the data dir) that overrides the built-in game rules
+pub const RULES_FILE: &str = "rules.toml";
+
+/// Feeding: stat changes, and the hunger at which the pet turns food down
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeedRules {
+    pub hunger: i32,
+    pub happiness: i32,
+    pub full: u8,
+}
+
+impl Default for FeedRules {
+    fn default() -> Self {
+        Self {
+            hunger: 20,
+            happiness: 10,
+            full: 95,
+        }
+    }
+}
+
+/// Playing: stat changes, and the energy needed to play at all
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlayRules {
+    pub happiness: i32,
+    pub energy: i32,
+    pub min_energy: u8,
+}
+
+impl Default for PlayRules {
+    fn default() -> Self {
+        Self {
+            happiness: 15,
+            energy: -10,
+            min_energy: 10,
+        }
+    }
+}
+
+/// Walking: stat changes, with the potty change only if the pet relieves itself
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WalkRules {
+    pub energy: i32,
+    pub potty: i32,
+    /// Chance (0 to 1) that the pet relieves itself on a walk
+    pub relief_chance: f32,
+}
+
+impl Default for WalkRules {
+    fn default() -> Self {
+        Self {
+            energy: 15,
+            potty: -50,
+            relief_chance: 0.8,
+        }
+    }
+}
+
+/// Training: stat changes, XP gained, and the energy needed to train at all
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrainRules {
+    pub energy: i32,
+    pub xp: u8,
+    pub min_energy: u8,
+    /// Happiness change for each level gained
+    pub level_up_happiness: i32,
+}
+
+impl Default for TrainRules {
+    fn default() -> Self {
+        Self {
+            energy: -15,
+            xp: 20,
+            min_energy: 10,
+            level_up_happiness: 5,
+        }
+    }
+}
+
+/// Potty breaks: happiness change when the break isn't too late
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PottyRules {
+    pub happiness: i32,
+}
+
+impl Default for PottyRules {
+    fn default() -> Self {
+        Self { happiness: 5 }
+    }
+}
+
+/// Cleaning: stat change
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CleanRules {
+    pub cleanliness: i32,
+}
+
+impl Default for CleanRules {
+    fn default() -> Self {
+        Self { cleanliness: 40 }
+    }
+}
+
+/// Accidents: the potty level they happen above, and their stat changes
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccidentRules {
+    pub threshold: u8,
+    pub cleanliness: i32,
+    pub happiness: i32,
+}
+
+impl Default for AccidentRules {
+    fn default() -> Self {
+        Self {
+            threshold: 80,
+            cleanliness: -30,
+            happiness: -15,
+        }
+    }
+}
+
+/// Decay: stat changes per 8-hour interval left alone
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DecayRules {
+    pub hunger: i32,
+    pub happiness: i32,
+    pub cleanliness: i32,
+    pub potty: i32,
+}
+
+impl Default for DecayRules {
+    fn default() -> Self {
+        Self {
+            hunger: -3,
+            happiness: -2,
+            cleanliness: -2,
+            potty: 2,
+        }
+    }
+}
+
+/// Catching up after time away: the levels a pet is told to have crossed
+/// Being desperate for the potty uses the accident threshold
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CatchUpRules {
+    /// Hunger below which the pet got hungry
+    pub hungry: u8,
+    /// Happiness below which the pet got bored
+    pub bored: u8,
+    /// Cleanliness below which the pet got grubby
+    pub dirty: u8,
+    /// Energy at or above which the pet caught up on sleep
+    pub rested: u8,
+}
+
+impl Default for CatchUpRules {
+    fn default() -> Self {
+        Self {
+            hungry: 40,
+            bored: 50,
+            dirty: 40,
+            rested: 30,
+        }
+    }
+}
+
+/// The numbers behind every action and decay, from `rules.toml` or built in
+/// Sections and keys left out of the file keep their built-in values
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    pub feed: FeedRules,
+    pub play: PlayRules,
+    pub walk: WalkRules,
+    pub train: TrainRules,
+    pub potty: PottyRules,
+    pub clean: CleanRules,
+    pub accident: AccidentRules,
+    pub decay: DecayRules,
+    pub catch_up: CatchUpRules,
+}
+
+impl Rules {
+    /// Reads the rules from `rules.toml` in the data dir, or the built-in rules
+    /// if there is no such file
+    pub fn load(data_dir: &Path) -> io::Result<Self> {
+        let path = data_dir.join(RULES_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        let invalid = |e: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is invalid: {}", path.display(), e),
+            )
+        };
+
+        let rules: Rules =
+            toml::from_str(&contents).map_err(|e| invalid(e.to_string().trim_end().to_string()))?;
+        rules.validate().map_err(invalid)?;
+        Ok(rules)
+    }
+
+    /// Checks that every number is one the game can work with
+    pub fn validate(&self) -> Result<(), String> {
+        let changes = [
+            ("feed.hunger", self.feed.hunger),
+            ("feed.happiness", self.feed.happiness),
+            ("play.happiness", self.play.happiness),
+            ("play.energy", self.play.energy),
+            ("walk.energy", self.walk.energy),
+            ("walk.potty", self.walk.potty),
+            ("train.energy", self.train.energy),
+            ("train.level_up_happiness", self.train.level_up_happiness),
+            ("potty.happiness", self.potty.happiness),
+            ("clean.cleanliness", self.clean.cleanliness),
+            ("accident.cleanliness", self.accident.cleanliness),
+            ("accident.happiness", self.accident.happiness),
+            ("decay.hunger", self.decay.hunger),
+            ("decay.happiness", self.decay.happiness),
+            ("decay.cleanliness", self.decay.cleanliness),
+            ("decay.potty", self.decay.potty),
+        ];
+        for (key, value) in changes {
+            if !(-100..=100).contains(&value) {
+                return Err(format!("{} is {} (must be -100 to 100)", key, value));
+            }
+        }
+
+        let levels = [
+            ("feed.full", self.feed.full),
+            ("play.min_energy", self.play.min_energy),
+            ("train.min_energy", self.train.min_energy),
+            ("train.xp", self.train.xp),
+            ("accident.threshold", self.accident.threshold),
+            ("catch_up.hungry", self.catch_up.hungry),
+            ("catch_up.bored", self.catch_up.bored),
+            ("catch_up.dirty", self.catch_up.dirty),
+            ("catch_up.rested", self.catch_up.rested),
+        ];
+        for (key, value) in levels {
+            if value > 100 {
+                return Err(format!("{} is {} (must be 0-100)", key, value));
+            }
+        }
+
+        if !(0.0..=1.0).contains(&self.walk.relief_chance) {
+            return Err(format!(
+                "walk.relief_chance is {} (must be 0 to 1)",
+                self.walk.relief_chance
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rules_file_overrides_only_what_it_sets() {
+        // Given: a rules file that makes feeding more filling
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join(RULES_FILE),
+            "[feed]\nhunger = 35\n\n[walk]\nrelief_chance = 0.5\n",
+        )
+        .unwrap();
+
+        // When: loading the rules
+        let rules = Rules::load(temp_dir.path()).unwrap();
+
+        // Then: those numbers change and everything else is built in
+        assert_eq!(rules.feed.hunger, 35);
+        assert_eq!(rules.feed.happiness, 10);
+        assert_eq!(rules.walk.relief_chance, 0.5);
+        assert_eq!(rules.decay, DecayRules::default());
+        assert_eq!(
+            Rules::load(&temp_dir.path().join("none")).unwrap(),
+            Rules::default()
+        );
+    }
+
+    #[test]
+    fn test_rules_file_is_validated() {
+        // Given: rules files with a typo and an impossible number
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(RULES_FILE);
+
+        // When/Then: each is refused, naming what is wrong
+        fs::write(&path, "[feed]\nhungr = 35\n").unwrap();
+        let error = Rules::load(temp_dir.path()).unwrap_err();
+        assert!(error.to_string().contains("hungr"), "{}", error);
+
+        fs::write(&path, "[walk]\nrelief_chance = 1.5\n").unwrap();
+        let error = Rules::load(temp_dir.path()).unwrap_err();
+        assert!(error.to_string().contains("walk.relief_chance is 1.5"));
+
+        fs::write(&path, "[catch_up]\nhungry = 120\n").unwrap();
+        let error = Rules::load(temp_dir.path()).unwrap_err();
+        assert!(error.to_string().contains("catch_up.hungry is 120"));
+    }
+}
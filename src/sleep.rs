@@ -0,0 +1,82 @@
+use crate::clock::Clock;
+use chrono::{DateTime, Days, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+
+/// Local hour pets go to sleep
+pub const BEDTIME_HOUR: u32 = 22;
+
+/// Local hour pets wake up
+pub const WAKE_HOUR: u32 = 7;
+
+fn at_hour(date: NaiveDate, hour: u32) -> NaiveDateTime {
+    date.and_time(NaiveTime::from_hms_opt(hour, 0, 0).expect("valid hour"))
+}
+
+/// Returns true if a local time falls in the night, between bedtime and waking up
+fn is_night(local: NaiveDateTime) -> bool {
+    local.hour() >= BEDTIME_HOUR || local.hour() < WAKE_HOUR
+}
+
+/// Returns true if pets are asleep right now by the clock's local time
+pub fn is_asleep(clock: &dyn Clock) -> bool {
+    is_night(clock.local(clock.now()))
+}
+
+/// Seconds between `from` and `to` that fell in the night by the clock's local time
+pub fn seconds_asleep(clock: &dyn Clock, from: DateTime<Utc>, to: DateTime<Utc>) -> u64 {
+    let elapsed = (to - from).num_seconds();
+    if elapsed <= 0 {
+        return 0;
+    }
+    let (start, end) = (clock.local(from), clock.local(to));
+
+    // Every night that could overlap, starting with the one begun the evening before
+    let mut asleep = 0;
+    let mut date = start.date() - Days::new(1);
+    while date <= end.date() {
+        let night_start = at_hour(date, BEDTIME_HOUR).max(start);
+        let night_end = at_hour(date + Days::new(1), WAKE_HOUR).min(end);
+        asleep += (night_end - night_start).num_seconds().max(0);
+        date = date + Days::new(1);
+    }
+    // A clock change can make the local hours longer than the real ones
+    asleep.min(elapsed) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use chrono::{Duration, FixedOffset, TimeZone};
+
+    #[test]
+    fn test_pets_sleep_at_night_in_the_local_time_zone() {
+        // Given: 21:30 UTC, which is 23:30 two hours east
+        let at = Utc.with_ymd_and_hms(2025, 10, 17, 21, 30, 0).unwrap();
+        let utc = FixedClock::new(at).with_offset(FixedOffset::east_opt(0).unwrap());
+        let east = FixedClock::new(at).with_offset(FixedOffset::east_opt(2 * 3600).unwrap());
+
+        // When/Then: the pet is only asleep where it is already past bedtime
+        assert!(!is_asleep(&utc));
+        assert!(is_asleep(&east));
+    }
+
+    #[test]
+    fn test_seconds_asleep_counts_only_the_night() {
+        // Given: a clock in UTC
+        let clock = FixedClock::new(Utc::now()).with_offset(FixedOffset::east_opt(0).unwrap());
+        let noon = Utc.with_ymd_and_hms(2025, 10, 17, 12, 0, 0).unwrap();
+
+        // When/Then: a whole day holds one full night, an afternoon none,
+        // and midnight to 03:00 is all asleep
+        assert_eq!(
+            seconds_asleep(&clock, noon, noon + Duration::days(1)),
+            9 * 3600
+        );
+        assert_eq!(seconds_asleep(&clock, noon, noon + Duration::hours(6)), 0);
+        let midnight = Utc.with_ymd_and_hms(2025, 10, 18, 0, 0, 0).unwrap();
+        assert_eq!(
+            seconds_asleep(&clock, midnight, midnight + Duration::hours(3)),
+            3 * 3600
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use super::{PetStore, StoreLock};
+use crate::away::DecaySummary;
+use crate::clock::Clock;
+use crate::git::commit_all;
+use crate::history::{Action, HistoryEntry, HistoryFilter};
+use crate::journal::{Event, JournalEntry};
+use crate::persistence::crypto::{file_stem, is_encrypted};
+use crate::persistence::{Diagnosis, pet_slug};
+use crate::pet::Pet;
+use crate::rng::GameRng;
+use crate::rules::Rules;
+use crate::undo::UndoStep;
+use std::cell::RefCell;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Wraps another store whose data directory is a git repository, committing
+/// each command's changes when it releases the lock
+/// The commit message describes what happened, e.g. "Fed Kylo: hunger +20, happiness +10",
+/// unless the data directory is encrypted, when it only names the changed file
+pub struct GitStore {
+    inner: Box<dyn PetStore>,
+    repo: PathBuf,
+    /// Descriptions of the changes since the last commit
+    pending: Rc<RefCell<Vec<String>>>,
+}
+
+impl GitStore {
+    pub fn new(inner: Box<dyn PetStore>, repo: &Path) -> Self {
+        Self {
+            inner,
+            repo: repo.to_path_buf(),
+            pending: Rc::default(),
+        }
+    }
+
+    /// Queues a change for the next commit message
+    /// In an encrypted data directory the description would give away what the
+    /// files hide, so the pet is named by its file's hashed stem instead
+    fn describe_change(&self, pet: &str, description: String) -> io::Result<()> {
+        let change = if is_encrypted(&self.repo) {
+            format!("Update pet {}", file_stem(&self.repo, &pet_slug(pet))?)
+        } else {
+            description
+        };
+        self.pending.borrow_mut().push(change);
+        Ok(())
+    }
+}
+
+/// Describes a history entry for a commit message
+fn describe(entry: &HistoryEntry) -> String {
+    let name = &entry.pet;
+    let what = match entry.action {
+        Action::Adopt => format!("Adopted {}", name),
+        Action::Feed => format!("Fed {}", name),
+        Action::Play => format!("Played with {}", name),
+        Action::Walk => format!("Walked {}", name),
+        Action::Train => format!("Trained {}", name),
+        Action::Potty => format!("Took {} to potty", name),
+        Action::Clean => format!("Cleaned {}", name),
+        Action::Accident => format!("{} had an accident", name),
+        Action::Decay => format!("{}'s stats decayed", name),
+        Action::Undo => format!("Undid {}'s last action", name),
+        Action::Restore => format!("Restored {} from a backup", name),
+        Action::Import => format!("Imported {}", name),
+        Action::Sync => format!("Synced {}", name),
+        Action::Vacation => format!("{}'s vacation started or ended", name),
+    };
+
+    let changes: Vec<String> = entry
+        .before
+        .fields()
+        .into_iter()
+        .zip(entry.after.fields())
+        .filter(|((_, before), (_, after))| before != after)
+        .map(|((stat, before), (_, after))| format!("{} {:+}", stat, after - before))
+        .collect();
+    if changes.is_empty() {
+        what
+    } else {
+        format!("{}: {}", what, changes.join(", "))
+    }
+}
+
+/// Builds a commit message: the last change (usually the action itself) as the
+/// subject, with every change listed in the body when there were several
+fn commit_message(changes: &[String]) -> String {
+    match changes {
+        [only] => only.clone(),
+        [.., last] => format!("{}\n\n{}", last, changes.join("\n")),
+        [] => String::new(),
+    }
+}
+
+impl PetStore for GitStore {
+    fn location(&self) -> String {
+        format!("{} (tracked with git)", self.inner.location())
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.inner.clock()
+    }
+
+    fn rng(&self) -> &GameRng {
+        self.inner.rng()
+    }
+
+    fn rules(&self) -> &Rules {
+        self.inner.rules()
+    }
+
+    fn lock(&self) -> io::Result<StoreLock> {
+        let lock = self.inner.lock()?;
+        let repo = self.repo.clone();
+        let pending = Rc::clone(&self.pending);
+        Ok(lock.on_release(move || {
+            // Changes nothing describes (such as the daily backup) go in with the next commit
+            let changes = pending.take();
+            if changes.is_empty() {
+                return;
+            }
+            if let Err(e) = commit_all(&repo, &commit_message(&changes)) {
+                eprintln!("⚠️  Couldn't commit to git: {}", e);
+            }
+        }))
+    }
+
+    fn load_pet(&self, name: Option<&str>) -> io::Result<Option<Pet>> {
+        self.inner.load_pet(name)
+    }
+
+    fn decay_summary(&self, name: &str) -> Option<DecaySummary> {
+        self.inner.decay_summary(name)
+    }
+
+    fn save_pet(&self, pet: &Pet) -> io::Result<()> {
+        self.inner.save_pet(pet)
+    }
+
+    fn list_pets(&self) -> io::Result<Vec<Pet>> {
+        self.inner.list_pets()
+    }
+
+    fn delete_pet(&self, name: &str) -> io::Result<()> {
+        self.inner.delete_pet(name)
+    }
+
+    fn pet_exists(&self, name: &str) -> io::Result<bool> {
+        self.inner.pet_exists(name)
+    }
+
+    fn active_pet_name(&self) -> io::Result<Option<String>> {
+        self.inner.active_pet_name()
+    }
+
+    fn set_active_pet(&self, name: &str) -> io::Result<()> {
+        self.inner.set_active_pet(name)
+    }
+
+    fn record(&self, entry: &HistoryEntry) -> io::Result<()> {
+        self.inner.record(entry)?;
+        self.describe_change(&entry.pet, describe(entry))
+    }
+
+    fn history(&self, filter: &HistoryFilter) -> io::Result<Vec<HistoryEntry>> {
+        self.inner.history(filter)
+    }
+
+    fn append_event(&self, entry: &JournalEntry) -> io::Result<()> {
+        self.inner.append_event(entry)?;
+        // Releasing a pet is the one change with no history entry
+        if entry.event == Event::Released {
+            self.describe_change(&entry.pet, format!("Released {}", entry.pet))?;
+        }
+        Ok(())
+    }
+
+    fn journal(&self) -> io::Result<Vec<JournalEntry>> {
+        self.inner.journal()
+    }
+
+    fn push_undo(&self, step: &UndoStep) -> io::Result<()> {
+        self.inner.push_undo(step)
+    }
+
+    fn last_undo(&self, name: &str) -> io::Result<Option<UndoStep>> {
+        self.inner.last_undo(name)
+    }
+
+    fn pop_undo(&self, name: &str) -> io::Result<Option<UndoStep>> {
+        self.inner.pop_undo(name)
+    }
+
+    fn is_encrypted(&self) -> bool {
+        self.inner.is_encrypted()
+    }
+
+    fn backup_dir(&self) -> Option<PathBuf> {
+        self.inner.backup_dir()
+    }
+
+    fn diagnose(&self, repair: bool) -> io::Result<Vec<Diagnosis>> {
+        self.inner.diagnose(repair)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{adopt_pet, feed_pet, walk_pet};
+    use crate::difficulty::Difficulty;
+    use crate::git::init_repo;
+    use crate::persistence::crypto::{create_key_file, test_params};
+    use crate::store::JsonFileStore;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_each_command_is_committed_with_what_it_did() {
+        // Given: a data directory tracked with git
+        let temp_dir = TempDir::new().unwrap();
+        init_repo(temp_dir.path()).unwrap();
+        let store = GitStore::new(
+            Box::new(JsonFileStore::new(temp_dir.path())),
+            temp_dir.path(),
+        );
+
+        // When: adopting, feeding and walking a pet
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        feed_pet(&store, None).unwrap();
+        walk_pet(&store, None, true).unwrap();
+
+        // Then: there is one commit per command, describing the change
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(temp_dir.path())
+            .args(["log", "--format=%s"])
+            .output()
+            .unwrap();
+        let log = String::from_utf8(output.stdout).unwrap();
+        let subjects: Vec<&str> = log.lines().collect();
+        assert_eq!(subjects.len(), 4);
+        assert!(subjects[0].starts_with("Walked Kylo: "));
+        assert_eq!(subjects[1], "Fed Kylo: hunger +20, happiness +10");
+        assert_eq!(subjects[2], "Adopted Kylo");
+    }
+
+    #[test]
+    fn test_encrypted_commits_dont_name_the_pet() {
+        // Given: an encrypted data directory tracked with git
+        let temp_dir = TempDir::new().unwrap();
+        init_repo(temp_dir.path()).unwrap();
+        create_key_file(temp_dir.path(), "hunter2", test_params()).unwrap();
+        let store = GitStore::new(
+            Box::new(JsonFileStore::new(temp_dir.path())),
+            temp_dir.path(),
+        );
+
+        // When: adopting and feeding a pet
+        adopt_pet(&store, "Kylo", "dog", Difficulty::Normal).unwrap();
+        feed_pet(&store, None).unwrap();
+
+        // Then: the commits happen, but nothing in the log names the pet or its stats
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(temp_dir.path())
+            .args(["log", "--format=%B", "--name-only"])
+            .output()
+            .unwrap();
+        let log = String::from_utf8(output.stdout).unwrap();
+        let stem = file_stem(temp_dir.path(), "kylo").unwrap();
+        assert!(log.contains(&format!("Update pet {}", stem)));
+        assert!(!log.to_lowercase().contains("kylo"), "{}", log);
+        assert!(!log.contains("hunger"));
+    }
+}
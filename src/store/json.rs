@@ -0,0 +1,202 @@
+use super::{PetStore, StoreLock};
+use crate::away::DecaySummary;
+use crate::backup::BACKUP_DIR;
+use crate::clock::{Clock, SystemClock};
+use crate::history::{
+    self, Action, HistoryEntry, HistoryFilter, PendingDecay, create_history_table, insert_history,
+    query_history,
+};
+use crate::journal::{self, JournalEntry};
+use crate::persistence::crypto::is_encrypted;
+use crate::persistence::{self, Diagnosis, HISTORY_FILE, HISTORY_LOG_FILE, JOURNAL_FILE};
+use crate::pet::Pet;
+use crate::rng::GameRng;
+use crate::rules::Rules;
+use crate::undo::UndoStep;
+use rusqlite::Connection;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+/// How long SQLite waits on a busy history database before giving up
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Keeps one JSON file per pet in the data directory (see `persistence`)
+/// The JSON files only hold each pet's latest state, so the action history
+/// lives next to them in a small SQLite database and the event journal in a
+/// JSON-lines file. While the data dir is encrypted, the history is kept in a
+/// JSON-lines file too, as its lines can be encrypted one by one
+#[derive(Debug)]
+pub struct JsonFileStore {
+    data_dir: PathBuf,
+    clock: Rc<dyn Clock>,
+    rng: GameRng,
+    rules: Rules,
+    pending_decay: PendingDecay,
+}
+
+impl JsonFileStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            data_dir: data_dir.to_path_buf(),
+            clock: Rc::new(SystemClock),
+            rng: GameRng::default(),
+            rules: Rules::default(),
+            pending_decay: PendingDecay::default(),
+        }
+    }
+
+    /// Uses another clock instead of the real time
+    pub fn with_clock(mut self, clock: Rc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Uses another source of random events, e.g. a seeded one
+    pub fn with_rng(mut self, rng: GameRng) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    /// Plays by other rules than the built-in ones
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Opens the history database, creating it on first use
+    fn open_history(&self) -> io::Result<Connection> {
+        fs::create_dir_all(&self.data_dir)?;
+        let conn = Connection::open(self.data_dir.join(HISTORY_FILE)).map_err(io::Error::other)?;
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(io::Error::other)?;
+        create_history_table(&conn)?;
+        Ok(conn)
+    }
+}
+
+impl PetStore for JsonFileStore {
+    fn location(&self) -> String {
+        self.data_dir.display().to_string()
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    fn rng(&self) -> &GameRng {
+        &self.rng
+    }
+
+    fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    fn lock(&self) -> io::Result<StoreLock> {
+        persistence::lock_data_dir(&self.data_dir).map(StoreLock::from)
+    }
+
+    fn load_pet(&self, name: Option<&str>) -> io::Result<Option<Pet>> {
+        let Some(mut pet) =
+            persistence::load_stored_pet(&self.data_dir, name, self.clock.as_ref())?
+        else {
+            return Ok(None);
+        };
+        self.pending_decay
+            .apply(&mut pet, self.clock.as_ref(), &self.rules);
+        Ok(Some(pet))
+    }
+
+    fn decay_summary(&self, name: &str) -> Option<DecaySummary> {
+        self.pending_decay.summary(name)
+    }
+
+    fn save_pet(&self, pet: &Pet) -> io::Result<()> {
+        persistence::save_pet(&self.data_dir, pet, self.clock.as_ref())?;
+        if let Some((decay, event)) = self.pending_decay.take(&pet.name) {
+            self.append_event(&event)?;
+            if decay.changed_stats() {
+                self.record(&decay)?;
+                self.push_undo(&UndoStep::new(Action::Decay, pet, decay.at))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn list_pets(&self) -> io::Result<Vec<Pet>> {
+        let mut pets = persistence::list_stored_pets(&self.data_dir, self.clock.as_ref())?;
+        pets.iter_mut().for_each(|pet| {
+            self.pending_decay
+                .apply(pet, self.clock.as_ref(), &self.rules)
+        });
+        Ok(pets)
+    }
+
+    fn delete_pet(&self, name: &str) -> io::Result<()> {
+        persistence::delete_pet(&self.data_dir, name, self.clock.as_ref())
+    }
+
+    fn pet_exists(&self, name: &str) -> io::Result<bool> {
+        persistence::pet_exists(&self.data_dir, name, self.clock.as_ref())
+    }
+
+    fn active_pet_name(&self) -> io::Result<Option<String>> {
+        persistence::active_pet_name(&self.data_dir, self.clock.as_ref())
+    }
+
+    fn set_active_pet(&self, name: &str) -> io::Result<()> {
+        persistence::set_active_pet(&self.data_dir, name, self.clock.as_ref())
+    }
+
+    fn record(&self, entry: &HistoryEntry) -> io::Result<()> {
+        if is_encrypted(&self.data_dir) {
+            let path = self.data_dir.join(HISTORY_LOG_FILE);
+            return history::append_to_file(&self.data_dir, &path, entry);
+        }
+        insert_history(&self.open_history()?, entry)
+    }
+
+    fn history(&self, filter: &HistoryFilter) -> io::Result<Vec<HistoryEntry>> {
+        if is_encrypted(&self.data_dir) {
+            let path = self.data_dir.join(HISTORY_LOG_FILE);
+            return history::read_file(&self.data_dir, &path, filter);
+        }
+        if !self.data_dir.join(HISTORY_FILE).exists() {
+            return Ok(Vec::new());
+        }
+        query_history(&self.open_history()?, filter)
+    }
+
+    fn append_event(&self, entry: &JournalEntry) -> io::Result<()> {
+        journal::append_to_file(&self.data_dir, &self.data_dir.join(JOURNAL_FILE), entry)
+    }
+
+    fn journal(&self) -> io::Result<Vec<JournalEntry>> {
+        journal::read_file(&self.data_dir, &self.data_dir.join(JOURNAL_FILE))
+    }
+
+    fn push_undo(&self, step: &UndoStep) -> io::Result<()> {
+        persistence::push_undo_step(&self.data_dir, step)
+    }
+
+    fn last_undo(&self, name: &str) -> io::Result<Option<UndoStep>> {
+        Ok(persistence::read_undo_steps(&self.data_dir, name)?.pop())
+    }
+
+    fn pop_undo(&self, name: &str) -> io::Result<Option<UndoStep>> {
+        persistence::pop_undo_step(&self.data_dir, name)
+    }
+
+    fn is_encrypted(&self) -> bool {
+        is_encrypted(&self.data_dir)
+    }
+
+    fn backup_dir(&self) -> Option<PathBuf> {
+        Some(self.data_dir.join(BACKUP_DIR))
+    }
+
+    fn diagnose(&self, repair: bool) -> io::Result<Vec<Diagnosis>> {
+        persistence::diagnose_data_dir(&self.data_dir, repair, self.clock.as_ref())
+    }
+}
@@ -0,0 +1,245 @@
+use super::{PetStore, StoreLock};
+use crate::away::DecaySummary;
+use crate::clock::{Clock, SystemClock};
+use crate::history::{Action, HistoryEntry, HistoryFilter, PendingDecay};
+use crate::journal::JournalEntry;
+use crate::persistence::pet_slug;
+use crate::pet::Pet;
+use crate::rng::GameRng;
+use crate::rules::Rules;
+use crate::undo::{UndoStep, push_step};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::rc::Rc;
+
+/// Keeps pets in memory only, for tests and for embedding termipet in other programs
+#[derive(Debug)]
+pub struct MemoryStore {
+    /// Pets keyed by slug, so iteration is already sorted by name
+    pets: RefCell<BTreeMap<String, Pet>>,
+    active: RefCell<Option<String>>,
+    history: RefCell<Vec<HistoryEntry>>,
+    journal: RefCell<Vec<JournalEntry>>,
+    /// Undo stacks keyed by slug, oldest step first
+    undo: RefCell<HashMap<String, Vec<UndoStep>>>,
+    clock: Rc<dyn Clock>,
+    rng: GameRng,
+    rules: Rules,
+    pending_decay: PendingDecay,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self {
+            pets: RefCell::default(),
+            active: RefCell::default(),
+            history: RefCell::default(),
+            journal: RefCell::default(),
+            undo: RefCell::default(),
+            clock: Rc::new(SystemClock),
+            rng: GameRng::default(),
+            rules: Rules::default(),
+            pending_decay: PendingDecay::default(),
+        }
+    }
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses another clock instead of the real time
+    pub fn with_clock(mut self, clock: Rc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Uses another source of random events, e.g. a seeded one
+    pub fn with_rng(mut self, rng: GameRng) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    /// Plays by other rules than the built-in ones
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    fn no_such_pet(name: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, format!("No pet named '{}'", name))
+    }
+}
+
+impl PetStore for MemoryStore {
+    fn location(&self) -> String {
+        "in-memory store".to_string()
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    fn rng(&self) -> &GameRng {
+        &self.rng
+    }
+
+    fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    fn lock(&self) -> io::Result<StoreLock> {
+        // Nothing outside this process can see the pets
+        Ok(StoreLock::unshared())
+    }
+
+    fn load_pet(&self, name: Option<&str>) -> io::Result<Option<Pet>> {
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => match self.active.borrow().clone() {
+                Some(name) => name,
+                None => return Ok(None),
+            },
+        };
+
+        let Some(mut pet) = self.pets.borrow().get(&pet_slug(&name)).cloned() else {
+            return Ok(None);
+        };
+        self.pending_decay
+            .apply(&mut pet, self.clock.as_ref(), &self.rules);
+        Ok(Some(pet))
+    }
+
+    fn decay_summary(&self, name: &str) -> Option<DecaySummary> {
+        self.pending_decay.summary(name)
+    }
+
+    fn save_pet(&self, pet: &Pet) -> io::Result<()> {
+        let slug = pet_slug(&pet.name);
+        if slug.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("'{}' is not a valid pet name", pet.name),
+            ));
+        }
+
+        let mut pet_to_save = pet.clone();
+        pet_to_save.last_updated = self.clock.now();
+        self.pets.borrow_mut().insert(slug, pet_to_save);
+
+        let mut active = self.active.borrow_mut();
+        if active.is_none() {
+            *active = Some(pet.name.clone());
+        }
+
+        if let Some((decay, event)) = self.pending_decay.take(&pet.name) {
+            self.append_event(&event)?;
+            if decay.changed_stats() {
+                self.record(&decay)?;
+                self.push_undo(&UndoStep::new(Action::Decay, pet, decay.at))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn list_pets(&self) -> io::Result<Vec<Pet>> {
+        let mut pets: Vec<Pet> = self.pets.borrow().values().cloned().collect();
+        pets.iter_mut().for_each(|pet| {
+            self.pending_decay
+                .apply(pet, self.clock.as_ref(), &self.rules)
+        });
+        Ok(pets)
+    }
+
+    fn delete_pet(&self, name: &str) -> io::Result<()> {
+        let slug = pet_slug(name);
+        if self.pets.borrow_mut().remove(&slug).is_none() {
+            return Err(Self::no_such_pet(name));
+        }
+        self.undo.borrow_mut().remove(&slug);
+
+        let mut active = self.active.borrow_mut();
+        if active
+            .as_deref()
+            .is_some_and(|active| pet_slug(active) == slug)
+        {
+            *active = self
+                .pets
+                .borrow()
+                .values()
+                .next()
+                .map(|pet| pet.name.clone());
+        }
+        Ok(())
+    }
+
+    fn pet_exists(&self, name: &str) -> io::Result<bool> {
+        Ok(self.pets.borrow().contains_key(&pet_slug(name)))
+    }
+
+    fn active_pet_name(&self) -> io::Result<Option<String>> {
+        Ok(self.active.borrow().clone())
+    }
+
+    fn set_active_pet(&self, name: &str) -> io::Result<()> {
+        let stored_name = match self.pets.borrow().get(&pet_slug(name)) {
+            Some(pet) => pet.name.clone(),
+            None => return Err(Self::no_such_pet(name)),
+        };
+        *self.active.borrow_mut() = Some(stored_name);
+        Ok(())
+    }
+
+    fn record(&self, entry: &HistoryEntry) -> io::Result<()> {
+        self.history.borrow_mut().push(entry.clone());
+        Ok(())
+    }
+
+    fn history(&self, filter: &HistoryFilter) -> io::Result<Vec<HistoryEntry>> {
+        let mut entries: Vec<HistoryEntry> = self
+            .history
+            .borrow()
+            .iter()
+            .filter(|entry| filter.matches(entry))
+            .cloned()
+            .collect();
+        entries.sort_by_key(|entry| entry.at);
+        Ok(entries)
+    }
+
+    fn append_event(&self, entry: &JournalEntry) -> io::Result<()> {
+        self.journal.borrow_mut().push(entry.clone());
+        Ok(())
+    }
+
+    fn journal(&self) -> io::Result<Vec<JournalEntry>> {
+        Ok(self.journal.borrow().clone())
+    }
+
+    fn push_undo(&self, step: &UndoStep) -> io::Result<()> {
+        let mut undo = self.undo.borrow_mut();
+        push_step(
+            undo.entry(pet_slug(&step.pet.name)).or_default(),
+            step.clone(),
+        );
+        Ok(())
+    }
+
+    fn last_undo(&self, name: &str) -> io::Result<Option<UndoStep>> {
+        Ok(self
+            .undo
+            .borrow()
+            .get(&pet_slug(name))
+            .and_then(|steps| steps.last().cloned()))
+    }
+
+    fn pop_undo(&self, name: &str) -> io::Result<Option<UndoStep>> {
+        Ok(self
+            .undo
+            .borrow_mut()
+            .get_mut(&pet_slug(name))
+            .and_then(|steps| steps.pop()))
+    }
+}
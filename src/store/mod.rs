@@ -0,0 +1,486 @@
+pub mod git;
+pub mod json;
+pub mod memory;
+pub mod sqlite;
+
+use crate::away::DecaySummary;
+use crate::clock::Clock;
+use crate::history::{HistoryEntry, HistoryFilter};
+use crate::journal::JournalEntry;
+use crate::persistence::{DataDirLock, Diagnosis};
+use crate::pet::Pet;
+use crate::rng::GameRng;
+use crate::rules::Rules;
+use crate::undo::UndoStep;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr;
+
+pub use git::GitStore;
+pub use json::JsonFileStore;
+pub use memory::MemoryStore;
+pub use sqlite::SqliteStore;
+
+/// Environment variable selecting the storage backend when --store isn't given
+pub const STORE_ENV: &str = "TERMIPET_STORE";
+
+/// Where pets are kept between commands
+/// Every command works through this trait, so game logic never touches files directly
+pub trait PetStore {
+    /// Describes where the pets live, for messages such as the doctor header
+    fn location(&self) -> String;
+
+    /// The clock that decay, save timestamps and entries are measured against
+    fn clock(&self) -> &dyn Clock;
+
+    /// Where random events get their luck, seeded to reproduce a run
+    fn rng(&self) -> &GameRng;
+
+    /// The numbers every action and decay plays by
+    fn rules(&self) -> &Rules;
+
+    /// Takes an exclusive lock held across a command's load → mutate → save cycle
+    fn lock(&self) -> io::Result<StoreLock>;
+
+    /// Loads a pet and applies decay
+    /// Decay is recorded in the history once the decayed pet is saved
+    /// `name` selects a specific pet; `None` loads the active pet
+    /// Returns None if nothing has been adopted or the named pet doesn't exist
+    fn load_pet(&self, name: Option<&str>) -> io::Result<Option<Pet>>;
+
+    /// What the decay applied when the named pet was last loaded did, until it is saved
+    fn decay_summary(&self, name: &str) -> Option<DecaySummary>;
+
+    /// Saves a pet, updating its last_updated timestamp
+    /// The first pet saved into an empty roster becomes the active one
+    /// Decay applied on load is recorded here, and can't be undone
+    fn save_pet(&self, pet: &Pet) -> io::Result<()>;
+
+    /// Loads every pet in the roster, sorted by name
+    fn list_pets(&self) -> io::Result<Vec<Pet>>;
+
+    /// Removes a pet; if it was active, the next pet in the roster becomes active
+    fn delete_pet(&self, name: &str) -> io::Result<()>;
+
+    /// Returns true if a pet with the given name is in the roster
+    fn pet_exists(&self, name: &str) -> io::Result<bool>;
+
+    /// Returns the name of the pet commands act on when no --pet is given
+    fn active_pet_name(&self) -> io::Result<Option<String>>;
+
+    /// Makes the named pet the default target for commands
+    fn set_active_pet(&self, name: &str) -> io::Result<()>;
+
+    /// Appends an entry to the action history
+    fn record(&self, entry: &HistoryEntry) -> io::Result<()>;
+
+    /// Returns the recorded history matching the filter, oldest first
+    fn history(&self, filter: &HistoryFilter) -> io::Result<Vec<HistoryEntry>>;
+
+    /// Appends an event to the journal; entries are never rewritten or removed
+    fn append_event(&self, entry: &JournalEntry) -> io::Result<()>;
+
+    /// Returns every journal entry, in the order they were appended
+    fn journal(&self) -> io::Result<Vec<JournalEntry>>;
+
+    /// Remembers a pet's state from before an action so the action can be undone
+    /// Only the most recent `UNDO_DEPTH` steps per pet are kept
+    fn push_undo(&self, step: &UndoStep) -> io::Result<()>;
+
+    /// Returns the pet's most recent undo step without removing it
+    fn last_undo(&self, name: &str) -> io::Result<Option<UndoStep>>;
+
+    /// Removes and returns the pet's most recent undo step
+    fn pop_undo(&self, name: &str) -> io::Result<Option<UndoStep>>;
+
+    /// Returns true if the pets are stored encrypted, so nothing about them may
+    /// leave the store in plain text
+    fn is_encrypted(&self) -> bool {
+        false
+    }
+
+    /// Directory holding the daily backups, or None if the store keeps no backups
+    fn backup_dir(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Checks the stored pets for damage; with `repair`, fixes what it can
+    fn diagnose(&self, _repair: bool) -> io::Result<Vec<Diagnosis>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Guard returned by `PetStore::lock`; the store stays locked until it is dropped
+pub struct StoreLock {
+    _guard: Option<DataDirLock>,
+    on_release: Option<Box<dyn FnOnce()>>,
+}
+
+impl StoreLock {
+    /// A lock for stores that only one process can see, such as `MemoryStore`
+    pub fn unshared() -> Self {
+        Self {
+            _guard: None,
+            on_release: None,
+        }
+    }
+
+    /// Runs `f` when the lock is dropped, just before it is released
+    pub fn on_release(mut self, f: impl FnOnce() + 'static) -> Self {
+        self.on_release = Some(Box::new(f));
+        self
+    }
+}
+
+impl From<DataDirLock> for StoreLock {
+    fn from(lock: DataDirLock) -> Self {
+        Self {
+            _guard: Some(lock),
+            on_release: None,
+        }
+    }
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        if let Some(f) = self.on_release.take() {
+            f();
+        }
+    }
+}
+
+impl fmt::Debug for StoreLock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoreLock")
+            .field("_guard", &self._guard)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The storage backends the CLI can use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StoreKind {
+    /// One JSON file per pet (the default)
+    #[default]
+    Json,
+    /// A single SQLite database
+    Sqlite,
+}
+
+impl FromStr for StoreKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(StoreKind::Json),
+            "sqlite" => Ok(StoreKind::Sqlite),
+            other => Err(format!(
+                "unknown store '{}' (expected 'json' or 'sqlite')",
+                other
+            )),
+        }
+    }
+}
+
+/// Picks the backend: the --store flag, then TERMIPET_STORE, then JSON files
+pub fn resolve_store_kind(flag: Option<StoreKind>) -> io::Result<StoreKind> {
+    if let Some(kind) = flag {
+        return Ok(kind);
+    }
+    match std::env::var(STORE_ENV) {
+        Ok(value) if !value.trim().is_empty() => value
+            .parse()
+            .map_err(|e: String| io::Error::new(io::ErrorKind::InvalidInput, e)),
+        _ => Ok(StoreKind::default()),
+    }
+}
+
+/// Opens the chosen backend inside the data directory
+/// If the data directory is a git repository, every change is also committed
+pub fn open_store(
+    kind: StoreKind,
+    data_dir: &Path,
+    clock: Rc<dyn Clock>,
+    rng: GameRng,
+    rules: Rules,
+) -> io::Result<Box<dyn PetStore>> {
+    let store: Box<dyn PetStore> = match kind {
+        StoreKind::Json => Box::new(
+            JsonFileStore::new(data_dir)
+                .with_clock(clock)
+                .with_rng(rng)
+                .with_rules(rules),
+        ),
+        StoreKind::Sqlite => Box::new(
+            SqliteStore::open(data_dir)?
+                .with_clock(clock)
+                .with_rng(rng)
+                .with_rules(rules),
+        ),
+    };
+    Ok(if crate::git::is_repo(data_dir) {
+        Box::new(GitStore::new(store, data_dir))
+    } else {
+        store
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    // Runs the same roster scenario against any backend
+    fn check_roster_behaviour(store: &dyn PetStore) {
+        // Nothing adopted yet
+        assert_eq!(store.load_pet(None).unwrap(), None);
+        assert!(store.list_pets().unwrap().is_empty());
+        assert_eq!(store.active_pet_name().unwrap(), None);
+
+        // The first pet saved becomes active
+        let mut kylo = Pet::new("Kylo".to_string(), "dog".to_string());
+        kylo.hunger = 42;
+        store.save_pet(&kylo).unwrap();
+        store
+            .save_pet(&Pet::new("Luna".to_string(), "cat".to_string()))
+            .unwrap();
+        assert_eq!(store.active_pet_name().unwrap().as_deref(), Some("Kylo"));
+        assert_eq!(store.load_pet(None).unwrap().unwrap().hunger, 42);
+        assert!(store.pet_exists("kylo").unwrap());
+        assert!(!store.pet_exists("Rex").unwrap());
+
+        // Names are matched case-insensitively and stored as the pet spells them
+        store.set_active_pet("luna").unwrap();
+        assert_eq!(store.active_pet_name().unwrap().as_deref(), Some("Luna"));
+        assert_eq!(store.load_pet(Some("KYLO")).unwrap().unwrap().name, "Kylo");
+        assert!(store.set_active_pet("Rex").is_err());
+
+        let names: Vec<String> = store
+            .list_pets()
+            .unwrap()
+            .into_iter()
+            .map(|pet| pet.name)
+            .collect();
+        assert_eq!(names, ["Kylo", "Luna"]);
+
+        // Deleting the active pet hands the slot to the next one
+        store.delete_pet("Luna").unwrap();
+        assert_eq!(store.active_pet_name().unwrap().as_deref(), Some("Kylo"));
+        store.delete_pet("Kylo").unwrap();
+        assert_eq!(store.active_pet_name().unwrap(), None);
+        assert_eq!(store.load_pet(None).unwrap(), None);
+    }
+
+    // Runs the same history scenario against any backend
+    fn check_history_behaviour(store: &dyn PetStore) {
+        use crate::history::Action;
+
+        let kylo = Pet::new("Kylo".to_string(), "dog".to_string());
+        let mut fed = kylo.clone();
+        fed.hunger = 100;
+        let luna = Pet::new("Luna".to_string(), "cat".to_string());
+        store
+            .record(&HistoryEntry::new(Action::Adopt, &kylo, &kylo, Utc::now()))
+            .unwrap();
+        store
+            .record(&HistoryEntry::new(Action::Feed, &kylo, &fed, Utc::now()))
+            .unwrap();
+        store
+            .record(&HistoryEntry::new(Action::Feed, &luna, &luna, Utc::now()))
+            .unwrap();
+
+        // Entries come back oldest first with their stats
+        let kylos = HistoryFilter {
+            pet: Some("kylo".to_string()),
+            ..Default::default()
+        };
+        let entries = store.history(&kylos).unwrap();
+        let actions: Vec<Action> = entries.iter().map(|entry| entry.action).collect();
+        assert_eq!(actions, [Action::Adopt, Action::Feed]);
+        assert_eq!(entries[1].before.hunger, 80);
+        assert_eq!(entries[1].after.hunger, 100);
+
+        // Filters combine
+        let feeds = HistoryFilter {
+            action: Some(Action::Feed),
+            ..Default::default()
+        };
+        assert_eq!(store.history(&feeds).unwrap().len(), 2);
+        let since_tomorrow = HistoryFilter {
+            since: Some(chrono::Utc::now() + chrono::Duration::days(1)),
+            ..Default::default()
+        };
+        assert!(store.history(&since_tomorrow).unwrap().is_empty());
+    }
+
+    // Runs the same undo scenario against any backend
+    fn check_undo_behaviour(store: &dyn PetStore) {
+        use crate::history::Action;
+        use crate::undo::UNDO_DEPTH;
+
+        let kylo = Pet::new("Kylo".to_string(), "dog".to_string());
+        store.save_pet(&kylo).unwrap();
+        assert_eq!(store.last_undo("Kylo").unwrap(), None);
+
+        // Only the most recent steps are kept, newest first out
+        for hunger in 0..=UNDO_DEPTH as u8 {
+            let mut before = kylo.clone();
+            before.hunger = hunger;
+            store
+                .push_undo(&UndoStep::new(Action::Feed, &before, Utc::now()))
+                .unwrap();
+        }
+        let latest = store.last_undo("kylo").unwrap().unwrap();
+        assert_eq!(latest.pet.hunger, UNDO_DEPTH as u8);
+        assert_eq!(store.pop_undo("Kylo").unwrap(), Some(latest));
+        let mut remaining = 1;
+        while store.pop_undo("Kylo").unwrap().is_some() {
+            remaining += 1;
+        }
+        assert_eq!(remaining, UNDO_DEPTH);
+
+        // Releasing a pet forgets its undo steps
+        store
+            .push_undo(&UndoStep::new(Action::Feed, &kylo, Utc::now()))
+            .unwrap();
+        store.delete_pet("Kylo").unwrap();
+        assert_eq!(store.last_undo("Kylo").unwrap(), None);
+    }
+
+    #[test]
+    fn test_json_store_records_decay_once_saved() {
+        use crate::clock::FixedClock;
+        use crate::history::Action;
+        use crate::journal::Event;
+        use chrono::{Duration, FixedOffset, TimeZone};
+
+        // Given: a pet file last updated at 20:00, 16 hours ago
+        let temp_dir = TempDir::new().unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 10, 17, 12, 0, 0).unwrap();
+        let clock = FixedClock::new(now).with_offset(FixedOffset::east_opt(0).unwrap());
+        let store = JsonFileStore::new(temp_dir.path()).with_clock(Rc::new(clock));
+        let mut kylo = Pet::new("Kylo".to_string(), "dog".to_string());
+        kylo.last_updated = now - Duration::hours(16);
+        let path = crate::persistence::get_pet_file_path(temp_dir.path(), "Kylo").unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, serde_json::to_string(&kylo).unwrap()).unwrap();
+
+        // When: looking at it twice, then saving it
+        store.load_pet(Some("Kylo")).unwrap().unwrap();
+        let pet = store.load_pet(Some("Kylo")).unwrap().unwrap();
+        assert!(store.history(&HistoryFilter::default()).unwrap().is_empty());
+        store.save_pet(&pet).unwrap();
+
+        // Then: the decay is recorded exactly once
+        let entries = store.history(&HistoryFilter::default()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, Action::Decay);
+        assert_eq!(entries[0].before.hunger, 80);
+        assert_eq!(entries[0].after.hunger, 74);
+        let events: Vec<Event> = store
+            .journal()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.event)
+            .collect();
+        assert_eq!(events, [Event::decayed(16 * 3600, 9 * 3600)]);
+    }
+
+    #[test]
+    fn test_json_store_roster() {
+        // Given: a JSON file store in an empty directory
+        let temp_dir = TempDir::new().unwrap();
+        let store = JsonFileStore::new(temp_dir.path());
+
+        // When/Then: it behaves like every other backend
+        check_roster_behaviour(&store);
+    }
+
+    #[test]
+    fn test_json_store_history() {
+        // Given: a JSON file store in an empty directory
+        let temp_dir = TempDir::new().unwrap();
+        let store = JsonFileStore::new(temp_dir.path());
+
+        // When/Then: history is recorded and filtered like every other backend
+        check_history_behaviour(&store);
+    }
+
+    #[test]
+    fn test_json_store_undo() {
+        // Given: a JSON file store in an empty directory
+        let temp_dir = TempDir::new().unwrap();
+        let store = JsonFileStore::new(temp_dir.path());
+
+        // When/Then: undo steps are kept like every other backend
+        check_undo_behaviour(&store);
+    }
+
+    #[test]
+    fn test_memory_store_roster() {
+        // Given: an empty in-memory store
+        let store = MemoryStore::new();
+
+        // When/Then: it behaves like every other backend
+        check_roster_behaviour(&store);
+    }
+
+    #[test]
+    fn test_memory_store_history() {
+        // Given: an empty in-memory store
+        let store = MemoryStore::new();
+
+        // When/Then: history is recorded and filtered like every other backend
+        check_history_behaviour(&store);
+    }
+
+    #[test]
+    fn test_memory_store_undo() {
+        // Given: an empty in-memory store
+        let store = MemoryStore::new();
+
+        // When/Then: undo steps are kept like every other backend
+        check_undo_behaviour(&store);
+    }
+
+    #[test]
+    fn test_sqlite_store_roster() {
+        // Given: a SQLite store in an empty directory
+        let temp_dir = TempDir::new().unwrap();
+        let store = SqliteStore::open(temp_dir.path()).unwrap();
+
+        // When/Then: it behaves like every other backend
+        check_roster_behaviour(&store);
+    }
+
+    #[test]
+    fn test_sqlite_store_history() {
+        // Given: a SQLite store in an empty directory
+        let temp_dir = TempDir::new().unwrap();
+        let store = SqliteStore::open(temp_dir.path()).unwrap();
+
+        // When/Then: history is recorded and filtered like every other backend
+        check_history_behaviour(&store);
+    }
+
+    #[test]
+    fn test_sqlite_store_undo() {
+        // Given: a SQLite store in an empty directory
+        let temp_dir = TempDir::new().unwrap();
+        let store = SqliteStore::open(temp_dir.path()).unwrap();
+
+        // When/Then: undo steps are kept like every other backend
+        check_undo_behaviour(&store);
+    }
+
+    #[test]
+    fn test_store_kind_parses_names() {
+        // Given/When/Then: backend names parse case-insensitively
+        assert_eq!("json".parse::<StoreKind>().unwrap(), StoreKind::Json);
+        assert_eq!("SQLite".parse::<StoreKind>().unwrap(), StoreKind::Sqlite);
+        assert!("yaml".parse::<StoreKind>().is_err());
+    }
+}
@@ -0,0 +1,477 @@
+use super::{PetStore, StoreLock};
+use crate::away::DecaySummary;
+use crate::backup::BACKUP_DIR;
+use crate::clock::{Clock, SystemClock};
+use crate::history::{
+    Action, HistoryEntry, HistoryFilter, PendingDecay, create_history_table, insert_history,
+    query_history,
+};
+use crate::journal::{JournalEntry, create_journal_table, insert_journal, query_journal};
+use crate::persistence::crypto::is_encrypted;
+use crate::persistence::migrations::CURRENT_SCHEMA_VERSION;
+use crate::persistence::{
+    Diagnosis, PetFileError, lock_data_dir, parse_pet, pet_slug, serialize_pet, too_new_error,
+};
+use crate::pet::Pet;
+use crate::rng::GameRng;
+use crate::rules::Rules;
+use crate::undo::{UndoStep, clear_undo, create_undo_table, insert_undo, latest_undo};
+use rusqlite::{Connection, OptionalExtension, params};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Database file (inside the data dir) used by the SQLite backend
+pub(crate) const DATABASE_FILE: &str = "termipet.db";
+
+/// Settings key holding the name of the active pet
+const ACTIVE_PET_KEY: &str = "active_pet";
+
+/// How long SQLite waits on a busy database before giving up
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Keeps every pet in a single SQLite database in the data directory
+/// Each row stores the same versioned JSON document as the file backend, so
+/// schema migrations apply to both. The action history and the event journal are
+/// tables in the same database.
+#[derive(Debug)]
+pub struct SqliteStore {
+    data_dir: PathBuf,
+    db_path: PathBuf,
+    conn: Connection,
+    clock: Rc<dyn Clock>,
+    rng: GameRng,
+    rules: Rules,
+    pending_decay: PendingDecay,
+}
+
+fn sql_error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+impl SqliteStore {
+    /// Uses another clock instead of the real time
+    pub fn with_clock(mut self, clock: Rc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Uses another source of random events, e.g. a seeded one
+    pub fn with_rng(mut self, rng: GameRng) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    /// Plays by other rules than the built-in ones
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Opens (creating if needed) the database in the given data directory
+    /// An encrypted data directory is refused, as nothing in the database would be
+    pub fn open(data_dir: &Path) -> io::Result<Self> {
+        if is_encrypted(data_dir) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is encrypted, which only the JSON store supports. Use --store json, or run 'termipet decrypt' first.",
+                    data_dir.display()
+                ),
+            ));
+        }
+        fs::create_dir_all(data_dir)?;
+        let db_path = data_dir.join(DATABASE_FILE);
+        let conn = Connection::open(&db_path).map_err(sql_error)?;
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(sql_error)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS pets (
+                 slug TEXT PRIMARY KEY,
+                 data TEXT NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS settings (
+                 key TEXT PRIMARY KEY,
+                 value TEXT NOT NULL
+             );",
+        )
+        .map_err(sql_error)?;
+        create_history_table(&conn)?;
+        create_journal_table(&conn)?;
+        create_undo_table(&conn)?;
+
+        Ok(Self {
+            data_dir: data_dir.to_path_buf(),
+            db_path,
+            conn,
+            clock: Rc::new(SystemClock),
+            rng: GameRng::default(),
+            rules: Rules::default(),
+            pending_decay: PendingDecay::default(),
+        })
+    }
+
+    /// Turns a stored document back into a pet, migrating old schema versions
+    fn decode(&self, slug: &str, data: &str) -> io::Result<Pet> {
+        match parse_pet(data, self.clock.now()) {
+            Ok((pet, _)) => Ok(pet),
+            Err(PetFileError::TooNew(version)) => Err(too_new_error(&self.db_path, version)),
+            Err(PetFileError::Corrupt(e)) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Pet '{}' in {} is damaged ({})",
+                    slug,
+                    self.db_path.display(),
+                    e
+                ),
+            )),
+        }
+    }
+
+    fn read_row(&self, slug: &str) -> io::Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT data FROM pets WHERE slug = ?1",
+                params![slug],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sql_error)
+    }
+
+    fn read_rows(&self) -> io::Result<Vec<(String, String)>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT slug, data FROM pets ORDER BY slug")
+            .map_err(sql_error)?;
+        let rows = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(sql_error)?;
+        rows.collect::<Result<_, _>>().map_err(sql_error)
+    }
+
+    fn write_active_pet(&self, name: Option<&str>) -> io::Result<()> {
+        match name {
+            Some(name) => self.conn.execute(
+                "INSERT INTO settings (key, value) VALUES (?1, ?2)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![ACTIVE_PET_KEY, name],
+            ),
+            None => self.conn.execute(
+                "DELETE FROM settings WHERE key = ?1",
+                params![ACTIVE_PET_KEY],
+            ),
+        }
+        .map(|_| ())
+        .map_err(sql_error)
+    }
+
+    fn no_such_pet(name: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, format!("No pet named '{}'", name))
+    }
+}
+
+impl PetStore for SqliteStore {
+    fn location(&self) -> String {
+        self.db_path.display().to_string()
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    fn rng(&self) -> &GameRng {
+        &self.rng
+    }
+
+    fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    fn lock(&self) -> io::Result<StoreLock> {
+        lock_data_dir(&self.data_dir).map(StoreLock::from)
+    }
+
+    fn load_pet(&self, name: Option<&str>) -> io::Result<Option<Pet>> {
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => match self.active_pet_name()? {
+                Some(name) => name,
+                None => return Ok(None),
+            },
+        };
+
+        let slug = pet_slug(&name);
+        let Some(data) = self.read_row(&slug)? else {
+            return Ok(None);
+        };
+        let mut pet = self.decode(&slug, &data)?;
+        self.pending_decay
+            .apply(&mut pet, self.clock.as_ref(), &self.rules);
+        Ok(Some(pet))
+    }
+
+    fn decay_summary(&self, name: &str) -> Option<DecaySummary> {
+        self.pending_decay.summary(name)
+    }
+
+    fn save_pet(&self, pet: &Pet) -> io::Result<()> {
+        let slug = pet_slug(&pet.name);
+        if slug.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("'{}' is not a valid pet name", pet.name),
+            ));
+        }
+
+        let mut pet_to_save = pet.clone();
+        pet_to_save.last_updated = self.clock.now();
+        self.conn
+            .execute(
+                "INSERT INTO pets (slug, data) VALUES (?1, ?2)
+                 ON CONFLICT(slug) DO UPDATE SET data = excluded.data",
+                params![slug, serialize_pet(&pet_to_save)?],
+            )
+            .map_err(sql_error)?;
+
+        if self.active_pet_name()?.is_none() {
+            self.write_active_pet(Some(&pet.name))?;
+        }
+
+        if let Some((decay, event)) = self.pending_decay.take(&pet.name) {
+            self.append_event(&event)?;
+            if decay.changed_stats() {
+                self.record(&decay)?;
+                self.push_undo(&UndoStep::new(Action::Decay, pet, decay.at))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn list_pets(&self) -> io::Result<Vec<Pet>> {
+        let mut pets = Vec::new();
+        for (slug, data) in self.read_rows()? {
+            let mut pet = self.decode(&slug, &data)?;
+            self.pending_decay
+                .apply(&mut pet, self.clock.as_ref(), &self.rules);
+            pets.push(pet);
+        }
+        Ok(pets)
+    }
+
+    fn delete_pet(&self, name: &str) -> io::Result<()> {
+        let slug = pet_slug(name);
+        let deleted = self
+            .conn
+            .execute("DELETE FROM pets WHERE slug = ?1", params![slug])
+            .map_err(sql_error)?;
+        if deleted == 0 {
+            return Err(Self::no_such_pet(name));
+        }
+        clear_undo(&self.conn, name)?;
+
+        // Hand the active slot to another pet so commands keep working
+        let was_active = self
+            .active_pet_name()?
+            .is_some_and(|active| pet_slug(&active) == slug);
+        if was_active {
+            let next = self.list_pets()?.into_iter().next();
+            self.write_active_pet(next.as_ref().map(|pet| pet.name.as_str()))?;
+        }
+        Ok(())
+    }
+
+    fn pet_exists(&self, name: &str) -> io::Result<bool> {
+        Ok(self.read_row(&pet_slug(name))?.is_some())
+    }
+
+    fn active_pet_name(&self) -> io::Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1",
+                params![ACTIVE_PET_KEY],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sql_error)
+    }
+
+    fn set_active_pet(&self, name: &str) -> io::Result<()> {
+        let slug = pet_slug(name);
+        let Some(data) = self.read_row(&slug)? else {
+            return Err(Self::no_such_pet(name));
+        };
+
+        // Record the name as the pet spells it, not as it was typed
+        let stored_name = self.decode(&slug, &data).map(|pet| pet.name);
+        self.write_active_pet(Some(stored_name.as_deref().unwrap_or(name)))
+    }
+
+    fn record(&self, entry: &HistoryEntry) -> io::Result<()> {
+        insert_history(&self.conn, entry)
+    }
+
+    fn history(&self, filter: &HistoryFilter) -> io::Result<Vec<HistoryEntry>> {
+        query_history(&self.conn, filter)
+    }
+
+    fn append_event(&self, entry: &JournalEntry) -> io::Result<()> {
+        insert_journal(&self.conn, entry)
+    }
+
+    fn journal(&self) -> io::Result<Vec<JournalEntry>> {
+        query_journal(&self.conn)
+    }
+
+    fn push_undo(&self, step: &UndoStep) -> io::Result<()> {
+        insert_undo(&self.conn, step)
+    }
+
+    fn last_undo(&self, name: &str) -> io::Result<Option<UndoStep>> {
+        latest_undo(&self.conn, name, false)
+    }
+
+    fn pop_undo(&self, name: &str) -> io::Result<Option<UndoStep>> {
+        latest_undo(&self.conn, name, true)
+    }
+
+    fn backup_dir(&self) -> Option<PathBuf> {
+        Some(self.data_dir.join(BACKUP_DIR))
+    }
+
+    fn diagnose(&self, repair: bool) -> io::Result<Vec<Diagnosis>> {
+        let mut findings = Vec::new();
+
+        let integrity: String = self
+            .conn
+            .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+            .map_err(sql_error)?;
+        if integrity != "ok" {
+            findings.push(Diagnosis::report(format!(
+                "{} failed its integrity check ({})",
+                self.db_path.display(),
+                integrity
+            )));
+        }
+
+        for (slug, data) in self.read_rows()? {
+            match parse_pet(&data, self.clock.now()) {
+                Err(PetFileError::TooNew(version)) => {
+                    findings.push(Diagnosis::report(format!(
+                        "Pet '{}' was saved by a newer termipet (schema v{}); upgrade termipet to read it",
+                        slug, version
+                    )));
+                }
+                Err(PetFileError::Corrupt(e)) => {
+                    findings.push(Diagnosis::report(format!(
+                        "Pet '{}' is damaged ({})",
+                        slug, e
+                    )));
+                }
+                Ok((pet, version)) if version < CURRENT_SCHEMA_VERSION => {
+                    let mut finding = Diagnosis::fixable(
+                        format!("Pet '{}' uses save schema v{}", slug, version),
+                        format!("upgrade it to v{}", CURRENT_SCHEMA_VERSION),
+                    );
+                    if repair {
+                        // Rewrite as-is so last_updated (and so decay) is untouched
+                        self.conn
+                            .execute(
+                                "UPDATE pets SET data = ?1 WHERE slug = ?2",
+                                params![serialize_pet(&pet)?, slug],
+                            )
+                            .map_err(sql_error)?;
+                        finding.repaired = true;
+                    }
+                    findings.push(finding);
+                }
+                Ok(_) => {}
+            }
+        }
+
+        // The active pet pointer must name a pet that exists
+        if let Some(active) = self.active_pet_name()?
+            && !self.pet_exists(&active)?
+        {
+            let mut finding = Diagnosis::fixable(
+                format!("Active pet '{}' is not in the roster", active),
+                "make the first pet in the roster active".to_string(),
+            );
+            if repair {
+                let first = self.read_rows()?.into_iter().next();
+                let first_name = first
+                    .and_then(|(slug, data)| self.decode(&slug, &data).ok())
+                    .map(|pet| pet.name);
+                self.write_active_pet(first_name.as_deref())?;
+                finding.repaired = true;
+            }
+            findings.push(finding);
+        }
+
+        Ok(findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sqlite_store_persists_between_opens() {
+        // Given: a pet saved through one connection
+        let temp_dir = TempDir::new().unwrap();
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.hunger = 33;
+        SqliteStore::open(temp_dir.path())
+            .unwrap()
+            .save_pet(&pet)
+            .unwrap();
+
+        // When: the database is opened again
+        let store = SqliteStore::open(temp_dir.path()).unwrap();
+
+        // Then: the pet and the active pointer are still there
+        assert_eq!(store.load_pet(None).unwrap().unwrap().hunger, 33);
+        assert!(temp_dir.path().join(DATABASE_FILE).exists());
+    }
+
+    #[test]
+    fn test_sqlite_store_doctor_repairs_dangling_active_pet() {
+        // Given: an active pointer to a pet that isn't stored
+        let temp_dir = TempDir::new().unwrap();
+        let store = SqliteStore::open(temp_dir.path()).unwrap();
+        store
+            .save_pet(&Pet::new("Kylo".to_string(), "dog".to_string()))
+            .unwrap();
+        store.write_active_pet(Some("Ghost")).unwrap();
+
+        // When: running the doctor with repair
+        let findings = store.diagnose(true).unwrap();
+
+        // Then: the pointer is moved to a real pet
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].repaired);
+        assert_eq!(store.active_pet_name().unwrap().as_deref(), Some("Kylo"));
+    }
+
+    #[test]
+    fn test_sqlite_store_refuses_encrypted_data_dir() {
+        // Given: a data directory encrypted for the JSON store
+        let temp_dir = TempDir::new().unwrap();
+        crate::persistence::crypto::create_key_file(
+            temp_dir.path(),
+            "hunter2",
+            crate::persistence::crypto::test_params(),
+        )
+        .unwrap();
+
+        // When: opening the SQLite store in it
+        let error = SqliteStore::open(temp_dir.path()).unwrap_err();
+
+        // Then: it is refused before a plain database is written
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(!temp_dir.path().join(DATABASE_FILE).exists());
+    }
+}
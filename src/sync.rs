@@ -0,0 +1,116 @@
+use crate::journal::{JournalEntry, read_file};
+use crate::persistence::write_atomic;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The journal kept in a sync directory, shared by every machine syncing with it
+pub const SYNC_JOURNAL: &str = "journal.jsonl";
+
+/// Two journals merged into one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merged {
+    /// Every entry from both journals once, in time order
+    pub journal: Vec<JournalEntry>,
+    /// Entries only the other journal had, in time order
+    pub received: Vec<JournalEntry>,
+    /// How many entries only the local journal had
+    pub sent: usize,
+}
+
+fn entry_key(entry: &JournalEntry) -> io::Result<String> {
+    serde_json::to_string(entry).map_err(io::Error::other)
+}
+
+/// Merges the local journal with another machine's, keeping entries both have once
+/// and ordering the rest by when they happened rather than where they came from
+pub fn merge_journals(local: &[JournalEntry], other: &[JournalEntry]) -> io::Result<Merged> {
+    let local_keys = local
+        .iter()
+        .map(entry_key)
+        .collect::<io::Result<HashSet<_>>>()?;
+    let other_keys = other
+        .iter()
+        .map(entry_key)
+        .collect::<io::Result<HashSet<_>>>()?;
+
+    let mut journal = local.to_vec();
+    let mut received = Vec::new();
+    let mut seen = local_keys.clone();
+    for entry in other {
+        if seen.insert(entry_key(entry)?) {
+            journal.push(entry.clone());
+            received.push(entry.clone());
+        }
+    }
+    // Stable sorts keep an accident ahead of the walk recorded at the same moment
+    journal.sort_by_key(|entry| entry.at);
+    received.sort_by_key(|entry| entry.at);
+
+    let sent = local_keys.difference(&other_keys).count();
+    Ok(Merged {
+        journal,
+        received,
+        sent,
+    })
+}
+
+/// Reads the journal in a sync directory (empty if nothing has synced yet)
+/// Encrypted stores can't sync, so the directory stands in for a data dir
+pub fn read_sync_journal(dir: &Path) -> io::Result<Vec<JournalEntry>> {
+    read_file(dir, &dir.join(SYNC_JOURNAL))
+}
+
+/// Replaces the journal in a sync directory in one step, so a sync tool never
+/// picks up half of it
+pub fn write_sync_journal(dir: &Path, entries: &[JournalEntry]) -> io::Result<()> {
+    let mut contents = String::new();
+    for entry in entries {
+        contents.push_str(&entry_key(entry)?);
+        contents.push('\n');
+    }
+    fs::create_dir_all(dir)?;
+    write_atomic(&dir.join(SYNC_JOURNAL), contents.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::Event;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_merge_interleaves_by_time_and_keeps_shared_entries_once() {
+        // Given: an adoption both machines have, then actions on each
+        let start = Utc::now() - Duration::hours(3);
+        let entry = |hours: i64, event: Event| JournalEntry {
+            at: start + Duration::hours(hours),
+            pet: "Kylo".to_string(),
+            event,
+        };
+        let adopted = entry(
+            0,
+            Event::Adopted {
+                species: "dog".to_string(),
+                difficulty: Default::default(),
+            },
+        );
+        let local = vec![
+            adopted.clone(),
+            entry(1, Event::Fed),
+            entry(3, Event::Cleaned),
+        ];
+        let other = vec![adopted, entry(2, Event::Played)];
+
+        // When: merging them
+        let merged = merge_journals(&local, &other).unwrap();
+
+        // Then: the other machine's play lands between the feed and the clean
+        let events: Vec<&Event> = merged.journal.iter().map(|entry| &entry.event).collect();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[2], &Event::Played);
+        assert_eq!(merged.received, vec![entry(2, Event::Played)]);
+        assert_eq!(merged.sent, 2);
+    }
+}
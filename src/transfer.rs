@@ -0,0 +1,202 @@
+use crate::actions::{REST_SECONDS_PER_POINT, XP_PER_LEVEL};
+use crate::persistence::{PetFileError, parse_pet, serialize_pet};
+use crate::pet::{Pet, validate_name};
+use crate::vacation::SITTER_VISIT_SECONDS;
+use chrono::{DateTime, Utc};
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// File formats a pet can be exported to and imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    /// Guesses the format from a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "yml" => Some(Format::Yaml),
+            other => other.parse().ok(),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "toml" => Ok(Format::Toml),
+            "yaml" => Ok(Format::Yaml),
+            other => Err(format!(
+                "unknown format '{}' (expected 'json', 'toml' or 'yaml')",
+                other
+            )),
+        }
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Writes a pet as a portable document: the same versioned document as a save
+/// file, including `last_updated` so decay carries on where it left off
+pub fn encode_pet(pet: &Pet, format: Format) -> io::Result<String> {
+    let json = serialize_pet(pet)?;
+    if format == Format::Json {
+        return Ok(json);
+    }
+
+    let document: serde_json::Value =
+        serde_json::from_str(&json).map_err(|e| invalid(e.to_string()))?;
+    match format {
+        Format::Toml => toml::to_string_pretty(&document).map_err(|e| invalid(e.to_string())),
+        Format::Yaml => serde_yaml::to_string(&document).map_err(|e| invalid(e.to_string())),
+        Format::Json => unreachable!("handled above"),
+    }
+}
+
+/// Reads a pet from a portable document, upgrading old schema versions and
+/// rejecting anything a real pet couldn't have
+/// `now` stands in for the last update of a pet exported before it was recorded
+pub fn decode_pet(contents: &str, format: Format, now: DateTime<Utc>) -> io::Result<Pet> {
+    let document: serde_json::Value = match format {
+        Format::Json => serde_json::from_str(contents).map_err(|e| invalid(e.to_string()))?,
+        Format::Toml => toml::from_str(contents).map_err(|e| invalid(e.to_string()))?,
+        Format::Yaml => serde_yaml::from_str(contents).map_err(|e| invalid(e.to_string()))?,
+    };
+
+    let pet = match parse_pet(&document.to_string(), now) {
+        Ok((pet, _)) => pet,
+        Err(PetFileError::TooNew(version)) => {
+            return Err(invalid(format!(
+                "This pet was exported by a newer termipet (schema v{}). Please upgrade termipet.",
+                version
+            )));
+        }
+        Err(PetFileError::Corrupt(e)) => {
+            return Err(invalid(format!("Not a termipet pet ({})", e)));
+        }
+    };
+    validate_pet(&pet).map_err(invalid)?;
+    Ok(pet)
+}
+
+/// Checks that every stat is in the range the game keeps it in
+pub fn validate_pet(pet: &Pet) -> Result<(), String> {
+    validate_name(&pet.name).map_err(|e| format!("{} ('{}')", e, pet.name))?;
+    if pet.species.trim().is_empty() {
+        return Err(format!("{} has no species", pet.name));
+    }
+
+    let percentages = [
+        ("hunger", pet.hunger),
+        ("happiness", pet.happiness),
+        ("energy", pet.energy),
+        ("cleanliness", pet.cleanliness),
+        ("potty_level", pet.potty_level),
+    ];
+    for (stat, value) in percentages {
+        if value > 100 {
+            return Err(format!(
+                "{}'s {} is {} (must be 0-100)",
+                pet.name, stat, value
+            ));
+        }
+    }
+    if pet.xp >= XP_PER_LEVEL {
+        return Err(format!(
+            "{}'s xp is {} (must be below {})",
+            pet.name, pet.xp, XP_PER_LEVEL
+        ));
+    }
+    if pet.level == 0 {
+        return Err(format!("{}'s level is 0 (must be at least 1)", pet.name));
+    }
+    if pet.decay_progress as u64 >= pet.difficulty.decay_interval() {
+        return Err(format!(
+            "{}'s decay_progress is {} (must be below {})",
+            pet.name,
+            pet.decay_progress,
+            pet.difficulty.decay_interval()
+        ));
+    }
+    if pet.rest_progress as u64 >= REST_SECONDS_PER_POINT {
+        return Err(format!(
+            "{}'s rest_progress is {} (must be below {})",
+            pet.name, pet.rest_progress, REST_SECONDS_PER_POINT
+        ));
+    }
+    if let Some(vacation) = &pet.vacation
+        && vacation.visit_progress >= SITTER_VISIT_SECONDS
+    {
+        return Err(format!(
+            "{}'s vacation visit_progress is {} (must be below {})",
+            pet.name, vacation.visit_progress, SITTER_VISIT_SECONDS
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_pet_round_trips_through_every_format() {
+        // Given: a pet last looked after a day ago
+        let mut pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        pet.hunger = 42;
+        pet.level = 3;
+        pet.last_updated = Utc::now() - Duration::days(1);
+
+        for format in [Format::Json, Format::Toml, Format::Yaml] {
+            // When: exporting and importing it
+            let document = encode_pet(&pet, format).unwrap();
+            let imported = decode_pet(&document, format, Utc::now()).unwrap();
+
+            // Then: nothing is lost, including when it was last updated
+            assert_eq!(imported, pet, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_import_rejects_out_of_range_stats() {
+        // Given: documents with impossible stats
+        let pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        let document = encode_pet(&pet, Format::Yaml).unwrap();
+
+        // When/Then: they are refused with the offending stat named
+        let hungry = document.replace("hunger: 80", "hunger: 180");
+        let error = decode_pet(&hungry, Format::Yaml, Utc::now()).unwrap_err();
+        assert!(error.to_string().contains("hunger is 180"));
+
+        let negative = document.replace("energy: 80", "energy: -5");
+        assert!(decode_pet(&negative, Format::Yaml, Utc::now()).is_err());
+
+        let nameless = document.replace("name: Kylo", "name: '!!'");
+        assert!(decode_pet(&nameless, Format::Yaml, Utc::now()).is_err());
+    }
+
+    #[test]
+    fn test_format_from_name_or_extension() {
+        // Given/When/Then: formats parse by name and are guessed from extensions
+        assert_eq!("TOML".parse::<Format>().unwrap(), Format::Toml);
+        assert!("xml".parse::<Format>().is_err());
+        assert_eq!(Format::from_path(Path::new("kylo.yml")), Some(Format::Yaml));
+        assert_eq!(
+            Format::from_path(Path::new("kylo.json")),
+            Some(Format::Json)
+        );
+        assert_eq!(Format::from_path(Path::new("kylo")), None);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::history::Action;
+use crate::persistence::pet_slug;
+use crate::pet::Pet;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// How many actions per pet can be undone
+pub const UNDO_DEPTH: usize = 10;
+
+/// A pet as it was just before an action, kept so the action can be undone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UndoStep {
+    /// The action this step undoes
+    pub action: Action,
+    /// When the action happened
+    pub at: DateTime<Utc>,
+    /// The pet before the action
+    pub pet: Pet,
+}
+
+impl UndoStep {
+    /// A step for an action that happened at `at` to a pet that looked like `before`
+    pub fn new(action: Action, before: &Pet, at: DateTime<Utc>) -> Self {
+        Self {
+            action,
+            at,
+            pet: before.clone(),
+        }
+    }
+
+    /// Adoptions, restores, imports, syncs, vacations and decay can't be undone, and nothing
+    /// older than them can be either
+    pub fn is_barrier(&self) -> bool {
+        matches!(
+            self.action,
+            Action::Adopt
+                | Action::Restore
+                | Action::Import
+                | Action::Sync
+                | Action::Vacation
+                | Action::Decay
+        )
+    }
+}
+
+/// Adds a step to a pet's undo stack, dropping the oldest beyond `UNDO_DEPTH`
+pub(crate) fn push_step(steps: &mut Vec<UndoStep>, step: UndoStep) {
+    steps.push(step);
+    if steps.len() > UNDO_DEPTH {
+        steps.drain(..steps.len() - UNDO_DEPTH);
+    }
+}
+
+fn sql_error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+/// Creates the undo table if the database doesn't have it yet
+pub(crate) fn create_undo_table(conn: &Connection) -> io::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS undo (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             pet_slug TEXT NOT NULL,
+             step TEXT NOT NULL
+         );",
+    )
+    .map_err(sql_error)
+}
+
+/// Adds a step to the pet's undo stack in the table, keeping only `UNDO_DEPTH` steps
+pub(crate) fn insert_undo(conn: &Connection, step: &UndoStep) -> io::Result<()> {
+    let slug = pet_slug(&step.pet.name);
+    let json = serde_json::to_string(step).map_err(io::Error::other)?;
+    conn.execute(
+        "INSERT INTO undo (pet_slug, step) VALUES (?1, ?2)",
+        params![slug, json],
+    )
+    .map_err(sql_error)?;
+    conn.execute(
+        "DELETE FROM undo WHERE pet_slug = ?1 AND id NOT IN
+             (SELECT id FROM undo WHERE pet_slug = ?1 ORDER BY id DESC LIMIT ?2)",
+        params![slug, UNDO_DEPTH as i64],
+    )
+    .map(|_| ())
+    .map_err(sql_error)
+}
+
+/// Returns the pet's most recent undo step, removing it if `remove` is set
+pub(crate) fn latest_undo(
+    conn: &Connection,
+    name: &str,
+    remove: bool,
+) -> io::Result<Option<UndoStep>> {
+    let row: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT id, step FROM undo WHERE pet_slug = ?1 ORDER BY id DESC LIMIT 1",
+            params![pet_slug(name)],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(sql_error)?;
+    let Some((id, json)) = row else {
+        return Ok(None);
+    };
+
+    if remove {
+        conn.execute("DELETE FROM undo WHERE id = ?1", params![id])
+            .map_err(sql_error)?;
+    }
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Forgets a pet's undo stack, e.g. when it is released
+pub(crate) fn clear_undo(conn: &Connection, name: &str) -> io::Result<()> {
+    conn.execute(
+        "DELETE FROM undo WHERE pet_slug = ?1",
+        params![pet_slug(name)],
+    )
+    .map(|_| ())
+    .map_err(sql_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_stack_keeps_only_the_most_recent_steps() {
+        // Given: a full undo stack
+        let pet = Pet::new("Kylo".to_string(), "dog".to_string());
+        let mut steps = Vec::new();
+        for _ in 0..UNDO_DEPTH {
+            push_step(&mut steps, UndoStep::new(Action::Feed, &pet, Utc::now()));
+        }
+
+        // When: one more action is remembered
+        push_step(&mut steps, UndoStep::new(Action::Train, &pet, Utc::now()));
+
+        // Then: the oldest step is dropped
+        assert_eq!(steps.len(), UNDO_DEPTH);
+        assert_eq!(steps.last().unwrap().action, Action::Train);
+    }
+}
@@ -1,4 +1,4 @@
-use rand::Rng;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
 
 /// Caps a stat value between min and max bounds
 /// Useful for ensuring pet stats stay within valid ranges (0-100)
@@ -12,11 +12,37 @@ pub fn cap_stat(value: i32, min: u8, max: u8) -> u8 {
     }
 }
 
-/// Returns true with the given probability (0.0 to 1.0)
-/// Used for probabilistic pet behaviors like potty relief during walks
-pub fn random_bool(probability: f32) -> bool {
-    let mut rng = rand::thread_rng();
-    rng.r#gen::<f32>() < probability
+/// Parses a point in time given on the command line: a relative age such as
+/// "30d", "12h" or "2w", a calendar date such as "2025-10-01", or an RFC 3339
+/// timestamp such as "2025-10-01T18:30:00Z"
+pub fn parse_time(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    let invalid = || {
+        format!(
+            "'{}' is not a valid time (use e.g. 30d, 12h, 2w, 2025-10-01 or 2025-10-01T18:30:00Z)",
+            value
+        )
+    };
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?.and_utc());
+    }
+
+    // The unit may not be ASCII, so split on a character boundary
+    let (split, _) = value.char_indices().last().ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let age = match unit {
+        "h" => TimeDelta::try_hours(amount),
+        "d" => TimeDelta::try_days(amount),
+        "w" => TimeDelta::try_weeks(amount),
+        _ => return Err(invalid()),
+    };
+    age.and_then(|age| now.checked_sub_signed(age))
+        .ok_or_else(invalid)
 }
 
 #[cfg(test)]
@@ -65,44 +91,37 @@ mod tests {
     }
 
     #[test]
-    fn test_random_bool_probability_distribution() {
-        // Given: a probability of 0.8 (80%)
-        let probability = 0.8;
-        let iterations = 1000;
-        let mut true_count = 0;
-
-        // When: calling random_bool many times
-        for _ in 0..iterations {
-            if random_bool(probability) {
-                true_count += 1;
-            }
-        }
-
-        // Then: approximately 80% should be true (within reasonable tolerance)
-        let actual_probability = true_count as f32 / iterations as f32;
-        // Allow 10% tolerance (0.7 to 0.9 range for 0.8 expected)
-        assert!(
-            actual_probability >= 0.7 && actual_probability <= 0.9,
-            "Expected ~0.8, got {}",
-            actual_probability
+    fn test_parse_time_accepts_relative_calendar_and_exact_times() {
+        // Given: a fixed "now"
+        let now = DateTime::parse_from_rfc3339("2025-10-31T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        // When/Then: relative ages count back from now, dates start at midnight UTC
+        assert_eq!(parse_time("30d", now).unwrap(), now - TimeDelta::days(30));
+        assert_eq!(parse_time("12h", now).unwrap(), now - TimeDelta::hours(12));
+        assert_eq!(
+            parse_time("2025-10-01", now).unwrap().to_rfc3339(),
+            "2025-10-01T00:00:00+00:00"
         );
+        assert_eq!(
+            parse_time("2025-10-01T19:30:00+01:00", now)
+                .unwrap()
+                .to_rfc3339(),
+            "2025-10-01T18:30:00+00:00"
+        );
+        assert!(parse_time("last month", now).is_err());
+        assert!(parse_time("", now).is_err());
     }
 
     #[test]
-    fn test_random_bool_always_true() {
-        // Given: probability of 1.0
-        // Then: should always return true
-        for _ in 0..100 {
-            assert!(random_bool(1.0));
-        }
-    }
-
-    #[test]
-    fn test_random_bool_never_true() {
-        // Given: probability of 0.0
-        // Then: should always return false
-        for _ in 0..100 {
-            assert!(!random_bool(0.0));
-        }
+    fn test_parse_time_refuses_odd_units_and_huge_ages() {
+        // Given: a fixed "now"
+        let now = Utc::now();
+
+        // When/Then: a multibyte unit and ages too big for a date are errors, not panics
+        assert!(parse_time("3é", now).is_err());
+        assert!(parse_time("99999999999999d", now).is_err());
+        assert!(parse_time("9999999999w", now).is_err());
     }
 }
@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Seconds between a pet-sitter's visits: twice a day
+pub const SITTER_VISIT_SECONDS: u32 = 12 * 3600;
+
+/// A break from looking after a pet, recorded on the pet while it lasts
+/// Without a sitter the pet's stats are frozen; with one, decay carries on but
+/// the sitter feeds and walks the pet every `SITTER_VISIT_SECONDS`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vacation {
+    pub since: DateTime<Utc>,
+    #[serde(default)]
+    pub sitter: bool,
+    /// Seconds since the sitter's last visit, or since the vacation started
+    #[serde(default)]
+    pub visit_progress: u32,
+}
+
+impl Vacation {
+    pub fn new(since: DateTime<Utc>, sitter: bool) -> Self {
+        Self {
+            since,
+            sitter,
+            visit_progress: 0,
+        }
+    }
+
+    /// Seconds until the sitter's next visit, or None if there is no sitter
+    pub fn until_visit(&self) -> Option<u64> {
+        self.sitter
+            .then(|| SITTER_VISIT_SECONDS.saturating_sub(self.visit_progress) as u64)
+    }
+}
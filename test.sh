@@ -1,12 +1,10 @@
 #!/bin/bash
-# test.sh - Runs all termiPet tests correctly
+# test.sh - Runs all termiPet tests
 #
-# This script handles the race condition in train command tests by running
-# tests with --test-threads=1 to ensure sequential execution.
+# Every test uses its own temporary data directory, so tests run in parallel.
 #
 # Usage:
 #   ./test.sh              # Run all tests
-#   ./test.sh --fast       # Run non-train tests in parallel, then train tests
 #   ./test.sh --verbose    # Run all tests with output
 #   ./test.sh --help       # Show this help
 
@@ -43,35 +41,20 @@ show_help() {
     echo "Usage: ./test.sh [OPTIONS]"
     echo ""
     echo "Options:"
-    echo "  --fast      Run non-train tests in parallel, then train tests sequentially"
-    echo "              (faster but more complex)"
     echo "  --verbose   Run all tests with output (shows println! statements)"
     echo "  --help      Show this help message"
     echo ""
-    echo "Default (no options): Runs all tests sequentially with --test-threads=1"
-    echo ""
-    echo "Why --test-threads=1?"
-    echo "  The train command tests modify the global HOME environment variable,"
-    echo "  which causes race conditions when tests run in parallel. Running"
-    echo "  sequentially ensures each test has exclusive environment access."
-    echo ""
     echo "Examples:"
-    echo "  ./test.sh              # Safe, comprehensive (all tests sequential)"
-    echo "  ./test.sh --fast       # Faster (parallel + sequential)"
+    echo "  ./test.sh              # Run all tests"
     echo "  ./test.sh --verbose    # Debug test output"
     exit 0
 }
 
 # Parse arguments
-FAST_MODE=false
 VERBOSE_MODE=false
 
 for arg in "$@"; do
     case $arg in
-        --fast)
-            FAST_MODE=true
-            shift
-            ;;
         --verbose)
             VERBOSE_MODE=true
             shift
@@ -92,30 +75,10 @@ echo ""
 print_info "Running termiPet tests..."
 echo ""
 
-if [ "$FAST_MODE" = true ]; then
-    print_info "Fast mode: Running non-train tests in parallel..."
-
-    if cargo test --lib -- --skip train; then
-        print_success "Non-train tests passed"
-    else
-        print_error "Non-train tests failed"
-        exit 1
-    fi
-
-    echo ""
-    print_info "Running train tests sequentially (--test-threads=1)..."
-
-    if cargo test train -- --test-threads=1; then
-        print_success "Train tests passed"
-    else
-        print_error "Train tests failed"
-        exit 1
-    fi
-
-elif [ "$VERBOSE_MODE" = true ]; then
-    print_info "Verbose mode: Running all tests sequentially with output..."
+if [ "$VERBOSE_MODE" = true ]; then
+    print_info "Verbose mode: Running all tests with output..."
 
-    if cargo test --lib -- --test-threads=1 --nocapture; then
+    if cargo test -- --nocapture; then
         print_success "All tests passed"
     else
         print_error "Tests failed"
@@ -123,9 +86,7 @@ elif [ "$VERBOSE_MODE" = true ]; then
     fi
 
 else
-    print_info "Running all tests sequentially (--test-threads=1)..."
-
-    if cargo test --lib -- --test-threads=1; then
+    if cargo test; then
         print_success "All tests passed"
     else
         print_error "Tests failed"